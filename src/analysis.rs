@@ -0,0 +1,614 @@
+use crate::runtime::rtree::{Resolved, RuntimeTree};
+use crate::tree::diagnostic::{Diagnostic, Span};
+use crate::tree::parser::ast::arg::{ArgumentRhs, Arguments, MesType};
+use crate::tree::parser::ast::call::Call;
+use crate::tree::parser::ast::message::{Message, Number};
+use crate::tree::parser::ast::{ImportName, Key, Tree, TreeType};
+use crate::tree::project::file::File;
+use crate::tree::project::imports::{local_names, ImportMap, STD};
+use crate::tree::project::{libs, FileName, Project, TreeName};
+use crate::tree::TreeError;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use strum_macros::Display;
+
+/// The rule of the analysis that produced the warning.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[strum(serialize_all = "snake_case")]
+pub enum Rule {
+    /// the child of a fallback placed after the child that never fails
+    UnreachableChild,
+    /// the definition that is not reachable from any root of the project
+    UnusedDefinition,
+    /// the imported file, name or alias that is not used in the file
+    UnusedImport,
+    /// the decorator wrapping an empty flow or cancelling the nested decorator
+    UselessDecorator,
+    /// the infinite repeat over the children that never return running
+    InfiniteRepeat,
+    /// the alias hiding another imported name or hidden by it
+    ShadowedAlias,
+    /// the root that can not be built into the runtime tree
+    UnbuiltRoot,
+}
+
+/// The warning of the analysis.
+/// The diagnostic points to the place in the source and keeps the rule in the note:
+/// ```text
+/// warning: the definition unused is not used by any root
+///  --> main.tree:5:1
+///   |
+/// 5 | impl unused();
+///   | ^^^^^^^^^^^^^^
+///   = note: lint unused_definition
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    pub rule: Rule,
+    pub diagnostic: Diagnostic,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.diagnostic)
+    }
+}
+
+/// Analyzes the project and reports the warnings sorted by the files and the positions.
+/// Every root of the project is built into the runtime tree to find out the used definitions,
+/// see [`analyze_resolved`].
+/// The root that can not be built is reported with the rule [`Rule::UnbuiltRoot`]
+/// and the other roots are analyzed further.
+pub fn analyze(project: &Project) -> Result<Vec<Lint>, TreeError> {
+    let mut resolved = vec![];
+    let mut unbuilt = vec![];
+    for file in project.files.values().sorted_by_key(|f| &f.name) {
+        for root in file.definitions.values().filter(|t| t.is_root()) {
+            match RuntimeTree::build_root(project, &file.name, &root.name) {
+                Ok((_, r)) => resolved.push(r),
+                Err(e) => unbuilt.extend(unbuilt_root(project, file, root, e)),
+            }
+        }
+    }
+    let lints = analyze_resolved(project, &resolved);
+    Ok(sorted(lints.into_iter().chain(unbuilt).collect()))
+}
+
+/// the errors of the root that can not be built, pointing to the places they occur
+fn unbuilt_root(project: &Project, file: &File, root: &Tree, e: TreeError) -> Vec<Lint> {
+    let note = format!("lint {}", Rule::UnbuiltRoot);
+    let lint = |diagnostic: Diagnostic| Lint {
+        rule: Rule::UnbuiltRoot,
+        diagnostic: diagnostic.with_note(note.clone()),
+    };
+    match e {
+        TreeError::Diagnostics(ds) => ds.into_iter().map(lint).collect(),
+        e => {
+            let src = project
+                .sources
+                .get(&file.name)
+                .map(String::as_str)
+                .unwrap_or_default();
+            let msg = format!("the root {} can not be built: {e}", root.name);
+            vec![lint(Diagnostic::new(&file.name, src, root.span, msg))]
+        }
+    }
+}
+
+/// Analyzes the project against the definitions the runtime trees are resolved to,
+/// see [`RuntimeTree::build_resolved`].
+/// The definitions that are not resolved by any of the trees are reported as unused.
+pub fn analyze_resolved(project: &Project, resolved: &[Resolved]) -> Vec<Lint> {
+    let analyzer = Analyzer::new(project);
+    let used: HashSet<&(FileName, TreeName)> =
+        resolved.iter().flat_map(|r| r.definitions.iter()).collect();
+    let mut lints = vec![];
+    for file in project.files.values().filter(|f| f.name != STD) {
+        analyzer.check_file(file, &used, &mut lints);
+    }
+    sorted(lints)
+}
+
+/// the lints sorted by the files and the positions
+fn sorted(lints: Vec<Lint>) -> Vec<Lint> {
+    lints
+        .into_iter()
+        .sorted_by(|a, b| {
+            let pos = |l: &Lint| {
+                (
+                    l.diagnostic.file.clone(),
+                    l.diagnostic.line,
+                    l.diagnostic.column,
+                )
+            };
+            pos(a).cmp(&pos(b))
+        })
+        .collect()
+}
+
+struct Analyzer<'a> {
+    project: &'a Project,
+    import_maps: HashMap<&'a FileName, ImportMap>,
+}
+
+/// the definitions being checked, to stop on the recursive trees
+type Visited<'a> = Vec<(&'a FileName, &'a TreeName)>;
+
+impl<'a> Analyzer<'a> {
+    fn new(project: &'a Project) -> Self {
+        let import_maps = project
+            .files
+            .values()
+            .flat_map(|f| ImportMap::build(f).ok().map(|m| (&f.name, m)))
+            .collect();
+        Analyzer {
+            project,
+            import_maps,
+        }
+    }
+
+    fn lint(&self, file: &File, rule: Rule, span: Span, msg: String) -> Lint {
+        let src = self
+            .project
+            .sources
+            .get(&file.name)
+            .map(String::as_str)
+            .unwrap_or_default();
+        Lint {
+            rule,
+            diagnostic: Diagnostic::warning(&file.name, src, span, msg)
+                .with_note(format!("lint {rule}")),
+        }
+    }
+
+    /// finds the definition called in the file, either in the file or in the imports
+    fn resolve(&self, file: &'a FileName, name: &Key) -> Option<(&'a Tree, &'a FileName)> {
+        let f = self.project.files.get(file)?;
+        match f.definitions.get(name) {
+            Some(tree) => Some((tree, &f.name)),
+            None => {
+                // the alias is resolved into the name of the definition
+                let (tree, def_file) = self.import_maps.get(file)?.find(name, self.project).ok()?;
+                let def_file = self.project.files.get(def_file)?;
+                def_file
+                    .definitions
+                    .get(&tree.name)
+                    .map(|tree| (tree, &def_file.name))
+            }
+        }
+    }
+
+    fn check_file(
+        &self,
+        file: &'a File,
+        used: &HashSet<&(FileName, TreeName)>,
+        lints: &mut Vec<Lint>,
+    ) {
+        // the libraries are shared between the projects, so not every definition is used in one of them
+        let is_lib = libs::package(&file.name).is_some();
+        for tree in file.definitions.values() {
+            let key = (file.name.clone(), tree.name.clone());
+            if !is_lib && !tree.is_root() && !used.contains(&key) {
+                lints.push(self.lint(
+                    file,
+                    Rule::UnusedDefinition,
+                    tree.span,
+                    format!("the definition {} is not used by any root", tree.name),
+                ));
+            }
+            if matches!(tree.tpe, TreeType::Fallback | TreeType::RFallback) {
+                self.check_fallback(file, &tree.calls.elems, lints);
+            }
+            for call in tree.calls.elems.iter() {
+                self.check_call(file, call, lints);
+            }
+        }
+        self.check_imports(file, lints);
+    }
+
+    fn check_call(&self, file: &'a File, call: &'a Call, lints: &mut Vec<Lint>) {
+        match call {
+            Call::Invocation(_, args, _) => self.check_args(file, args, lints),
+            Call::HoInvocation(_, _) => {}
+            Call::Lambda(tpe, calls, _) => {
+                if matches!(tpe, TreeType::Fallback | TreeType::RFallback) {
+                    self.check_fallback(file, &calls.elems, lints);
+                }
+                for call in calls.elems.iter() {
+                    self.check_call(file, call, lints);
+                }
+            }
+            Call::Decorator(tpe, args, child, span) => {
+                self.check_decorator(file, &tpe.to_string(), child, *span, lints);
+                self.check_nested_decorator(file, tpe, child, *span, lints);
+                if *tpe == TreeType::Repeat
+                    && is_infinite(args)
+                    && !self.can_run(&file.name, child, &mut vec![])
+                {
+                    lints.push(self.lint(
+                        file,
+                        Rule::InfiniteRepeat,
+                        *span,
+                        "the repeat is infinite but none of its children can return running, so the tree spins without waiting".to_string(),
+                    ));
+                }
+                self.check_args(file, args, lints);
+                self.check_call(file, child, lints);
+            }
+            Call::CustomDecorator(name, args, child, span) => {
+                self.check_decorator(file, name, child, *span, lints);
+                self.check_args(file, args, lints);
+                self.check_call(file, child, lints);
+            }
+        }
+    }
+
+    fn check_args(&self, file: &'a File, args: &'a Arguments, lints: &mut Vec<Lint>) {
+        for arg in args.args.iter() {
+            if let ArgumentRhs::Call(call) = arg.value() {
+                self.check_call(file, call, lints);
+            }
+        }
+    }
+
+    /// the children after the child that never fails are never ticked
+    fn check_fallback(&self, file: &'a File, children: &'a [Call], lints: &mut Vec<Lint>) {
+        if let Some(idx) = children
+            .iter()
+            .position(|c| self.never_fails(&file.name, c, &mut vec![]))
+        {
+            for child in children.iter().skip(idx + 1) {
+                lints.push(self.lint(
+                    file,
+                    Rule::UnreachableChild,
+                    child.span(),
+                    "the child is unreachable since the previous child of the fallback never fails"
+                        .to_string(),
+                ));
+            }
+        }
+    }
+
+    fn check_decorator(
+        &self,
+        file: &File,
+        name: &str,
+        child: &Call,
+        span: Span,
+        lints: &mut Vec<Lint>,
+    ) {
+        if let Call::Lambda(tpe, calls, _) = child {
+            if calls.elems.is_empty() {
+                lints.push(self.lint(
+                    file,
+                    Rule::UselessDecorator,
+                    span,
+                    format!("the decorator {name} wraps the empty {tpe}"),
+                ));
+            }
+        }
+    }
+
+    /// the nested inverters cancel each other
+    /// and the forced result overrides the result of the nested decorators changing it
+    fn check_nested_decorator(
+        &self,
+        file: &File,
+        tpe: &TreeType,
+        child: &Call,
+        span: Span,
+        lints: &mut Vec<Lint>,
+    ) {
+        if let Call::Decorator(inner, _, _, _) = child {
+            let msg = match (tpe, inner) {
+                (TreeType::Inverter, TreeType::Inverter) => {
+                    "the decorator inverter cancels the nested inverter".to_string()
+                }
+                (
+                    TreeType::ForceSuccess | TreeType::ForceFail,
+                    TreeType::ForceSuccess | TreeType::ForceFail | TreeType::Inverter,
+                ) => format!("the decorator {tpe} overrides the result of the nested {inner}"),
+                _ => return,
+            };
+            lints.push(self.lint(file, Rule::UselessDecorator, span, msg));
+        }
+    }
+
+    fn check_imports(&self, file: &'a File, lints: &mut Vec<Lint>) {
+        let referred = referred_names(file);
+        let defines_const = |f_name: &FileName, name: &Key| {
+            self.project
+                .files
+                .get(f_name)
+                .map(|f| f.consts.contains_key(name))
+                .unwrap_or(false)
+        };
+
+        for f_name in file.imports.keys().sorted() {
+            let span = file.import_spans.get(f_name).copied().unwrap_or_default();
+            let imported = self.project.files.get(f_name);
+            for name in file.imports[f_name]
+                .iter()
+                .sorted_by_key(|n| format!("{n:?}"))
+            {
+                let msg = match name {
+                    // the constants are inlined before the analysis, so the files with them are skipped
+                    ImportName::WholeFile => match imported {
+                        Some(f) if f.consts.is_empty() => {
+                            let used = local_names(f)
+                                .any(|n| referred.contains(n) && !file.definitions.contains_key(n));
+                            (!used).then(|| format!("the import of the file {f_name} is not used"))
+                        }
+                        _ => None,
+                    },
+                    ImportName::Id(n) => (!referred.contains(n) && !defines_const(f_name, n))
+                        .then(|| format!("the imported name {n} is not used")),
+                    ImportName::Alias(n, alias) => {
+                        if let Some(msg) = self.shadowed_alias(file, f_name, alias) {
+                            lints.push(self.lint(file, Rule::ShadowedAlias, span, msg));
+                        }
+                        (!referred.contains(alias) && !defines_const(f_name, n)).then(|| {
+                            format!("the alias {alias} of the imported name {n} is not used")
+                        })
+                    }
+                };
+                if let Some(msg) = msg {
+                    lints.push(self.lint(file, Rule::UnusedImport, span, msg));
+                }
+            }
+        }
+    }
+
+    /// the alias is hidden by the same name imported from another file,
+    /// or hides the name of another file imported as a whole
+    fn shadowed_alias(&self, file: &File, alias_file: &FileName, alias: &Key) -> Option<String> {
+        file.imports
+            .iter()
+            .filter(|(f_name, _)| *f_name != alias_file)
+            .sorted_by_key(|(f_name, _)| *f_name)
+            .find_map(|(f_name, names)| {
+                if names.contains(&ImportName::Id(alias.clone())) {
+                    Some(format!(
+                        "the alias {alias} is shadowed by the name imported from the file {f_name}"
+                    ))
+                } else if names.contains(&ImportName::WholeFile)
+                    && self
+                        .project
+                        .files
+                        .get(f_name)
+                        .map(|f| local_names(f).contains(alias))
+                        .unwrap_or(false)
+                {
+                    Some(format!(
+                        "the alias {alias} shadows the name imported with the file {f_name}"
+                    ))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// the call never returns failure, i.e. it either succeeds or keeps running
+    fn never_fails(&self, file: &'a FileName, call: &'a Call, visited: &mut Visited<'a>) -> bool {
+        match call {
+            Call::Invocation(name, _, _) => {
+                self.definition(file, name, visited, |def_file, tree, visited| {
+                    match tree.tpe {
+                        _ if def_file == STD => matches!(tree.name.as_str(), "success" | "running"),
+                        tpe => self.flow_never_fails(def_file, &tpe, &tree.calls.elems, visited),
+                    }
+                })
+            }
+            Call::Lambda(tpe, calls, _) => self.flow_never_fails(file, tpe, &calls.elems, visited),
+            Call::Decorator(tpe, _, child, _) => match tpe {
+                TreeType::ForceSuccess | TreeType::KeepRunningUntilSuccess => true,
+                TreeType::Inverter => self.never_succeeds(file, child, visited),
+                TreeType::Delay => self.never_fails(file, child, visited),
+                _ => false,
+            },
+            Call::HoInvocation(_, _) | Call::CustomDecorator(_, _, _, _) => false,
+        }
+    }
+
+    /// the call never returns success, i.e. it either fails or keeps running
+    fn never_succeeds(
+        &self,
+        file: &'a FileName,
+        call: &'a Call,
+        visited: &mut Visited<'a>,
+    ) -> bool {
+        match call {
+            Call::Invocation(name, _, _) => {
+                self.definition(file, name, visited, |def_file, tree, visited| {
+                    match tree.tpe {
+                        _ if def_file == STD => {
+                            matches!(tree.name.as_str(), "fail" | "fail_empty" | "running")
+                        }
+                        tpe => self.flow_never_succeeds(def_file, &tpe, &tree.calls.elems, visited),
+                    }
+                })
+            }
+            Call::Lambda(tpe, calls, _) => {
+                self.flow_never_succeeds(file, tpe, &calls.elems, visited)
+            }
+            Call::Decorator(tpe, _, child, _) => match tpe {
+                TreeType::ForceFail | TreeType::KeepRunningUntilFailure => true,
+                TreeType::Inverter => self.never_fails(file, child, visited),
+                TreeType::Delay => self.never_succeeds(file, child, visited),
+                _ => false,
+            },
+            Call::HoInvocation(_, _) | Call::CustomDecorator(_, _, _, _) => false,
+        }
+    }
+
+    fn flow_never_fails(
+        &self,
+        file: &'a FileName,
+        tpe: &TreeType,
+        children: &'a [Call],
+        visited: &mut Visited<'a>,
+    ) -> bool {
+        match tpe {
+            TreeType::Sequence | TreeType::MSequence | TreeType::RSequence => {
+                !children.is_empty() && children.iter().all(|c| self.never_fails(file, c, visited))
+            }
+            TreeType::Fallback | TreeType::RFallback => {
+                children.iter().any(|c| self.never_fails(file, c, visited))
+            }
+            _ => false,
+        }
+    }
+
+    fn flow_never_succeeds(
+        &self,
+        file: &'a FileName,
+        tpe: &TreeType,
+        children: &'a [Call],
+        visited: &mut Visited<'a>,
+    ) -> bool {
+        match tpe {
+            TreeType::Sequence | TreeType::MSequence | TreeType::RSequence => children
+                .iter()
+                .any(|c| self.never_succeeds(file, c, visited)),
+            TreeType::Fallback | TreeType::RFallback => {
+                !children.is_empty()
+                    && children
+                        .iter()
+                        .all(|c| self.never_succeeds(file, c, visited))
+            }
+            _ => false,
+        }
+    }
+
+    /// the call can return running and thus let the tree wait for something.
+    /// The actions declared with `impl` are supposed to take time, unlike the conditions.
+    fn can_run(&self, file: &'a FileName, call: &'a Call, visited: &mut Visited<'a>) -> bool {
+        match call {
+            Call::Invocation(name, _, _) => {
+                match self.resolve(file, name) {
+                    Some((tree, def_file)) if visited.contains(&(def_file, &tree.name)) => false,
+                    Some((tree, def_file)) => {
+                        visited.push((def_file, &tree.name));
+                        let res = match tree.tpe {
+                            _ if def_file == STD => tree.name == "running",
+                            TreeType::Impl => true,
+                            TreeType::Cond => false,
+                            _ => tree
+                                .calls
+                                .elems
+                                .iter()
+                                .any(|c| self.can_run(def_file, c, visited)),
+                        };
+                        visited.pop();
+                        res
+                    }
+                    // the unknown call is not judged
+                    None => true,
+                }
+            }
+            Call::Lambda(_, calls, _) => calls.elems.iter().any(|c| self.can_run(file, c, visited)),
+            Call::Decorator(TreeType::Delay | TreeType::RetryBackoff, _, _, _) => true,
+            Call::Decorator(_, _, child, _) => self.can_run(file, child, visited),
+            Call::HoInvocation(_, _) | Call::CustomDecorator(_, _, _, _) => true,
+        }
+    }
+
+    /// applies the check to the definition of the call.
+    /// The unknown and the recursive calls are not judged and give false.
+    fn definition<F>(
+        &self,
+        file: &'a FileName,
+        name: &Key,
+        visited: &mut Visited<'a>,
+        check: F,
+    ) -> bool
+    where
+        F: FnOnce(&'a FileName, &'a Tree, &mut Visited<'a>) -> bool,
+    {
+        match self.resolve(file, name) {
+            Some((tree, def_file)) if !visited.contains(&(def_file, &tree.name)) => {
+                visited.push((def_file, &tree.name));
+                let res = check(def_file, tree, visited);
+                visited.pop();
+                res
+            }
+            _ => false,
+        }
+    }
+}
+
+/// the repeat with the negative count is infinite
+fn is_infinite(args: &Arguments) -> bool {
+    match args.args.first().map(|a| a.value()) {
+        Some(ArgumentRhs::Mes(Message::Num(Number::Int(n) | Number::Hex(n)))) => *n < 0,
+        Some(ArgumentRhs::Mes(Message::Num(Number::Binary(n)))) => *n < 0,
+        Some(ArgumentRhs::Mes(Message::Num(Number::Float(n)))) => *n < 0.0,
+        _ => false,
+    }
+}
+
+/// the names of the trees and the enums the file refers to
+fn referred_names(file: &File) -> HashSet<Key> {
+    let mut names = HashSet::new();
+    for tree in file.definitions.values() {
+        for p in tree.params.params.iter() {
+            let mut tpe = &p.tpe;
+            while let MesType::ArrayOf(elem) = tpe {
+                tpe = elem;
+            }
+            if let MesType::Enum(name) = tpe {
+                names.insert(name.clone());
+            }
+        }
+        for call in tree.calls.elems.iter() {
+            call_names(call, &mut names);
+        }
+    }
+    names
+}
+
+fn call_names(call: &Call, names: &mut HashSet<Key>) {
+    match call {
+        Call::Invocation(name, args, _) => {
+            names.insert(name.clone());
+            args_names(args, names);
+        }
+        Call::HoInvocation(_, _) => {}
+        Call::Lambda(_, calls, _) => {
+            for call in calls.elems.iter() {
+                call_names(call, names);
+            }
+        }
+        Call::Decorator(_, args, child, _) => {
+            args_names(args, names);
+            call_names(child, names);
+        }
+        Call::CustomDecorator(name, args, child, _) => {
+            names.insert(name.clone());
+            args_names(args, names);
+            call_names(child, names);
+        }
+    }
+}
+
+fn args_names(args: &Arguments, names: &mut HashSet<Key>) {
+    for arg in args.args.iter() {
+        match arg.value() {
+            ArgumentRhs::Call(call) => call_names(call, names),
+            ArgumentRhs::Mes(m) => message_names(m, names),
+            _ => {}
+        }
+    }
+}
+
+fn message_names(m: &Message, names: &mut HashSet<Key>) {
+    match m {
+        Message::Enum(tpe, _) => {
+            names.insert(tpe.clone());
+        }
+        Message::Array(elems) => elems.iter().for_each(|e| message_names(e, names)),
+        Message::Object(elems) => elems.values().for_each(|e| message_names(e, names)),
+        Message::Num(_) | Message::String(_) | Message::Bool(_) => {}
+    }
+}
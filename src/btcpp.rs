@@ -0,0 +1,86 @@
+mod export;
+mod import;
+
+pub use export::{export, export_tree};
+pub use import::{import, import_source};
+
+use crate::runtime::rtree::rnode::{DecoratorType, FlowType};
+use crate::tree::parser::ast::arg::MesType;
+
+/// The version of the [BehaviorTree.CPP](https://www.behaviortree.dev) xml format.
+pub const FORMAT: &str = "4";
+
+/// The control nodes of BehaviorTree.CPP standing for the flows.
+pub(crate) fn flow_tag(tpe: &FlowType) -> &'static str {
+    match tpe {
+        FlowType::Root | FlowType::Sequence => "Sequence",
+        FlowType::MSequence => "SequenceWithMemory",
+        FlowType::RSequence => "ReactiveSequence",
+        FlowType::Fallback => "Fallback",
+        FlowType::RFallback => "ReactiveFallback",
+        FlowType::Parallel => "Parallel",
+    }
+}
+
+/// The decorator node of BehaviorTree.CPP:
+/// the tag, the ports for the arguments of the decorator in order and the fixed attributes
+/// making the node behave the same way as the decorator does.
+pub(crate) struct NativeDecorator {
+    pub tag: &'static str,
+    pub ports: &'static [&'static str],
+    pub attrs: &'static [(&'static str, &'static str)],
+}
+
+/// the decorators that have the counterparts in BehaviorTree.CPP
+pub(crate) fn native_decorator(tpe: &DecoratorType) -> Option<NativeDecorator> {
+    let native = |tag, ports, attrs| Some(NativeDecorator { tag, ports, attrs });
+    match tpe {
+        DecoratorType::Inverter => native("Inverter", &[], &[]),
+        DecoratorType::ForceSuccess => native("ForceSuccess", &[], &[]),
+        DecoratorType::ForceFail => native("ForceFailure", &[], &[]),
+        DecoratorType::Repeat => native("Repeat", &["num_cycles"], &[]),
+        DecoratorType::Retry => native("RetryUntilSuccessful", &["num_attempts"], &[]),
+        DecoratorType::Timeout => native("Timeout", &["msec"], &[]),
+        DecoratorType::Delay => native("Delay", &["delay_msec"], &[]),
+        DecoratorType::Guard => native("Precondition", &["if"], &[("else", "FAILURE")]),
+        // the node returns the result of the first run instead of skipping the child
+        DecoratorType::RunOnce => native("RunOnce", &[], &[("then_skip", "false")]),
+        DecoratorType::KeepRunningUntilFailure => native("KeepRunningUntilFailure", &[], &[]),
+        _ => None,
+    }
+}
+
+/// the names of the arguments of the built-in decorators that do not have the counterparts
+pub(crate) fn decorator_ports(tpe: &DecoratorType) -> &'static [&'static str] {
+    match tpe {
+        DecoratorType::RetryBackoff => &["attempts", "base", "factor"],
+        DecoratorType::Cooldown => &["duration"],
+        DecoratorType::RateLimit => &["limit", "window"],
+        _ => &[],
+    }
+}
+
+/// the built-in actions that have the counterparts in BehaviorTree.CPP
+pub(crate) fn native_action(name: &str) -> Option<&'static str> {
+    match name {
+        "success" => Some("AlwaysSuccess"),
+        "fail" | "fail_empty" => Some("AlwaysFailure"),
+        _ => None,
+    }
+}
+
+/// the types of the ports in the model of the nodes
+pub(crate) fn port_type(tpe: &MesType) -> Option<&'static str> {
+    match tpe {
+        MesType::Num => Some("double"),
+        MesType::String | MesType::Enum(_) => Some("std::string"),
+        MesType::Bool => Some("bool"),
+        MesType::ArrayOf(elem) => match elem.as_ref() {
+            MesType::Num => Some("std::vector<double>"),
+            MesType::String | MesType::Enum(_) => Some("std::vector<std::string>"),
+            MesType::Bool => Some("std::vector<bool>"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
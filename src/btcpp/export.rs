@@ -0,0 +1,312 @@
+use crate::btcpp::{decorator_ports, flow_tag, native_action, native_decorator, port_type, FORMAT};
+use crate::runtime::args::{RtArgs, RtValue, RtValueNumber};
+use crate::runtime::rtree::rnode::{DecoratorType, FlowType, RNode, RNodeId, RNodeName};
+use crate::runtime::rtree::RuntimeTree;
+use crate::tree::parser::ast::{Tree, TreeType};
+use crate::tree::project::Project;
+use crate::tree::{cerr, TreeError};
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+
+/// Exports the main root of the project to the BehaviorTree.CPP xml:
+/// ```xml
+/// <?xml version="1.0" encoding="UTF-8"?>
+/// <root BTCPP_format="4" main_tree_to_execute="main">
+///     <BehaviorTree ID="main">
+///         <Sequence>
+///             <Condition ID="is_ready"/>
+///             <Action ID="move_to" target="a"/>
+///         </Sequence>
+///     </BehaviorTree>
+///     <TreeNodesModel>
+///         <Condition ID="is_ready"/>
+///         <Action ID="move_to">
+///             <input_port name="target" type="std::string"/>
+///         </Action>
+///     </TreeNodesModel>
+/// </root>
+/// ```
+/// The definitions of the project give the conditions and the types of the ports in the model.
+/// The parameters of the trees are replaced with the values passed to them,
+/// so `patrol("dock")` calling `move_to(point)` gives `<Action ID="move_to" target="dock"/>`.
+pub fn export(project: &Project) -> Result<String, TreeError> {
+    let (tree, _) = RuntimeTree::build_resolved(project)?;
+    Exporter::new(&tree, Some(project)).xml()
+}
+
+/// Exports the runtime tree without the project.
+/// All leaves are exported as actions and the ports of the model do not have types.
+pub fn export_tree(tree: &RuntimeTree) -> Result<String, TreeError> {
+    Exporter::new(tree, None).xml()
+}
+
+/// the values of the parameters of the trees enclosing the node
+type Scope = HashMap<String, RtValue>;
+
+/// The node in the `TreeNodesModel` with the ports: the name, the direction and the type
+struct Model {
+    kind: &'static str,
+    ports: Vec<(String, bool, Option<&'static str>)>,
+}
+
+struct Exporter<'a> {
+    tree: &'a RuntimeTree,
+    /// the actions, the conditions and the declared decorators
+    definitions: HashMap<&'a str, &'a Tree>,
+    models: BTreeMap<String, Model>,
+    out: String,
+}
+
+impl<'a> Exporter<'a> {
+    fn new(tree: &'a RuntimeTree, project: Option<&'a Project>) -> Self {
+        let definitions = project
+            .map(|p| {
+                p.files
+                    .values()
+                    .flat_map(|f| f.definitions.values())
+                    .filter(|t| t.tpe.is_action() || t.tpe == TreeType::Decorator)
+                    .map(|t| (t.name.as_str(), t))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Exporter {
+            tree,
+            definitions,
+            models: BTreeMap::new(),
+            out: String::new(),
+        }
+    }
+
+    fn xml(mut self) -> Result<String, TreeError> {
+        let (name, children) = match self.tree.nodes.get(&self.tree.root) {
+            Some(RNode::Flow(FlowType::Root, RNodeName::Name(name), _, children)) => {
+                (name.clone(), children.clone())
+            }
+            _ => return Err(cerr(format!("the root {} is not found", self.tree.root))),
+        };
+
+        self.line(0, r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string());
+        self.line(
+            0,
+            format!(
+                "<root BTCPP_format=\"{FORMAT}\" main_tree_to_execute=\"{}\">",
+                escape(&name)
+            ),
+        );
+        self.open(1, "BehaviorTree", &[("ID".to_string(), name)], false);
+        // the tree in BehaviorTree.CPP has only one child, the root ticks the children in a sequence
+        let scope = Scope::new();
+        match children.as_slice() {
+            [child] => self.node(*child, 2, &scope)?,
+            children => {
+                self.open(2, flow_tag(&FlowType::Root), &[], children.is_empty());
+                for child in children {
+                    self.node(*child, 3, &scope)?;
+                }
+                if !children.is_empty() {
+                    self.close(2, flow_tag(&FlowType::Root));
+                }
+            }
+        }
+        self.close(1, "BehaviorTree");
+
+        self.line(1, "<TreeNodesModel>".to_string());
+        let models = std::mem::take(&mut self.models);
+        for (id, model) in models.iter() {
+            let attrs = [("ID".to_string(), id.clone())];
+            self.open(2, model.kind, &attrs, model.ports.is_empty());
+            if !model.ports.is_empty() {
+                for (name, output, tpe) in model.ports.iter() {
+                    let tag = if *output { "output_port" } else { "input_port" };
+                    let mut attrs = vec![("name".to_string(), name.clone())];
+                    if let Some(tpe) = tpe {
+                        attrs.push(("type".to_string(), tpe.to_string()));
+                    }
+                    self.open(3, tag, &attrs, true);
+                }
+                self.close(2, model.kind);
+            }
+        }
+        self.line(1, "</TreeNodesModel>".to_string());
+        self.line(0, "</root>".to_string());
+        Ok(self.out)
+    }
+
+    fn node(&mut self, id: RNodeId, depth: usize, scope: &Scope) -> Result<(), TreeError> {
+        let node = self
+            .tree
+            .nodes
+            .get(&id)
+            .ok_or(cerr(format!("the node {id} is not found in the tree")))?;
+        match node {
+            // the empty flow finishes at once with success
+            RNode::Flow(_, name, _, children) if children.is_empty() => {
+                self.open(depth, "AlwaysSuccess", &instance(name), true);
+            }
+            RNode::Flow(tpe, name, args, children) => {
+                let tag = flow_tag(tpe);
+                self.open(depth, tag, &instance(name), false);
+                let scope = enclose(scope, args);
+                for child in children {
+                    self.node(*child, depth + 1, &scope)?;
+                }
+                self.close(depth, tag);
+            }
+            RNode::Decorator(tpe, args, child) => {
+                let (tag, attrs) = match native_decorator(tpe) {
+                    Some(native) => {
+                        let mut attrs = positional(native.ports, args, scope);
+                        for (k, v) in native.attrs {
+                            attrs.push((k.to_string(), v.to_string()));
+                        }
+                        (native.tag, attrs)
+                    }
+                    None => {
+                        let id = tpe.to_string();
+                        let ports = match tpe {
+                            DecoratorType::Custom(_) => named(args, scope),
+                            tpe => positional(decorator_ports(tpe), args, scope),
+                        };
+                        self.model(&id, "Decorator", &ports);
+                        let mut attrs = vec![("ID".to_string(), id)];
+                        attrs.extend(ports);
+                        ("Decorator", attrs)
+                    }
+                };
+                self.open(depth, tag, &attrs, false);
+                self.node(*child, depth + 1, scope)?;
+                self.close(depth, tag);
+            }
+            RNode::Leaf(name, args) => {
+                let action = match name {
+                    RNodeName::Name(n) | RNodeName::Alias(n, _) => n.clone(),
+                    RNodeName::Lambda => return Err(cerr(format!("the leaf {id} has no name"))),
+                };
+                match native_action(&action) {
+                    Some(tag) if self.tree.std_nodes.contains(&action) => {
+                        self.open(depth, tag, &[], true);
+                    }
+                    _ => {
+                        let kind = match self.definitions.get(action.as_str()) {
+                            Some(t) if t.tpe == TreeType::Cond => "Condition",
+                            _ => "Action",
+                        };
+                        let ports = named(args, scope);
+                        self.model(&action, kind, &ports);
+                        let mut attrs = vec![("ID".to_string(), action)];
+                        if let RNodeName::Alias(_, alias) = name {
+                            attrs.push(("name".to_string(), alias.clone()));
+                        }
+                        attrs.extend(ports);
+                        self.open(depth, kind, &attrs, true);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// adds the node to the model, the ports are taken from the definition if it is known
+    fn model(&mut self, id: &str, kind: &'static str, ports: &[(String, String)]) {
+        if self.models.contains_key(id) {
+            return;
+        }
+        let ports = match self.definitions.get(id) {
+            Some(t) => t
+                .params
+                .params
+                .iter()
+                .map(|p| (p.name.clone(), p.output, port_type(&p.tpe)))
+                .collect(),
+            None => ports
+                .iter()
+                .map(|(k, _)| (k.clone(), false, None))
+                .collect(),
+        };
+        self.models.insert(id.to_string(), Model { kind, ports });
+    }
+
+    fn open(&mut self, depth: usize, tag: &str, attrs: &[(String, String)], empty: bool) {
+        let attrs = attrs
+            .iter()
+            .map(|(k, v)| format!(" {k}=\"{}\"", escape(v)))
+            .join("");
+        let end = if empty { "/>" } else { ">" };
+        self.line(depth, format!("<{tag}{attrs}{end}"));
+    }
+
+    fn close(&mut self, depth: usize, tag: &str) {
+        self.line(depth, format!("</{tag}>"));
+    }
+
+    fn line(&mut self, depth: usize, text: String) {
+        self.out.push_str(&"    ".repeat(depth));
+        self.out.push_str(&text);
+        self.out.push('\n');
+    }
+}
+
+/// the name of the node instance: the name of the called tree or the alias it is called by
+fn instance(name: &RNodeName) -> Vec<(String, String)> {
+    match name {
+        RNodeName::Lambda => vec![],
+        RNodeName::Name(n) | RNodeName::Alias(_, n) => vec![("name".to_string(), n.clone())],
+    }
+}
+
+/// the scope of the children of the tree, the arguments of the tree take the values from the outer scope
+fn enclose(scope: &Scope, args: &RtArgs) -> Scope {
+    let mut inner = scope.clone();
+    for a in args.0.iter() {
+        inner.insert(a.clone().name(), substitute(&a.clone().val(), scope));
+    }
+    inner
+}
+
+/// replaces the parameters of the enclosing trees with their values
+fn substitute(value: &RtValue, scope: &Scope) -> RtValue {
+    match value {
+        RtValue::Pointer(key) => scope.get(key).cloned().unwrap_or(value.clone()),
+        v => v.clone(),
+    }
+}
+
+fn named(args: &RtArgs, scope: &Scope) -> Vec<(String, String)> {
+    args.0
+        .iter()
+        .map(|a| {
+            let value = substitute(&a.clone().val(), scope);
+            (a.clone().name(), port_value(&value))
+        })
+        .collect()
+}
+
+fn positional(ports: &[&str], args: &RtArgs, scope: &Scope) -> Vec<(String, String)> {
+    ports
+        .iter()
+        .zip(args.0.iter())
+        .map(|(port, a)| {
+            let value = substitute(&a.clone().val(), scope);
+            (port.to_string(), port_value(&value))
+        })
+        .collect()
+}
+
+/// The value of the port, the blackboard keys are written in braces: `{key}`.
+/// The pointer that is not a parameter of the enclosing trees refers to the blackboard.
+fn port_value(value: &RtValue) -> String {
+    match value {
+        RtValue::Pointer(key) | RtValue::Ref(key, _) => format!("{{{key}}}"),
+        RtValue::Array(elems) => elems.iter().map(port_value).join(";"),
+        RtValue::Number(RtValueNumber::Hex(v)) => v.to_string(),
+        RtValue::Number(RtValueNumber::Binary(v)) => v.to_string(),
+        v => v.to_string(),
+    }
+}
+
+fn escape(v: &str) -> String {
+    v.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
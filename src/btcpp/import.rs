@@ -0,0 +1,672 @@
+use crate::btcpp::{decorator_ports, flow_tag, native_action, native_decorator, port_type};
+use crate::runtime::rtree::rnode::{DecoratorType, FlowType};
+use crate::tree::diagnostic::Span;
+use crate::tree::parser::ast::arg::{Argument, ArgumentRhs, Arguments, MesType, Param, Params};
+use crate::tree::parser::ast::call::{Call, Calls};
+use crate::tree::parser::ast::message::Message;
+use crate::tree::parser::ast::{AstFile, FileEntity, Import, Tree, TreeType};
+use crate::tree::parser::{is_keyword, parse_expr};
+use crate::tree::printer::print;
+use crate::tree::project::imports::STD;
+use crate::tree::TreeError;
+use roxmltree::{Document, Node};
+use std::path::Path;
+use std::str::FromStr;
+
+const FLOWS: [TreeType; 6] = [
+    TreeType::Sequence,
+    TreeType::MSequence,
+    TreeType::RSequence,
+    TreeType::Fallback,
+    TreeType::RFallback,
+    TreeType::Parallel,
+];
+
+const DECORATORS: [TreeType; 14] = [
+    TreeType::Inverter,
+    TreeType::ForceSuccess,
+    TreeType::ForceFail,
+    TreeType::Repeat,
+    TreeType::Retry,
+    TreeType::Timeout,
+    TreeType::Delay,
+    TreeType::RetryBackoff,
+    TreeType::Cooldown,
+    TreeType::RateLimit,
+    TreeType::Guard,
+    TreeType::RunOnce,
+    TreeType::KeepRunningUntilFailure,
+    TreeType::KeepRunningUntilSuccess,
+];
+
+/// Imports the BehaviorTree.CPP xml into the file of the tree language:
+/// - the main tree becomes the root and the other trees become the definitions,
+/// - the control nodes and the decorators become the counterparts from the language,
+/// - the unknown leaves and decorators get declared with `impl`, `cond` and `decorator`,
+///   their ports become the parameters.
+///
+/// The types of the parameters are taken from the `TreeNodesModel`
+/// or guessed from the values passed in the tree.
+pub fn import(xml: &str) -> Result<AstFile, TreeError> {
+    let doc = Document::parse(xml)
+        .map_err(|e| TreeError::ParseError(format!("the xml can not be parsed: {e}")))?;
+    let root = doc.root_element();
+    if !root.has_tag_name("root") {
+        return Err(perr(format!(
+            "the xml should start with the element root but got {}",
+            root.tag_name().name()
+        )));
+    }
+    let mut importer = Importer::default();
+    for model in elements(root).filter(|n| n.has_tag_name("TreeNodesModel")) {
+        importer.models(model)?;
+    }
+
+    let trees: Vec<Node> = elements(root)
+        .filter(|n| n.has_tag_name("BehaviorTree"))
+        .collect();
+    for tree in trees.iter() {
+        for child in elements(*tree) {
+            importer.collect(child)?;
+        }
+    }
+    let main = root
+        .attribute("main_tree_to_execute")
+        .or(trees.first().and_then(|t| t.attribute("ID")))
+        .ok_or(perr("the xml has no trees".to_string()))?;
+    if !trees.iter().any(|t| t.attribute("ID") == Some(main)) {
+        return Err(perr(format!("the main tree {main} is not found")));
+    }
+    let definitions = trees
+        .iter()
+        .flat_map(|t| t.attribute("ID"))
+        .chain(importer.used.iter().map(|(id, _)| id.as_str()))
+        .chain(importer.models.iter().map(|(id, _, _)| id.as_str()));
+    unique(definitions, "trees and nodes")?;
+    unique(importer.keys.iter().map(String::as_str), "blackboard keys")?;
+
+    let mut entities = vec![];
+    if importer.std {
+        entities.push(FileEntity::Import(Import::file(STD)));
+    }
+    for include in elements(root).filter(|n| n.has_tag_name("include")) {
+        let path = include
+            .attribute("path")
+            .ok_or(perr("the include has no path".to_string()))?;
+        let file = Path::new(path).with_extension("tree");
+        entities.push(FileEntity::Import(Import::file(&file.to_string_lossy())));
+    }
+    for declaration in importer.declarations()? {
+        entities.push(FileEntity::Tree(declaration));
+    }
+
+    let mut root_tree = None;
+    for tree in trees {
+        let id = tree
+            .attribute("ID")
+            .ok_or(perr("the tree has no ID".to_string()))?;
+        let calls = match elements(tree).collect::<Vec<_>>().as_slice() {
+            [] => vec![],
+            [child] => vec![importer.call(*child)?],
+            _ => return Err(perr(format!("the tree {id} should have only one child"))),
+        };
+        if id == main {
+            root_tree = Some(Tree::root(&ident(id), calls));
+            continue;
+        }
+        let tree = match calls.as_slice() {
+            [Call::Lambda(tpe, calls, _)] => {
+                Tree::new(*tpe, ident(id), Params::default(), calls.clone())
+            }
+            _ => Tree::new(
+                TreeType::Sequence,
+                ident(id),
+                Params::default(),
+                Calls::new(calls),
+            ),
+        };
+        entities.push(FileEntity::Tree(tree));
+    }
+    entities.extend(root_tree.map(FileEntity::Tree));
+    Ok(AstFile::new(entities))
+}
+
+/// Imports the BehaviorTree.CPP xml and prints the file of the tree language.
+pub fn import_source(xml: &str) -> Result<String, TreeError> {
+    Ok(print(&import(xml)?))
+}
+
+fn perr(v: String) -> TreeError {
+    TreeError::ParseError(v)
+}
+
+/// the node of BehaviorTree.CPP recognized by the tag or the ID
+enum Kind {
+    Flow(TreeType),
+    Decorator(TreeType),
+    /// the action from `std::actions`
+    Std(&'static str),
+    SubTree(String),
+    /// the node that gets declared with `impl`, `cond` or `decorator`
+    Custom(TreeType, String),
+}
+
+/// the port from the `TreeNodesModel`
+#[derive(Clone)]
+struct Port {
+    name: String,
+    output: bool,
+    tpe: Option<MesType>,
+    default: Option<String>,
+}
+
+#[derive(Default)]
+struct Importer {
+    /// the kind of the node and the ports by the ID
+    models: Vec<(String, String, Vec<Port>)>,
+    /// the declared nodes in the order they are met in the trees
+    used: Vec<(String, TreeType)>,
+    /// the ports passed in every call of the declared node
+    calls: Vec<(String, Vec<(String, String)>)>,
+    /// the parameters of the declared nodes
+    params: Vec<(String, Vec<Param>)>,
+    /// the actions from `std::actions` are used
+    std: bool,
+    /// the keys of the blackboard the ports refer to
+    keys: Vec<String>,
+}
+
+impl Importer {
+    fn models(&mut self, model: Node) -> Result<(), TreeError> {
+        for node in elements(model) {
+            let kind = node.tag_name().name();
+            let id = node
+                .attribute("ID")
+                .ok_or(perr(format!("the model {kind} has no ID")))?;
+            let ports = elements(node)
+                .filter_map(|p| {
+                    let output = match p.tag_name().name() {
+                        "input_port" => false,
+                        "output_port" | "inout_port" => true,
+                        _ => return None,
+                    };
+                    Some(Port {
+                        name: p.attribute("name")?.to_string(),
+                        output,
+                        tpe: p.attribute("type").and_then(mes_type),
+                        default: p.attribute("default").map(ToString::to_string),
+                    })
+                })
+                .collect();
+            self.models.push((id.to_string(), kind.to_string(), ports));
+        }
+        Ok(())
+    }
+
+    fn model(&self, id: &str) -> Option<(&str, &[Port])> {
+        self.models
+            .iter()
+            .find(|(m, _, _)| m == id)
+            .map(|(_, kind, ports)| (kind.as_str(), ports.as_slice()))
+    }
+
+    fn kind(&self, node: Node) -> Result<Kind, TreeError> {
+        let tag = node.tag_name().name();
+        let id = match tag {
+            "Action" | "Condition" | "Decorator" | "Control" | "SubTree" | "SubTreePlus" => node
+                .attribute("ID")
+                .ok_or(perr(format!("the node {tag} has no ID")))?,
+            tag => tag,
+        };
+        if matches!(tag, "SubTree" | "SubTreePlus") {
+            return Ok(Kind::SubTree(id.to_string()));
+        }
+        if let Some(tpe) = flow_type(id) {
+            return Ok(Kind::Flow(tpe));
+        }
+        if let Some(tpe) = decorator_type(id) {
+            return Ok(Kind::Decorator(tpe));
+        }
+        if let Some(action) = std_action(id) {
+            return Ok(Kind::Std(action));
+        }
+        // the explicit tag wins over the model
+        let kind = match tag {
+            "Action" | "Condition" | "Decorator" | "Control" => Some(tag),
+            _ => self.model(id).map(|(kind, _)| kind),
+        };
+        let tpe = match kind {
+            Some("Action") => TreeType::Impl,
+            Some("Condition") => TreeType::Cond,
+            Some("Decorator") => TreeType::Decorator,
+            Some("SubTree") => return Ok(Kind::SubTree(id.to_string())),
+            Some(_) => {
+                return Err(perr(format!(
+                    "the control node {id} has no counterpart in the tree language"
+                )))
+            }
+            // without the model the kind is guessed from the children
+            None => match elements(node).count() {
+                0 => TreeType::Impl,
+                1 => TreeType::Decorator,
+                _ => {
+                    return Err(perr(format!(
+                        "the control node {id} has no counterpart in the tree language"
+                    )))
+                }
+            },
+        };
+        Ok(Kind::Custom(tpe, id.to_string()))
+    }
+
+    /// checks the structure of the tree and gathers the calls of the nodes to declare
+    fn collect(&mut self, node: Node) -> Result<(), TreeError> {
+        let tag = node.tag_name().name();
+        let children = elements(node).count();
+        let kind = self.kind(node)?;
+        match &kind {
+            Kind::Flow(_) => {}
+            Kind::Decorator(_) | Kind::Custom(TreeType::Decorator, _) if children != 1 => {
+                return Err(perr(format!(
+                    "the decorator {tag} should have only one child"
+                )))
+            }
+            Kind::Decorator(_) | Kind::Custom(TreeType::Decorator, _) => {}
+            _ if children > 0 => {
+                return Err(perr(format!("the node {tag} should not have children")))
+            }
+            _ => {}
+        }
+        for (port, value) in ports(node)? {
+            match reference(&value) {
+                Some("=") => self.keys.push(port),
+                Some(key) => self.keys.push(key.trim_start_matches('@').to_string()),
+                None => {}
+            }
+        }
+        match kind {
+            Kind::Std(_) => self.std = true,
+            Kind::SubTree(id) => {
+                // the trees share the blackboard, thus the ports can be passed only as they are
+                for (port, value) in ports(node)? {
+                    if value != "{=}" && value != format!("{{{port}}}") {
+                        return Err(perr(format!(
+                            "the port {port} of the subtree {id} is remapped to {value} but the trees share the blackboard"
+                        )));
+                    }
+                }
+            }
+            Kind::Custom(tpe, id) => {
+                match self.used.iter().find(|(used, _)| *used == id) {
+                    Some((_, used)) if *used != tpe => {
+                        return Err(perr(format!(
+                            "the node {id} is used both as {used} and {tpe}"
+                        )))
+                    }
+                    Some(_) => {}
+                    None => self.used.push((id.clone(), tpe)),
+                }
+                self.calls.push((id, ports(node)?));
+            }
+            _ => {}
+        }
+        for child in elements(node) {
+            self.collect(child)?;
+        }
+        Ok(())
+    }
+
+    /// the declarations of the used nodes and the rest of the nodes from the model
+    fn declarations(&mut self) -> Result<Vec<Tree>, TreeError> {
+        let mut nodes = self.used.clone();
+        for (id, kind, _) in self.models.iter() {
+            let tpe = match kind.as_str() {
+                "Action" => TreeType::Impl,
+                "Condition" => TreeType::Cond,
+                "Decorator" => TreeType::Decorator,
+                _ => continue,
+            };
+            let known =
+                flow_type(id).is_some() || decorator_type(id).is_some() || std_action(id).is_some();
+            if !known && !nodes.iter().any(|(n, _)| n == id) {
+                nodes.push((id.clone(), tpe));
+            }
+        }
+
+        let mut declarations = vec![];
+        for (id, tpe) in nodes {
+            let params = self.declare(&id)?;
+            declarations.push(Tree::new(
+                tpe,
+                ident(&id),
+                Params::new(params.clone()),
+                Calls::default(),
+            ));
+            self.params.push((id, params));
+        }
+        Ok(declarations)
+    }
+
+    /// the parameters are the ports from the model and the ports passed in the calls.
+    /// The port becomes optional if some calls do not pass it.
+    fn declare(&self, id: &str) -> Result<Vec<Param>, TreeError> {
+        let calls: Vec<&Vec<(String, String)>> = self
+            .calls
+            .iter()
+            .filter(|(c, _)| c == id)
+            .map(|(_, ports)| ports)
+            .collect();
+        let mut ports: Vec<Port> = self
+            .model(id)
+            .map(|(_, ports)| ports.to_vec())
+            .unwrap_or_default();
+        for (name, _) in calls.iter().flat_map(|c| c.iter()) {
+            if !ports.iter().any(|p| p.name == *name) {
+                ports.push(Port {
+                    name: name.clone(),
+                    output: false,
+                    tpe: None,
+                    default: None,
+                });
+            }
+        }
+
+        unique(
+            ports.iter().map(|p| p.name.as_str()),
+            &format!("ports of the node {id}"),
+        )?;
+
+        let params = ports
+            .into_iter()
+            .map(|port| {
+                let values: Vec<&str> = calls
+                    .iter()
+                    .filter_map(|c| c.iter().find(|(n, _)| *n == port.name))
+                    .map(|(_, v)| v.as_str())
+                    .collect();
+                let tpe = port.tpe.unwrap_or_else(|| infer(&values));
+                let default = match port.default {
+                    Some(v) if !port.output => message(&v, &tpe),
+                    _ => None,
+                };
+                Param {
+                    name: ident(&port.name),
+                    optional: default.is_none() && values.len() < calls.len(),
+                    tpe,
+                    output: port.output,
+                    default,
+                }
+            })
+            .collect();
+        Ok(params)
+    }
+
+    fn call(&self, node: Node) -> Result<Call, TreeError> {
+        let tag = node.tag_name().name();
+        let child = || match elements(node).next() {
+            Some(child) => self.call(child),
+            None => Err(perr(format!(
+                "the decorator {tag} should have only one child"
+            ))),
+        };
+        let call = match self.kind(node)? {
+            Kind::Flow(tpe) => {
+                let calls = elements(node)
+                    .map(|n| self.call(n))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Call::lambda(tpe, Calls::new(calls))
+            }
+            Kind::Decorator(TreeType::Guard) => {
+                let script = node
+                    .attribute("if")
+                    .ok_or(perr(format!("the node {tag} has no condition")))?;
+                fixed(node, &DecoratorType::Guard)?;
+                // the scripts of BehaviorTree.CPP quote the strings with the single quotes
+                let cond = parse_expr(&script.replace('\'', "\"")).map_err(|_| {
+                    perr(format!(
+                        "the condition {script} of the node {tag} can not be converted"
+                    ))
+                })?;
+                Call::decorator(
+                    TreeType::Guard,
+                    Arguments::new(vec![Argument::expr(cond)]),
+                    child()?,
+                )
+            }
+            Kind::Decorator(tpe) => {
+                let decorator = DecoratorType::try_from(tpe)?;
+                fixed(node, &decorator)?;
+                let ports = native_decorator(&decorator)
+                    .map(|n| n.ports)
+                    .unwrap_or(decorator_ports(&decorator));
+                // the arguments of the decorators are positional
+                let args = ports
+                    .iter()
+                    .map_while(|p| node.attribute(*p))
+                    .map(|v| {
+                        let rhs = value(v, &infer(&[v]), "")
+                            .ok_or(perr(format!("the value {v} of the node {tag} is wrong")))?;
+                        Ok(Argument::Unassigned(rhs, Span::default()))
+                    })
+                    .collect::<Result<Vec<_>, TreeError>>()?;
+                Call::decorator(tpe, Arguments::new(args), child()?)
+            }
+            Kind::Std(action) => Call::invocation(action, Arguments::default()),
+            Kind::SubTree(id) => Call::invocation(&ident(&id), Arguments::default()),
+            Kind::Custom(TreeType::Decorator, id) => {
+                Call::custom_decorator(&ident(&id), self.args(&id, node)?, child()?)
+            }
+            Kind::Custom(_, id) => Call::invocation(&ident(&id), self.args(&id, node)?),
+        };
+        Ok(call)
+    }
+
+    /// the named arguments converted to the types of the parameters
+    fn args(&self, id: &str, node: Node) -> Result<Arguments, TreeError> {
+        let params = self
+            .params
+            .iter()
+            .find(|(p, _)| p == id)
+            .map(|(_, params)| params.as_slice())
+            .unwrap_or_default();
+        let mut args = vec![];
+        for (port, raw) in ports(node)? {
+            let name = ident(&port);
+            let param = params.iter().find(|p| p.name == name).ok_or(perr(format!(
+                "the port {port} of the node {id} is not declared"
+            )))?;
+            let rhs = value(&raw, &param.tpe, &port).ok_or(perr(format!(
+                "the value {raw} of the port {port} of the node {id} does not fit the type {}",
+                param.tpe
+            )))?;
+            if param.output && !matches!(rhs, ArgumentRhs::BBRef(_)) {
+                return Err(perr(format!(
+                    "the output port {port} of the node {id} expects a reference to the blackboard but got {raw}"
+                )));
+            }
+            args.push(Argument::Assigned(name, rhs, Span::default()));
+        }
+        Ok(Arguments::new(args))
+    }
+}
+
+fn elements<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(|n| n.is_element())
+}
+
+/// the attributes passed to the ports, the instance name is dropped
+fn ports(node: Node) -> Result<Vec<(String, String)>, TreeError> {
+    let mut ports = vec![];
+    for attr in node.attributes() {
+        match attr.name() {
+            "ID" | "name" | "_autoremap" => {}
+            name if name.starts_with('_') => {
+                return Err(perr(format!(
+                    "the attribute {name} of the node {} has no counterpart in the tree language",
+                    node.tag_name().name()
+                )))
+            }
+            name => ports.push((name.to_string(), attr.value().to_string())),
+        }
+    }
+    Ok(ports)
+}
+
+/// the attributes of the native decorator should be the same the export writes
+fn fixed(node: Node, tpe: &DecoratorType) -> Result<(), TreeError> {
+    let attrs = native_decorator(tpe).map(|n| n.attrs).unwrap_or_default();
+    for (name, expected) in attrs.iter() {
+        match node.attribute(*name) {
+            Some(v) if v != *expected => {
+                return Err(perr(format!(
+                    "the attribute {name}=\"{v}\" of the node {} has no counterpart in the tree language",
+                    node.tag_name().name()
+                )))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn flow_type(tag: &str) -> Option<TreeType> {
+    // the name of the version 3
+    if tag == "SequenceStar" {
+        return Some(TreeType::MSequence);
+    }
+    FLOWS.into_iter().find(|tpe| {
+        FlowType::try_from(*tpe)
+            .map(|flow| flow_tag(&flow) == tag)
+            .unwrap_or(false)
+    })
+}
+
+fn decorator_type(tag: &str) -> Option<TreeType> {
+    // the name of the version 3
+    if tag == "RetryUntilSuccesful" {
+        return Some(TreeType::Retry);
+    }
+    DECORATORS
+        .into_iter()
+        .find(|tpe| match DecoratorType::try_from(*tpe) {
+            Ok(d) => match native_decorator(&d) {
+                Some(native) => native.tag == tag,
+                None => d.to_string() == tag,
+            },
+            Err(_) => false,
+        })
+}
+
+fn std_action(tag: &str) -> Option<&'static str> {
+    ["success", "fail_empty"]
+        .into_iter()
+        .find(|action| native_action(action) == Some(tag))
+}
+
+/// the type of the parameter for the type of the port in the model
+fn mes_type(tpe: &str) -> Option<MesType> {
+    let candidates = [
+        MesType::Num,
+        MesType::String,
+        MesType::Bool,
+        MesType::array_of(MesType::Num),
+        MesType::array_of(MesType::String),
+        MesType::array_of(MesType::Bool),
+    ];
+    candidates
+        .into_iter()
+        .find(|c| port_type(c) == Some(tpe))
+        .or(match tpe {
+            "int" | "unsigned" | "unsigned int" | "long" | "unsigned long" | "float" | "short"
+            | "int32_t" | "uint32_t" | "int64_t" | "uint64_t" | "size_t" => Some(MesType::Num),
+            _ => None,
+        })
+}
+
+/// the type guessed from the values passed to the port, the references are skipped
+fn infer(values: &[&str]) -> MesType {
+    let literals: Vec<&&str> = values.iter().filter(|v| reference(v).is_none()).collect();
+    if literals.is_empty() {
+        MesType::String
+    } else if literals.iter().all(|v| message(v, &MesType::Num).is_some()) {
+        MesType::Num
+    } else if literals.iter().all(|v| matches!(**v, "true" | "false")) {
+        MesType::Bool
+    } else {
+        MesType::String
+    }
+}
+
+/// the key of the blackboard written in the braces: `{key}`
+fn reference(value: &str) -> Option<&str> {
+    value.trim().strip_prefix('{')?.strip_suffix('}')
+}
+
+/// The value of the port: either the reference to the blackboard or the message of the type.
+/// The reference `{=}` points to the key named as the port.
+fn value(raw: &str, tpe: &MesType, port: &str) -> Option<ArgumentRhs> {
+    match reference(raw) {
+        Some("=") => Some(ArgumentRhs::BBRef(ident(port))),
+        // the keys of the root blackboard start with @
+        Some(key) => Some(ArgumentRhs::BBRef(ident(key.trim_start_matches('@')))),
+        None => message(raw, tpe).map(ArgumentRhs::Mes),
+    }
+}
+
+/// the literal converted to the type, the arrays are separated with `;`
+fn message(raw: &str, tpe: &MesType) -> Option<Message> {
+    match tpe {
+        MesType::Num => raw.trim().parse::<i64>().map(Message::int).ok().or(raw
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .map(Message::float)),
+        MesType::Bool => match raw.trim().to_lowercase().as_str() {
+            "true" | "1" => Some(Message::bool(true)),
+            "false" | "0" => Some(Message::bool(false)),
+            _ => None,
+        },
+        MesType::ArrayOf(_) if raw.is_empty() => Some(Message::array(vec![])),
+        MesType::ArrayOf(elem) => raw
+            .split(';')
+            .map(|v| message(v, elem))
+            .collect::<Option<Vec<_>>>()
+            .map(Message::array),
+        _ => Some(Message::str(raw)),
+    }
+}
+
+/// the different names should not become the same identifier, like `move-to` and `move_to`
+fn unique<'a>(names: impl Iterator<Item = &'a str>, what: &str) -> Result<(), TreeError> {
+    let mut idents: Vec<(String, &str)> = vec![];
+    for name in names {
+        let id = ident(name);
+        match idents.iter().find(|(i, _)| *i == id) {
+            Some((_, other)) if *other != name => {
+                return Err(perr(format!(
+                    "the {what} {other} and {name} both become {id} in the tree language"
+                )))
+            }
+            Some(_) => {}
+            None => idents.push((id, name)),
+        }
+    }
+    Ok(())
+}
+
+/// The name that is valid in the tree language.
+/// The other symbols are replaced with `_` and the reserved words get `_` at the end.
+fn ident(name: &str) -> String {
+    let mut id: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if id.is_empty() || id.starts_with(|c: char| c.is_ascii_digit()) {
+        id.insert(0, '_');
+    }
+    if is_keyword(&id) || TreeType::from_str(&id).is_ok() {
+        id.push('_');
+    }
+    id
+}
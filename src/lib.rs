@@ -8,6 +8,9 @@ use log::LevelFilter;
 use std::fs;
 use std::path::PathBuf;
 
+pub mod analysis;
+pub mod btcpp;
+pub mod manifest;
 pub mod runtime;
 pub mod simulator;
 pub mod tracer;
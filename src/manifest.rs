@@ -0,0 +1,243 @@
+use crate::analysis::{analyze, Lint};
+use crate::btcpp;
+use crate::runtime::builder::ForesterBuilder;
+use crate::runtime::rtree::RuntimeTree;
+use crate::runtime::{RtResult, RuntimeError};
+use crate::simulator::config::SimProfile;
+use crate::simulator::Simulator;
+use crate::tracer::{Tracer, TracerConfiguration};
+use crate::tree::project::libs::{Libs, ManifestLibs};
+use crate::tree::project::{FileName, Project, TreeName};
+use crate::tree::TreeError;
+use crate::visualizer::Visualizer;
+use crate::{get_pb, read_file};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub use crate::tree::project::libs::MANIFEST;
+
+/// The manifest of the project, the file `forester.toml`.
+/// It keeps the configuration shared by the engine, the simulator, the visualizer and the cli.
+/// ```toml
+/// root = "trees"
+/// main = "main.tree"
+/// tree = "main"
+/// libs = ["../libs"]
+/// optimize = true
+///
+/// [simulation]
+/// default = "sim/default.yaml"
+/// slow = "sim/slow.yaml"
+///
+/// [output]
+/// dir = "gen"
+/// svg = "main.svg"
+///
+/// [tracer]
+/// file = "main.trace"
+/// indent = 2
+/// paths = true
+/// ```
+/// All fields are optional.
+/// The relative paths relate to the folder of the manifest, except the outputs relating to the output folder.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct Manifest {
+    /// the folder the manifest is placed in
+    #[serde(skip)]
+    pub dir: PathBuf,
+    /// the root of the project, the folder of the manifest by default
+    pub root: Option<PathBuf>,
+    /// the main file, `main.tree` by default
+    pub main: Option<FileName>,
+    /// the root definition in the main file, the first one by default
+    pub tree: Option<TreeName>,
+    /// the library paths, the project reads them itself, see [`ManifestLibs`]
+    #[serde(flatten)]
+    pub libs: ManifestLibs,
+    /// turns on the optimization of the runtime tree
+    #[serde(default)]
+    pub optimize: bool,
+    /// the named simulation profiles
+    #[serde(default)]
+    pub simulation: HashMap<String, PathBuf>,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub tracer: TracerConfig,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct OutputConfig {
+    /// the folder for the generated files, the root of the project by default
+    pub dir: Option<PathBuf>,
+    /// the file of the visualization, the name of the main file with the extension `svg` by default
+    pub svg: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct TracerConfig {
+    /// the file to write the trace into. If it is absent, the tracer is turned off.
+    pub file: Option<PathBuf>,
+    pub indent: Option<usize>,
+    /// adds the paths of the nodes to the trace
+    #[serde(default)]
+    pub paths: bool,
+}
+
+impl Manifest {
+    pub fn parse(text: &str, dir: PathBuf) -> RtResult<Manifest> {
+        let mut manifest: Manifest = toml::from_str(text).map_err(|e| {
+            RuntimeError::IOError(format!("the manifest {MANIFEST} is invalid: {e}"))
+        })?;
+        manifest.dir = dir;
+        Ok(manifest)
+    }
+
+    /// reads the manifest file.
+    pub fn load(file: &PathBuf) -> RtResult<Manifest> {
+        let dir = file.parent().map(PathBuf::from).unwrap_or_default();
+        Manifest::parse(read_file(file)?.as_str(), dir)
+    }
+
+    /// reads the manifest `forester.toml` in the folder if it exists.
+    pub fn find(dir: &PathBuf) -> RtResult<Option<Manifest>> {
+        let file = dir.join(MANIFEST);
+        if file.exists() {
+            Manifest::load(&file).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn path(&self, p: &PathBuf) -> PathBuf {
+        get_pb(&p.to_string_lossy().to_string(), self.dir.clone())
+    }
+
+    pub fn root(&self) -> PathBuf {
+        match &self.root {
+            Some(root) => self.path(root),
+            None => self.dir.clone(),
+        }
+    }
+
+    pub fn main_file(&self) -> FileName {
+        self.main.clone().unwrap_or("main.tree".to_string())
+    }
+
+    pub fn libs(&self) -> Libs {
+        self.libs.resolve(&self.dir)
+    }
+
+    pub fn output_dir(&self) -> PathBuf {
+        match &self.output.dir {
+            Some(dir) => self.path(dir),
+            None => self.root(),
+        }
+    }
+
+    /// the path of the generated file relating to the output folder
+    pub fn output(&self, file: &PathBuf) -> PathBuf {
+        get_pb(&file.to_string_lossy().to_string(), self.output_dir())
+    }
+
+    pub fn svg(&self) -> PathBuf {
+        match &self.output.svg {
+            Some(svg) => self.output(svg),
+            None => self.output(&PathBuf::from(self.main_file()).with_extension("svg")),
+        }
+    }
+
+    /// the configuration of the tracer if the file for the trace is set.
+    pub fn tracer(&self) -> Option<TracerConfiguration> {
+        self.tracer.file.as_ref().map(|file| {
+            let mut cfg = TracerConfiguration::in_file(self.output(file));
+            if let Some(indent) = self.tracer.indent {
+                cfg.indent = indent;
+            }
+            cfg.paths = self.tracer.paths;
+            cfg
+        })
+    }
+
+    /// the simulation profile by the name.
+    /// If the name is not set, the profile `default` or the only profile is taken.
+    /// Without the profiles in the manifest, the default profile is used.
+    pub fn profile(&self, name: Option<&str>) -> RtResult<SimProfile> {
+        let file = match name {
+            Some(name) => self
+                .simulation
+                .get(name)
+                .ok_or(RuntimeError::IOError(format!(
+                    "the simulation profile {name} is not found in the manifest"
+                )))?,
+            None if self.simulation.is_empty() => return Ok(SimProfile::default()),
+            None if self.simulation.len() == 1 => self.simulation.values().next().unwrap(),
+            None => self
+                .simulation
+                .get("default")
+                .ok_or(RuntimeError::IOError(format!(
+                    "the simulation profile should be chosen among {}",
+                    self.simulation.keys().sorted().join(", ")
+                )))?,
+        };
+        SimProfile::parse_file(&self.path(file))
+    }
+
+    /// the project with the main file, the main tree and the libraries of the manifest.
+    pub fn project(&self) -> Result<Project, TreeError> {
+        Project::build_with_libs(
+            self.main_file(),
+            self.tree.clone(),
+            self.root(),
+            self.libs().paths,
+        )
+    }
+
+    /// the builder of the engine with the project and the tracer of the manifest.
+    /// The actions are supposed to be registered further.
+    pub fn forester_builder(&self) -> RtResult<ForesterBuilder> {
+        let mut fb = ForesterBuilder::new();
+        fb.root(self.root());
+        fb.main_file(self.main_file());
+        if let Some(tree) = &self.tree {
+            fb.main_tree(tree.clone());
+        }
+        for lib in self.libs().paths {
+            fb.lib(lib);
+        }
+        fb.optimize(self.optimize);
+        if let Some(cfg) = self.tracer() {
+            fb.tracer(Tracer::create(cfg)?);
+        }
+        Ok(fb)
+    }
+
+    /// the simulator with the profile of the manifest, see [`Manifest::profile`].
+    /// The outputs of the profile relate to the output folder.
+    pub fn simulator(&self, profile: Option<&str>) -> RtResult<Simulator> {
+        Simulator::build_with(
+            self.profile(profile)?,
+            self.root(),
+            self.output_dir(),
+            self.forester_builder()?,
+        )
+    }
+
+    /// the warnings of the static analysis of the project, see [`analyze`].
+    pub fn lint(&self) -> Result<Vec<Lint>, TreeError> {
+        analyze(&self.project()?)
+    }
+
+    /// the main tree in the BehaviorTree.CPP xml, see [`export`](crate::btcpp::export).
+    pub fn export(&self) -> Result<String, TreeError> {
+        btcpp::export(&self.project()?)
+    }
+
+    /// draws the main tree into the svg file of the manifest.
+    pub fn visualize(&self) -> Result<String, TreeError> {
+        let tree = RuntimeTree::build(self.project()?)?;
+        Visualizer::svg_file(&tree, self.svg())
+    }
+}
@@ -2,6 +2,7 @@ pub mod action;
 pub mod args;
 pub mod blackboard;
 pub mod builder;
+pub mod clock;
 pub mod context;
 pub mod forester;
 pub mod rtree;
@@ -70,6 +71,11 @@ impl From<serde_json::Error> for RuntimeError {
         RuntimeError::IOError(value.to_string())
     }
 }
+impl From<bincode::Error> for RuntimeError {
+    fn from(value: bincode::Error) -> Self {
+        RuntimeError::IOError(value.to_string())
+    }
+}
 impl From<std::io::Error> for RuntimeError {
     fn from(value: std::io::Error) -> Self {
         RuntimeError::IOError(value.to_string())
@@ -6,6 +6,7 @@ pub mod keeper;
 use crate::runtime::args::{RtArgs, RtValue};
 use crate::runtime::context::TreeContext;
 use crate::runtime::{RtResult, RuntimeError, TickResult};
+use crate::tree::parser::ast::arg::Params;
 use std::collections::HashMap;
 
 pub type ActionName = String;
@@ -32,15 +33,33 @@ impl Action {
             Action::Async(aa) => aa.tick(args, ctx),
         }
     }
+    pub fn params(&self) -> Option<Params> {
+        match self {
+            Action::Impl(a) => a.params(),
+            Action::Async(aa) => aa.params(),
+        }
+    }
 }
 
 pub trait Impl {
     fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick;
+
+    /// The parameters the action expects.
+    /// If they are declared, they get checked against the `impl` definition in the tree
+    /// when the forester is built.
+    fn params(&self) -> Option<Params> {
+        None
+    }
 }
 
 pub trait ImplAsync {
     fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick;
     fn halt(&self, ctx: &mut TreeContext) -> Tick;
+
+    /// The same as `Impl::params`
+    fn params(&self) -> Option<Params> {
+        None
+    }
 }
 
 impl From<Box<dyn Impl>> for Action {
@@ -1,11 +1,63 @@
 use crate::runtime::action::flow::{run_with, CURSOR, LEN, REASON};
+use crate::runtime::action::keeper::ActionKeeper;
 use crate::runtime::action::Tick;
+use crate::runtime::args::eval::{eval_bool, resolve};
 use crate::runtime::args::{RtArgs, RtArgument, RtValue, RtValueNumber};
 use crate::runtime::context::{RNodeState, TreeContext};
 use crate::runtime::rtree::rnode::DecoratorType;
 use crate::runtime::{RtOk, RtResult, RuntimeError, TickResult};
-use std::thread::sleep;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// the default limit for the timeout in milliseconds
+pub const DEFAULT_TIMEOUT: i64 = 1000;
+/// the time(millis) when the timeout or delay started to measure the child.
+/// It is passed between ticks while the decorator is running.
+pub const START: &str = "start";
+/// the number of the failed attempts for the retry_backoff
+pub const ATTEMPT: &str = "attempt";
+/// the time(millis) the retry_backoff waits before the next attempt
+pub const WAIT: &str = "wait";
+/// the time(millis) of the latest success of the child for the cooldown
+pub const LAST: &str = "last";
+/// the times(millis) when the rate_limit started the child within the window
+pub const RUNS: &str = "runs";
+/// denotes that the rate_limit has already started the child and it is running
+pub const ACTIVE: &str = "active";
+/// the cached result of the child for the run_once
+pub const RESULT: &str = "result";
+
+/// The user-defined decorator.
+/// It gets declared in the tree as `decorator name(params);`,
+/// registered in the `ForesterBuilder` by the same name
+/// and follows the same stages as the built-in decorators.
+/// The child that is still running gets halted when `prepare` or `monitor` finishes the decorator.
+pub trait Decorator {
+    /// runs when the child is ready but not running.
+    fn prepare(
+        &self,
+        _init_args: RtArgs,
+        tick_args: RtArgs,
+        _ctx: &mut TreeContext,
+    ) -> RtResult<RNodeState> {
+        Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1))))
+    }
+    /// runs when the child returns running.
+    fn monitor(
+        &self,
+        _init_args: RtArgs,
+        tick_args: RtArgs,
+        _ctx: &mut TreeContext,
+    ) -> RtResult<RNodeState> {
+        Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1))))
+    }
+    /// runs when the child is finished and decides what the decorator returns.
+    fn finalize(
+        &self,
+        init_args: RtArgs,
+        tick_args: RtArgs,
+        child_res: TickResult,
+        ctx: &mut TreeContext,
+    ) -> RtResult<RNodeState>;
+}
 
 // It runs on the preparation stage when the child is ready but not running.
 // It is useful to save some information before(counters, timeout etc)
@@ -14,19 +66,103 @@ pub(crate) fn prepare(
     init_args: RtArgs,
     tick_args: RtArgs,
     ctx: &mut TreeContext,
+    keeper: &ActionKeeper,
 ) -> RtResult<RNodeState> {
     debug!(target:"> decorator::prepare", "tick:{}, type:{}",ctx.curr_ts(), tpe);
+    let init_args = resolve_args(tpe, init_args, ctx)?;
     match tpe {
-        DecoratorType::Delay => {
-            sleep(Duration::from_millis(
-                u64::try_from(get_delay(init_args)?).unwrap(),
-            ));
-            Ok(RNodeState::Running(run_with(tick_args, 0, 1)))
+        DecoratorType::Delay | DecoratorType::Timeout => Ok(RNodeState::Running(
+            start_args(tick_args, ctx).with(LEN, RtValue::int(1)),
+        )),
+        DecoratorType::Cooldown => {
+            let cooldown = int_at(&init_args, 0).unwrap_or(0);
+            match tick_args.find(LAST.to_string()).and_then(RtValue::as_int) {
+                Some(last) if ctx.clock().millis() - last < cooldown => {
+                    Ok(RNodeState::Failure(run_with(tick_args, 0, 1).with(
+                        REASON,
+                        RtValue::str(format!("the cooldown {cooldown} is not passed")),
+                    )))
+                }
+                _ => Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1)))),
+            }
+        }
+        DecoratorType::RateLimit => {
+            if tick_args.find(ACTIVE.to_string()).is_some() {
+                return Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1))));
+            }
+            let limit = int_at(&init_args, 0).unwrap_or(0);
+            let window = int_at(&init_args, 1).unwrap_or(0);
+            let now = ctx.clock().millis();
+            let mut runs = recent_runs(&tick_args, now, window);
+            if runs.len() as i64 >= limit {
+                Ok(RNodeState::Failure(
+                    run_with(RtArgs::default(), 0, 1)
+                        .with(RUNS, RtValue::Array(runs))
+                        .with(
+                            REASON,
+                            RtValue::str(format!("the rate limit {limit} per {window} exceeded")),
+                        ),
+                ))
+            } else {
+                runs.push(RtValue::int(now));
+                Ok(RNodeState::Running(
+                    run_with(RtArgs::default(), 0, 1)
+                        .with(RUNS, RtValue::Array(runs))
+                        .with(ACTIVE, RtValue::Bool(true)),
+                ))
+            }
+        }
+        DecoratorType::Guard => {
+            let expr = init_args
+                .first_as(RtValue::as_expr)
+                .ok_or(RuntimeError::uex(format!(
+                    "the decorator guard does not have an expression"
+                )))?;
+            let reason = match eval_bool(&expr, ctx.bb()) {
+                Ok(true) => return Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1)))),
+                Ok(false) => format!("the guard {expr} is false"),
+                // the absent key means the condition is not met yet
+                Err(RuntimeError::BlackBoardError(e)) => e,
+                Err(e) => return Err(e),
+            };
+            Ok(RNodeState::Failure(
+                run_with(tick_args, 0, 1).with(REASON, RtValue::str(reason)),
+            ))
         }
-        DecoratorType::Timeout => Ok(RNodeState::Running(start_args().with(LEN, RtValue::int(1)))),
+        DecoratorType::RunOnce => match tick_args
+            .find(RESULT.to_string())
+            .and_then(RtValue::as_bool)
+        {
+            Some(true) => Ok(RNodeState::Success(tick_args)),
+            Some(false) => Ok(RNodeState::Failure(tick_args)),
+            None => Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1)))),
+        },
+        DecoratorType::Custom(name) => keeper.decorator(name)?.prepare(init_args, tick_args, ctx),
         _ => Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1)))),
     }
 }
+// It runs when the decorator is running but the child is not started yet.
+// The delay keeps the child waiting until the time has passed,
+// giving the control back to the parent meanwhile so the other nodes and ticks can proceed.
+// Returns the milliseconds left to wait if the child is kept waiting.
+pub(crate) fn is_waiting(
+    tpe: &DecoratorType,
+    init_args: &RtArgs,
+    tick_args: &RtArgs,
+    ctx: &mut TreeContext,
+) -> RtResult<Option<i64>> {
+    let wait = match tpe {
+        DecoratorType::Delay => get_delay(resolve_args(tpe, init_args.clone(), ctx)?)?,
+        DecoratorType::RetryBackoff => match tick_args.find(WAIT.to_string()) {
+            Some(wait) => wait.as_int().unwrap_or(0),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    let left = wait - elapsed(tick_args, ctx)?;
+    Ok(if left > 0 { Some(left) } else { None })
+}
+
 // This runs when the child returns running.
 // It works for timeout and other controlling decorators
 pub(crate) fn monitor(
@@ -34,28 +170,19 @@ pub(crate) fn monitor(
     init_args: RtArgs,
     tick_args: RtArgs,
     ctx: &mut TreeContext,
+    keeper: &ActionKeeper,
 ) -> RtResult<RNodeState> {
     debug!(target:"> decorator::monitor", "tick:{}, type:{}",ctx.curr_ts(), tpe);
+    let init_args = resolve_args(tpe, init_args, ctx)?;
     match tpe {
-        DecoratorType::Timeout => {
-            let timeout = init_args.first_as(RtValue::as_int).unwrap_or(0);
-            let err = format!("the decorator timeout does not have a start time");
-            let args = tick_args.clone();
-            let start = args
-                .first_as(RtValue::as_int)
-                .ok_or(RuntimeError::uex(err))?;
-            let curr = get_ts();
-            if curr - start >= timeout {
-                let args = run_with(tick_args, 0, 1).with(
-                    REASON,
-                    RtValue::str(format!("the timeout {timeout} exceeded")),
-                );
-
-                Ok(RNodeState::Failure(args))
-            } else {
-                Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1))))
-            }
-        }
+        DecoratorType::Timeout => match timeout_exceeded(&init_args, &tick_args, ctx)? {
+            Some(timeout) => Ok(RNodeState::Failure(run_with(RtArgs::default(), 0, 1).with(
+                REASON,
+                RtValue::str(format!("the timeout {timeout} exceeded")),
+            ))),
+            None => Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1)))),
+        },
+        DecoratorType::Custom(name) => keeper.decorator(name)?.monitor(init_args, tick_args, ctx),
         _ => Ok(RNodeState::Running(tick_args.with(LEN, RtValue::int(1)))),
     }
 }
@@ -67,8 +194,10 @@ pub(crate) fn finalize(
     init_args: RtArgs,
     child_res: TickResult,
     ctx: &mut TreeContext,
+    keeper: &ActionKeeper,
 ) -> RtResult<RNodeState> {
     debug!(target:"> decorator::fin", "tick:{}, type:{}",ctx.curr_ts(), tpe);
+    let init_args = resolve_args(tpe, init_args, ctx)?;
     match tpe {
         DecoratorType::Inverter => match child_res {
             TickResult::Success => {
@@ -93,9 +222,10 @@ pub(crate) fn finalize(
             ))),
         },
         DecoratorType::Repeat => {
+            // the negative count means the child is repeated infinitely
             let count = init_args.first_as(RtValue::as_int).unwrap_or(1);
             let attempt = tick_args.first_as(RtValue::as_int).unwrap_or(1);
-            if attempt >= count {
+            if count >= 0 && attempt >= count {
                 Ok(RNodeState::Success(run_with(tick_args, 0, 1)))
             } else {
                 let args = RtArgs(vec![RtArgument::new_noname(RtValue::int(attempt + 1))]);
@@ -103,27 +233,18 @@ pub(crate) fn finalize(
             }
         }
         DecoratorType::Timeout => match child_res {
-            TickResult::Running => {
-                let timeout = init_args.first_as(RtValue::as_int).unwrap_or(0);
-                let err = format!("the decorator timeout does not have a start time");
-                let args = tick_args.clone();
-                let start = args
-                    .first_as(RtValue::as_int)
-                    .ok_or(RuntimeError::uex(err))?;
-                let curr = get_ts();
-                if curr - start >= timeout {
-                    let args = run_with(tick_args, 0, 1)
-                        .with(REASON, RtValue::str("decorator force fail.".to_string()));
-                    Ok(RNodeState::Failure(args))
-                } else {
-                    Ok(RNodeState::Running(run_with(tick_args, 0, 1)))
-                }
-            }
-            r => Ok(RNodeState::from(run_with(tick_args, 1, 1), r)),
+            TickResult::Running => match timeout_exceeded(&init_args, &tick_args, ctx)? {
+                Some(timeout) => Ok(RNodeState::Failure(run_with(RtArgs::default(), 0, 1).with(
+                    REASON,
+                    RtValue::str(format!("the timeout {timeout} exceeded")),
+                ))),
+                None => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
+            },
+            r => Ok(RNodeState::from(run_with(RtArgs::default(), 1, 1), r)),
         },
         DecoratorType::Delay => match child_res {
             TickResult::Running => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
-            r => Ok(RNodeState::from(run_with(tick_args, 0, 1), r)),
+            r => Ok(RNodeState::from(run_with(RtArgs::default(), 0, 1), r)),
         },
         DecoratorType::Retry => match child_res {
             TickResult::Success => Ok(RNodeState::Success(run_with(tick_args, 1, 1))),
@@ -141,20 +262,156 @@ pub(crate) fn finalize(
             }
             TickResult::Running => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
         },
+        DecoratorType::RetryBackoff => match child_res {
+            TickResult::Success => Ok(RNodeState::Success(run_with(RtArgs::default(), 1, 1))),
+            TickResult::Failure(v) => {
+                let attempts = int_at(&init_args, 0).unwrap_or(0);
+                let attempt = tick_args
+                    .find(ATTEMPT.to_string())
+                    .and_then(RtValue::as_int)
+                    .unwrap_or(0);
+                if attempt >= attempts {
+                    let args = run_with(RtArgs::default(), 0, 1).with(REASON, RtValue::str(v));
+                    Ok(RNodeState::Failure(args))
+                } else {
+                    let base = int_at(&init_args, 1).unwrap_or(0);
+                    let factor = float_at(&init_args, 2).unwrap_or(1.0);
+                    let wait = (base as f64 * factor.powi(attempt as i32)) as i64;
+                    let args = run_with(RtArgs::default(), 0, 1)
+                        .with(ATTEMPT, RtValue::int(attempt + 1))
+                        .with(START, RtValue::int(ctx.clock().millis()))
+                        .with(WAIT, RtValue::int(wait));
+                    Ok(RNodeState::Running(args))
+                }
+            }
+            TickResult::Running => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
+        },
+        DecoratorType::Cooldown => match child_res {
+            TickResult::Success => Ok(RNodeState::Success(
+                run_with(RtArgs::default(), 0, 1).with(LAST, RtValue::int(ctx.clock().millis())),
+            )),
+            r => Ok(RNodeState::from(run_with(tick_args, 0, 1), r)),
+        },
+        DecoratorType::RateLimit => match child_res {
+            TickResult::Running => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
+            r => {
+                let runs = tick_args
+                    .find(RUNS.to_string())
+                    .unwrap_or(RtValue::Array(vec![]));
+                Ok(RNodeState::from(
+                    run_with(RtArgs::default(), 0, 1).with(RUNS, runs),
+                    r,
+                ))
+            }
+        },
+        DecoratorType::Guard => match child_res {
+            TickResult::Running => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
+            r => Ok(RNodeState::from(run_with(tick_args, 0, 1), r)),
+        },
+        DecoratorType::RunOnce => match child_res {
+            TickResult::Success => Ok(RNodeState::Success(
+                run_with(RtArgs::default(), 0, 1).with(RESULT, RtValue::Bool(true)),
+            )),
+            TickResult::Failure(v) => Ok(RNodeState::Failure(
+                run_with(RtArgs::default(), 0, 1)
+                    .with(RESULT, RtValue::Bool(false))
+                    .with(REASON, RtValue::str(v)),
+            )),
+            TickResult::Running => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
+        },
+        DecoratorType::KeepRunningUntilFailure => match child_res {
+            TickResult::Failure(v) => Ok(RNodeState::Failure(
+                run_with(tick_args, 0, 1).with(REASON, RtValue::str(v)),
+            )),
+            _ => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
+        },
+        DecoratorType::KeepRunningUntilSuccess => match child_res {
+            TickResult::Success => Ok(RNodeState::Success(run_with(tick_args, 0, 1))),
+            _ => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
+        },
+        DecoratorType::Custom(name) => keeper
+            .decorator(name)?
+            .finalize(init_args, tick_args, child_res, ctx),
     }
 }
 
-fn get_ts() -> i64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("")
-        .as_secs() as i64
+// keeps the start time if the child is still running since the previous tick
+fn start_args(tick_args: RtArgs, ctx: &TreeContext) -> RtArgs {
+    match tick_args.find(START.to_string()) {
+        Some(_) => tick_args,
+        None => tick_args.with(START, RtValue::int(ctx.clock().millis())),
+    }
 }
-fn start_args() -> RtArgs {
-    RtArgs(vec![RtArgument::new_noname(RtValue::Number(
-        RtValueNumber::Int(get_ts()),
-    ))])
+
+// returns the limit if the time passed since the start is equal or greater than it
+fn timeout_exceeded(
+    init_args: &RtArgs,
+    tick_args: &RtArgs,
+    ctx: &TreeContext,
+) -> RtResult<Option<i64>> {
+    let timeout = init_args
+        .first_as(RtValue::as_int)
+        .unwrap_or(DEFAULT_TIMEOUT);
+    if elapsed(tick_args, ctx)? >= timeout {
+        Ok(Some(timeout))
+    } else {
+        Ok(None)
+    }
 }
+
+// the time(millis) passed since the decorator started
+fn elapsed(tick_args: &RtArgs, ctx: &TreeContext) -> RtResult<i64> {
+    let start = tick_args
+        .find(START.to_string())
+        .and_then(RtValue::as_int)
+        .ok_or(RuntimeError::uex(format!(
+            "the decorator does not have a start time"
+        )))?;
+    Ok(ctx.clock().millis() - start)
+}
+// the times when the child was started within the window before now
+fn recent_runs(tick_args: &RtArgs, now: i64, window: i64) -> Vec<RtValue> {
+    tick_args
+        .find(RUNS.to_string())
+        .and_then(|v| v.as_vec(RtValue::as_int))
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .filter(|t| now - t < window)
+        .map(RtValue::int)
+        .collect()
+}
+
+// The built-in decorators take the numbers that can be given by the keys in the blackboard,
+// thus the keys are resolved into the values before the arguments are read.
+// The custom decorators get the arguments as they are.
+fn resolve_args(tpe: &DecoratorType, args: RtArgs, ctx: &mut TreeContext) -> RtResult<RtArgs> {
+    if let DecoratorType::Custom(_) = tpe {
+        return Ok(args);
+    }
+    let mut resolved = vec![];
+    for a in args.0 {
+        let name = a.clone().name();
+        let value = match a.val() {
+            RtValue::Pointer(key) => resolve(&key, ctx.bb())?,
+            v => v,
+        };
+        resolved.push(RtArgument::new(name, value));
+    }
+    Ok(RtArgs(resolved))
+}
+
+fn int_at(args: &RtArgs, idx: usize) -> Option<i64> {
+    args.0.get(idx).and_then(|a| a.clone().val().as_int())
+}
+
+fn float_at(args: &RtArgs, idx: usize) -> Option<f64> {
+    args.0.get(idx).and_then(|a| {
+        let v = a.clone().val();
+        v.clone().as_float().or(v.as_int().map(|i| i as f64))
+    })
+}
+
 fn get_delay(args: RtArgs) -> RtResult<i64> {
     let err = format!(
         "the decorator delay accepts one integer param, denoting duration of delay in millis"
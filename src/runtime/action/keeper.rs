@@ -1,17 +1,35 @@
+use crate::runtime::action::decorator::Decorator;
 use crate::runtime::action::{Action, ActionName};
 use crate::runtime::args::RtArgs;
 use crate::runtime::context::{RNodeState, TreeContext};
-use crate::runtime::{RtResult, RuntimeError};
-use std::collections::HashMap;
+use crate::runtime::rtree::rnode::Name;
+use crate::runtime::rtree::RuntimeTree;
+use crate::runtime::{RtOk, RtResult, RuntimeError};
+use crate::tree::parser::ast::arg::Params;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Default)]
 pub struct ActionKeeper {
     actions: HashMap<ActionName, Action>,
+    decorators: HashMap<Name, Box<dyn Decorator>>,
 }
 
 impl ActionKeeper {
     pub fn new(actions: HashMap<ActionName, Action>) -> Self {
-        Self { actions }
+        Self {
+            actions,
+            decorators: HashMap::default(),
+        }
+    }
+    pub fn with_decorators(
+        actions: HashMap<ActionName, Action>,
+        decorators: HashMap<Name, Box<dyn Decorator>>,
+    ) -> Self {
+        Self {
+            actions,
+            decorators,
+        }
     }
 }
 
@@ -22,8 +40,104 @@ impl ActionKeeper {
         )))
     }
 
+    pub fn decorator(&self, name: &Name) -> RtResult<&dyn Decorator> {
+        self.decorators
+            .get(name)
+            .map(|d| d.as_ref())
+            .ok_or(RuntimeError::uex(format!(
+                "the decorator {name} is not registered"
+            )))
+    }
+
     pub fn register(&mut self, name: ActionName, action: Action) -> RtResult<()> {
         &self.actions.insert(name, action);
         Ok(())
     }
+
+    /// Cross-checks the leaves of the tree against the registered actions.
+    /// ## Checks
+    ///  - every leaf has a registered action. All missing actions are reported at once.
+    ///  - every action declaring the params (see `Impl::params`)
+    ///    has the same names and types as in the `impl` definition from `definitions`.
+    ///  - the registered actions that are not used in the tree are logged as warnings.
+    ///  - every user-defined decorator used in the tree is registered.
+    pub fn validate(&self, tree: &RuntimeTree, definitions: &HashMap<ActionName, Params>) -> RtOk {
+        let leaves = tree.leaves();
+
+        let missing_decorators: Vec<&Name> = tree
+            .custom_decorators()
+            .into_iter()
+            .filter(|name| !self.decorators.contains_key(*name))
+            .sorted()
+            .collect();
+
+        if !missing_decorators.is_empty() {
+            return Err(RuntimeError::UnImplementedAction(format!(
+                "the decorators {} are not registered",
+                missing_decorators.iter().join(", ")
+            )));
+        }
+
+        let missing: Vec<&ActionName> = leaves
+            .iter()
+            .filter(|name| !self.actions.contains_key(**name))
+            .copied()
+            .sorted()
+            .collect();
+
+        let extra: Vec<&ActionName> = self
+            .actions
+            .keys()
+            .filter(|name| !leaves.contains(name))
+            .sorted()
+            .collect();
+
+        if !extra.is_empty() {
+            warn!(
+                "the actions {} are registered but not used in the tree",
+                extra.iter().join(", ")
+            );
+        }
+
+        if !missing.is_empty() {
+            return Err(RuntimeError::UnImplementedAction(format!(
+                "the actions {} are not registered",
+                missing.iter().join(", ")
+            )));
+        }
+
+        let mut errors = vec![];
+        for name in leaves.iter().sorted() {
+            let declared = self.actions.get(*name).and_then(Action::params);
+            if let (Some(declared), Some(defined)) = (declared, definitions.get(*name)) {
+                if !declared.same_signature(defined) {
+                    errors.push(format!(
+                        "the action {name} expects ({}) but the definition has ({})",
+                        params_to_str(&declared),
+                        params_to_str(defined)
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(RuntimeError::WrongArgument(errors.join("; ")))
+        }
+    }
+}
+
+fn params_to_str(params: &Params) -> String {
+    params
+        .params
+        .iter()
+        .map(|p| {
+            if p.output {
+                format!("out {}:{:?}", p.name, p.tpe)
+            } else {
+                format!("{}:{:?}", p.name, p.tpe)
+            }
+        })
+        .join(",")
 }
@@ -1,12 +1,15 @@
 pub mod display;
+pub mod eval;
 pub mod transform;
 use crate::runtime::blackboard::{BBKey, BlackBoard};
 use crate::runtime::rtree::rnode::DecoratorType;
+use crate::runtime::rtree::sorted_map;
 use crate::runtime::{RtResult, RuntimeError};
 use crate::tree::parser::ast::arg::{
     Argument, ArgumentRhs, Arguments, ArgumentsType, MesType, Param, Params,
 };
 use crate::tree::parser::ast::call::Call;
+use crate::tree::parser::ast::expr::Expr;
 use crate::tree::parser::ast::message::{Message, Number};
 use crate::tree::parser::ast::Key;
 use crate::tree::{cerr, TreeError};
@@ -40,10 +43,16 @@ pub enum RtValue {
     String(String),
     Bool(bool),
     Array(Vec<RtValue>),
+    #[serde(serialize_with = "sorted_map")]
     Object(HashMap<String, RtValue>),
     Number(RtValueNumber),
     Pointer(BBKey),
+    /// the reference `{key}` to the blackboard that gets resolved right before the tick.
+    /// The value is expected to have the type of the parameter.
+    Ref(BBKey, MesType),
     Call(Call),
+    /// the expression that gets evaluated against the blackboard at tick time
+    Expr(Expr),
 }
 
 pub struct RtValueCast<'a> {
@@ -86,6 +95,9 @@ impl RtValue {
     pub fn int(i: i64) -> Self {
         RtValue::Number(RtValueNumber::Int(i))
     }
+    pub fn float(f: f64) -> Self {
+        RtValue::Number(RtValueNumber::Float(f))
+    }
     pub fn str(s: String) -> Self {
         RtValue::String(s)
     }
@@ -139,6 +151,12 @@ impl RtValue {
         }
     }
 
+    pub fn as_expr(self) -> Option<Expr> {
+        match self {
+            RtValue::Expr(e) => Some(e),
+            _ => None,
+        }
+    }
     pub fn as_pointer(self) -> Option<String> {
         match self {
             RtValue::Pointer(k) => Some(k),
@@ -159,6 +177,20 @@ impl RtValue {
             v => Ok(v),
         }
     }
+
+    /// the value can be passed to the parameter of the given type.
+    /// The enums are passed as the names of the variants.
+    pub fn fits(&self, tpe: &MesType) -> bool {
+        match (self, tpe) {
+            (RtValue::Number(_), MesType::Num) => true,
+            (RtValue::String(_), MesType::String | MesType::Enum(_)) => true,
+            (RtValue::Bool(_), MesType::Bool) => true,
+            (RtValue::Array(_), MesType::Array) => true,
+            (RtValue::Array(elems), MesType::ArrayOf(t)) => elems.iter().all(|e| e.fits(t)),
+            (RtValue::Object(_), MesType::Object) => true,
+            _ => false,
+        }
+    }
 }
 
 impl From<Message> for RtValue {
@@ -171,11 +203,13 @@ impl From<Message> for RtValue {
             Message::Object(elems) => {
                 RtValue::Object(elems.into_iter().map(|(k, v)| (k, v.into())).collect())
             }
+            // the action gets the name of the variant
+            Message::Enum(_, variant) => RtValue::String(variant),
         }
     }
 }
 
-#[derive(Default, Debug, PartialEq, Clone)]
+#[derive(Default, Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct RtArgs(pub Vec<RtArgument>);
 
 impl RtArgs {
@@ -228,7 +262,7 @@ impl Display for RtArgs {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct RtArgument {
     name: RtAKey,
     value: RtValue,
@@ -259,7 +293,9 @@ impl Display for RtValue {
             }
             RtValue::Number(n) => f.write_str(format!("{}", n).as_str())?,
             RtValue::Pointer(p) => f.write_str(format!("{}", p).as_str())?,
+            RtValue::Ref(key, _) => f.write_str(format!("{{{}}}", key).as_str())?,
             RtValue::Call(_) => f.write_str(format!("<Call>>").as_str())?,
+            RtValue::Expr(e) => f.write_str(format!("{}", e).as_str())?,
         }
         Ok(())
     }
@@ -293,11 +329,29 @@ impl RtArgument {
     }
 
     pub fn try_from(a: ArgumentRhs, p: Param) -> Result<Option<RtArgument>, TreeError> {
-        let _ = RtArgument::validate_type(a.clone(), p.tpe)?;
+        let _ = RtArgument::validate_type(a.clone(), p.tpe.clone())?;
+        if p.output {
+            // the action gets the key to write the result into
+            return match a {
+                ArgumentRhs::BBRef(key) | ArgumentRhs::Id(key) => {
+                    Ok(Some(RtArgument::new(p.name, RtValue::Pointer(key))))
+                }
+                a => Err(cerr(format!(
+                    "the output parameter {} expects a reference to the blackboard but got {a}",
+                    p.name
+                ))),
+            };
+        }
         match &a {
+            // the reference gets resolved into the value right before the tick
+            ArgumentRhs::BBRef(key) => Ok(Some(RtArgument::new(
+                p.name,
+                RtValue::Ref(key.clone(), p.tpe),
+            ))),
             ArgumentRhs::Id(id) => Ok(Some(RtArgument::new(p.name, RtValue::Pointer(id.clone())))),
             ArgumentRhs::Mes(m) => Ok(Some(RtArgument::new(p.name, m.clone().into()))),
             ArgumentRhs::Call(c) => Ok(Some(RtArgument::new(p.name, RtValue::Call(c.clone())))),
+            ArgumentRhs::Expr(e) => Ok(Some(RtArgument::new(p.name, RtValue::Expr(e.clone())))),
         }
     }
     pub fn validate_type(arg: ArgumentRhs, param: MesType) -> Result<(), TreeError> {
@@ -311,9 +365,18 @@ impl RtArgument {
             (ArgumentRhs::Call(_), MesType::Tree) => Ok(()),
             (ArgumentRhs::Id(_), MesType::Tree) => error("pointer", "call"),
             (ArgumentRhs::Mes(_), MesType::Tree) => error("message", "call"),
+            (ArgumentRhs::Expr(_), MesType::Tree) => error("expression", "call"),
+            (ArgumentRhs::BBRef(_), MesType::Tree) => error("reference", "call"),
 
             (ArgumentRhs::Call(_), m) => error("call", format!("{:?}", m).as_str()),
             (ArgumentRhs::Id(_), _) => Ok(()),
+            (ArgumentRhs::Expr(e), m_t) => match e.tpe() {
+                Some(tpe) if !tpe.fits(&m_t) => {
+                    error(format!("{}", tpe).as_str(), format!("{:?}", m_t).as_str())
+                }
+                _ => Ok(()),
+            },
+            (ArgumentRhs::BBRef(_), _) => Ok(()),
 
             (ArgumentRhs::Mes(m), m_t) if m.same(&m_t) => Ok(()),
             (ArgumentRhs::Mes(m), m_t) => {
@@ -14,9 +14,10 @@ impl<'a> Display for ShortDisplayedRtArgument<'a> {
         let short_mes = |m: &RtValue| match m {
             RtValue::Array(_) => "[..]".to_string(),
             RtValue::Object(_) => "{..}".to_string(),
-            RtValue::Call(Call::Lambda(tpe, _)) => format!("{tpe}.."),
-            RtValue::Call(Call::Decorator(tpe, args, _)) => format!("{tpe}({args})"),
-            RtValue::Call(Call::Invocation(key, _)) => format!("{key}(<>)"),
+            RtValue::Call(Call::Lambda(tpe, _, _)) => format!("{tpe}.."),
+            RtValue::Call(Call::Decorator(tpe, args, _, _)) => format!("{tpe}({args})"),
+            RtValue::Call(Call::CustomDecorator(name, args, _, _)) => format!("{name}({args})"),
+            RtValue::Call(Call::Invocation(key, _, _)) => format!("{key}(<>)"),
             m => format!("{}", m),
         };
 
@@ -0,0 +1,152 @@
+use crate::runtime::args::{RtArgs, RtArgument, RtValue, RtValueNumber};
+use crate::runtime::blackboard::BlackBoard;
+use crate::runtime::{RtResult, RuntimeError};
+use crate::tree::parser::ast::expr::{BinOp, Expr};
+use std::cmp::Ordering;
+
+/// Evaluates the expression against the blackboard.
+/// The identifiers are the keys in the blackboard, following the pointers if the value is a pointer.
+/// ## Errors
+///  - `BlackBoardError` if the key is absent in the blackboard.
+///  - `WrongArgument` if the operands have the types that are not supported by the operation.
+pub fn eval(expr: &Expr, bb: &BlackBoard) -> RtResult<RtValue> {
+    match expr {
+        Expr::Id(key) => resolve(key, bb),
+        Expr::Mes(m) => Ok(m.clone().into()),
+        Expr::Not(e) => Ok(RtValue::Bool(!eval_bool(e, bb)?)),
+        Expr::Binary(lhs, BinOp::And, rhs) => {
+            Ok(RtValue::Bool(eval_bool(lhs, bb)? && eval_bool(rhs, bb)?))
+        }
+        Expr::Binary(lhs, BinOp::Or, rhs) => {
+            Ok(RtValue::Bool(eval_bool(lhs, bb)? || eval_bool(rhs, bb)?))
+        }
+        Expr::Neg(e) => match eval(e, bb)? {
+            RtValue::Number(RtValueNumber::Float(v)) => Ok(RtValue::float(-v)),
+            RtValue::Number(n) => Ok(RtValue::int(-as_i64(&n))),
+            v => Err(RuntimeError::WrongArgument(format!(
+                "the value {v} can not be negated"
+            ))),
+        },
+        Expr::Binary(lhs, op, rhs) if op.is_arithmetic() => {
+            calculate(eval(lhs, bb)?, *op, eval(rhs, bb)?)
+        }
+        Expr::Binary(lhs, op, rhs) => {
+            compare(eval(lhs, bb)?, *op, eval(rhs, bb)?).map(RtValue::Bool)
+        }
+    }
+}
+
+/// Evaluates the arguments that are expressions or references to the blackboard,
+/// leaving the other ones untouched.
+/// The value of the reference should have the type of the parameter.
+pub fn eval_args(args: RtArgs, bb: &BlackBoard) -> RtResult<RtArgs> {
+    let mut evaluated = vec![];
+    for RtArgument { name, value } in args.0 {
+        let value = match value {
+            RtValue::Expr(e) => eval(&e, bb)?,
+            RtValue::Ref(key, tpe) => {
+                let v = resolve(&key, bb)?;
+                if !v.fits(&tpe) {
+                    return Err(RuntimeError::WrongArgument(format!(
+                        "the value {v} of the key {key} does not fit the type {tpe} of the parameter {name}"
+                    )));
+                }
+                v
+            }
+            v => v,
+        };
+        evaluated.push(RtArgument::new(name, value));
+    }
+    Ok(RtArgs(evaluated))
+}
+
+pub fn eval_bool(expr: &Expr, bb: &BlackBoard) -> RtResult<bool> {
+    let v = eval(expr, bb)?;
+    v.clone()
+        .as_bool()
+        .ok_or(RuntimeError::WrongArgument(format!(
+            "the expression {expr} is expected to be bool but got {v}"
+        )))
+}
+
+pub(crate) fn resolve(key: &String, bb: &BlackBoard) -> RtResult<RtValue> {
+    let absent = |k: &String| RuntimeError::bb(format!("the key {k} is absent in the blackboard"));
+    let mut value = bb.get(key.clone())?.cloned().ok_or(absent(key))?;
+    while let RtValue::Pointer(p) = value {
+        value = bb.get(p.clone())?.cloned().ok_or(absent(&p))?;
+    }
+    Ok(value)
+}
+
+fn compare(lhs: RtValue, op: BinOp, rhs: RtValue) -> RtResult<bool> {
+    let ord = match (&lhs, &rhs) {
+        (RtValue::Number(l), RtValue::Number(r)) => as_f64(l).partial_cmp(&as_f64(r)),
+        (RtValue::String(l), RtValue::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    };
+    match (op, ord) {
+        (BinOp::Eq, Some(o)) => Ok(o == Ordering::Equal),
+        (BinOp::Ne, Some(o)) => Ok(o != Ordering::Equal),
+        (BinOp::Gt, Some(o)) => Ok(o == Ordering::Greater),
+        (BinOp::Ge, Some(o)) => Ok(o != Ordering::Less),
+        (BinOp::Lt, Some(o)) => Ok(o == Ordering::Less),
+        (BinOp::Le, Some(o)) => Ok(o != Ordering::Greater),
+        (BinOp::Eq, None) => Ok(lhs == rhs),
+        (BinOp::Ne, None) => Ok(lhs != rhs),
+        (op, _) => Err(RuntimeError::WrongArgument(format!(
+            "the values {lhs} and {rhs} can not be compared with {op}"
+        ))),
+    }
+}
+
+fn calculate(lhs: RtValue, op: BinOp, rhs: RtValue) -> RtResult<RtValue> {
+    match (&lhs, op, &rhs) {
+        (RtValue::String(l), BinOp::Add, r) => Ok(RtValue::str(format!("{l}{r}"))),
+        (l, BinOp::Add, RtValue::String(r)) => Ok(RtValue::str(format!("{l}{r}"))),
+        (RtValue::Number(l @ RtValueNumber::Float(_)), _, RtValue::Number(r))
+        | (RtValue::Number(l), _, RtValue::Number(r @ RtValueNumber::Float(_))) => {
+            let (l, r) = (as_f64(l), as_f64(r));
+            let res = match op {
+                BinOp::Add => l + r,
+                BinOp::Sub => l - r,
+                BinOp::Mul => l * r,
+                BinOp::Div => l / r,
+                _ => l % r,
+            };
+            Ok(RtValue::float(res))
+        }
+        (RtValue::Number(l), _, RtValue::Number(r)) => {
+            let (l, r) = (as_i64(l), as_i64(r));
+            let res = match op {
+                BinOp::Add => l.checked_add(r),
+                BinOp::Sub => l.checked_sub(r),
+                BinOp::Mul => l.checked_mul(r),
+                BinOp::Div => l.checked_div(r),
+                _ => l.checked_rem(r),
+            };
+            res.map(RtValue::int)
+                .ok_or(RuntimeError::WrongArgument(format!(
+                    "the operation {lhs} {op} {rhs} overflows or divides by zero"
+                )))
+        }
+        _ => Err(RuntimeError::WrongArgument(format!(
+            "the values {lhs} and {rhs} can not be calculated with {op}"
+        ))),
+    }
+}
+
+fn as_i64(n: &RtValueNumber) -> i64 {
+    match n {
+        RtValueNumber::Int(v) | RtValueNumber::Hex(v) => *v,
+        RtValueNumber::Float(v) => *v as i64,
+        RtValueNumber::Binary(v) => *v as i64,
+    }
+}
+
+fn as_f64(n: &RtValueNumber) -> f64 {
+    match n {
+        RtValueNumber::Int(v) | RtValueNumber::Hex(v) => *v as f64,
+        RtValueNumber::Float(v) => *v,
+        RtValueNumber::Binary(v) => *v as f64,
+    }
+}
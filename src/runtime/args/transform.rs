@@ -33,6 +33,39 @@ pub fn to_dec_rt_args(tpe: &DecoratorType, args: Arguments) -> Result<RtArgs, Tr
         },
         _ => Err(cerr("decorator has only one argument".to_string())),
     };
+    let one_expr = |args: &Arguments| match args.args.as_slice() {
+        [a] => match a.value() {
+            ArgumentRhs::Expr(e) => Ok(RtArgs(vec![RtArgument::new_noname(RtValue::Expr(
+                e.clone(),
+            ))])),
+            e => Err(cerr(format!(
+                "decorator {tpe} accepts only an expression but got {e}"
+            ))),
+        },
+        _ => Err(cerr(format!("decorator {tpe} has only one argument"))),
+    };
+    let nums = |args: &Arguments, n: usize| {
+        if args.args.len() != n {
+            return Err(cerr(format!("decorator {tpe} has {n} arguments")));
+        }
+        let mut rt_args = vec![];
+        for a in args.args.iter() {
+            match a.value() {
+                ArgumentRhs::Id(id) => {
+                    rt_args.push(RtArgument::new_noname(RtValue::Pointer(id.to_string())))
+                }
+                ArgumentRhs::Mes(Message::Num(n)) => {
+                    rt_args.push(RtArgument::new_noname(RtValue::Number((*n).into())))
+                }
+                e => {
+                    return Err(cerr(format!(
+                        "decorator {tpe} accepts either id or num but got {e}"
+                    )))
+                }
+            }
+        }
+        Ok(RtArgs(rt_args))
+    };
 
     match tpe {
         DecoratorType::Inverter => empty(&args),
@@ -42,11 +75,22 @@ pub fn to_dec_rt_args(tpe: &DecoratorType, args: Arguments) -> Result<RtArgs, Tr
         DecoratorType::Retry => one_num(&args),
         DecoratorType::Timeout => one_num(&args),
         DecoratorType::Delay => one_num(&args),
+        DecoratorType::RetryBackoff => nums(&args, 3),
+        DecoratorType::Cooldown => one_num(&args),
+        DecoratorType::RateLimit => nums(&args, 2),
+        DecoratorType::Guard => one_expr(&args),
+        DecoratorType::RunOnce => empty(&args),
+        DecoratorType::KeepRunningUntilFailure => empty(&args),
+        DecoratorType::KeepRunningUntilSuccess => empty(&args),
+        DecoratorType::Custom(name) => Err(cerr(format!(
+            "the arguments of the decorator {name} are defined by its declaration"
+        ))),
     }
 }
 
 pub fn to_rt_args(name: &str, args: Arguments, params: Params) -> Result<RtArgs, TreeError> {
-    if args.args.len() != params.params.len() {
+    let required = params.params.iter().filter(|p| p.is_required()).count();
+    if args.args.len() > params.params.len() || args.args.len() < required {
         Err(cerr(format!(
             "the call {} doesn't have the same number of arguments and parameters",
             name
@@ -55,32 +99,68 @@ pub fn to_rt_args(name: &str, args: Arguments, params: Params) -> Result<RtArgs,
         let mut rt_args: Vec<RtArgument> = vec![];
         match args.get_type()? {
             ArgumentsType::Unnamed => {
-                for (a, p) in args.args.into_iter().zip(params.params) {
-                    if let Some(rt_a) = RtArgument::try_from(a.value().clone(), p)? {
+                let mut args = args.args.into_iter();
+                for p in params.params {
+                    let rt_a = match args.next() {
+                        Some(a) => RtArgument::try_from(a.value().clone(), p)?,
+                        None => omitted(name, p)?,
+                    };
+                    if let Some(rt_a) = rt_a {
                         rt_args.push(rt_a);
                     }
                 }
                 Ok(RtArgs(rt_args))
             }
             ArgumentsType::Named => {
-                let param_map: HashMap<String, Param> =
-                    HashMap::from_iter(params.params.into_iter().map(|p| (p.name.clone(), p)));
+                let mut param_map: HashMap<String, Param> =
+                    HashMap::from_iter(params.params.iter().map(|p| (p.name.clone(), p.clone())));
 
                 for a in args.args {
-                    let p = a.name().and_then(|n| param_map.get(n)).ok_or(cerr(format!(
-                        "the argument {a} does not correspond to the definition"
-                    )))?;
-                    if let Some(rt_a) = RtArgument::try_from(a.value().clone(), p.clone())? {
+                    let p = a
+                        .name()
+                        .and_then(|n| param_map.remove(n))
+                        .ok_or(cerr(format!(
+                            "the argument {a} does not correspond to the definition"
+                        )))?;
+                    if let Some(rt_a) = RtArgument::try_from(a.value().clone(), p)? {
+                        rt_args.push(rt_a);
+                    }
+                }
+                // the params that are not mentioned in the call keep the order of the definition
+                for p in params.params {
+                    if param_map.contains_key(&p.name) {
+                        if let Some(rt_a) = omitted(name, p)? {
+                            rt_args.push(rt_a);
+                        }
+                    }
+                }
+                Ok(RtArgs(rt_args))
+            }
+            ArgumentsType::Empty => {
+                for p in params.params {
+                    if let Some(rt_a) = omitted(name, p)? {
                         rt_args.push(rt_a);
                     }
                 }
                 Ok(RtArgs(rt_args))
             }
-            ArgumentsType::Empty => Ok(RtArgs(rt_args)),
         }
     }
 }
 
+/// The argument for the param that is omitted in the call:
+/// the default value, nothing for the optional param or an error otherwise.
+fn omitted(name: &str, p: Param) -> Result<Option<RtArgument>, TreeError> {
+    match p.default.clone() {
+        Some(m) => RtArgument::try_from(ArgumentRhs::Mes(m), p),
+        None if p.optional => Ok(None),
+        None => Err(cerr(format!(
+            "the call {name} does not have the argument for the parameter {}",
+            p.name
+        ))),
+    }
+}
+
 pub fn find_arg_value(
     key: &Key,
     params: &Params,
@@ -1,12 +1,16 @@
 use crate::runtime::action::builtin::data::{CheckEq, StoreData, StoreTick};
 use crate::runtime::action::builtin::ReturnResult;
+use crate::runtime::action::decorator::Decorator;
 use crate::runtime::action::keeper::ActionKeeper;
 use crate::runtime::action::{Action, ActionName};
 use crate::runtime::blackboard::BlackBoard;
+use crate::runtime::clock::{Clock, SystemClock};
 use crate::runtime::forester::Forester;
+use crate::runtime::rtree::rnode::Name;
 use crate::runtime::rtree::RuntimeTree;
 use crate::runtime::{RtResult, RuntimeError};
 use crate::tracer::Tracer;
+use crate::tree::project::typecheck::check_types;
 use crate::tree::project::{FileName, Project, TreeName};
 use std::collections::HashMap;
 use std::fmt::format;
@@ -14,24 +18,34 @@ use std::path::{Path, PathBuf};
 
 pub struct ForesterBuilder {
     actions: HashMap<ActionName, Action>,
+    decorators: HashMap<Name, Box<dyn Decorator>>,
     main_file: Option<FileName>,
     main: Option<TreeName>,
     root: Option<PathBuf>,
+    libs: Vec<PathBuf>,
     bb: BlackBoard,
     tracer: Tracer,
+    clock: Box<dyn Clock>,
     bb_load: Option<String>,
+    optimize: bool,
+    tree: Option<RuntimeTree>,
 }
 
 impl ForesterBuilder {
     pub fn new() -> Self {
         Self {
             actions: HashMap::default(),
+            decorators: HashMap::default(),
             main_file: None,
             main: None,
             root: None,
+            libs: vec![],
             bb: BlackBoard::default(),
             tracer: Tracer::noop(),
+            clock: Box::new(SystemClock::default()),
             bb_load: None,
+            optimize: false,
+            tree: None,
         }
     }
 
@@ -39,9 +53,23 @@ impl ForesterBuilder {
         self.actions.insert(name.to_string(), action);
     }
 
+    /// registers the user-defined decorator declared in the tree as `decorator name(..);`
+    pub fn register_decorator<T>(&mut self, name: &str, decorator: T)
+    where
+        T: Decorator + 'static,
+    {
+        self.decorators
+            .insert(name.to_string(), Box::new(decorator));
+    }
+
     pub fn root(&mut self, root: PathBuf) {
         self.root = Some(root);
     }
+    /// adds the library path to search the imported packages (`import "nav::follow.tree"`) in.
+    /// The paths from the manifest `forester.toml` in the root are added as well.
+    pub fn lib(&mut self, path: PathBuf) {
+        self.libs.push(path);
+    }
     pub fn main_file(&mut self, main_file: FileName) {
         self.main_file = Some(main_file);
     }
@@ -52,21 +80,51 @@ impl ForesterBuilder {
     pub fn tracer(&mut self, tr: Tracer) {
         self.tracer = tr;
     }
+    /// the clock for the time-related nodes. The `SystemClock` is used by default.
+    pub fn clock<T>(&mut self, clock: T)
+    where
+        T: Clock + 'static,
+    {
+        self.clock = Box::new(clock);
+    }
+    /// loads the blackboard from the dump, the relative path is taken from the root.
     pub fn bb_load(&mut self, bb: String) {
         self.bb_load = Some(bb);
     }
+    /// turns on the optimization of the runtime tree, see [`RuntimeTree::optimize`].
+    pub fn optimize(&mut self, optimize: bool) {
+        self.optimize = optimize;
+    }
+    /// takes the compiled tree instead of building it from the project,
+    /// the root and the main file are not needed then, see [`RuntimeTree::load`].
+    /// The types of the arguments of the actions are not checked against the definitions
+    /// since they are checked when the tree gets compiled.
+    pub fn runtime_tree(&mut self, tree: RuntimeTree) {
+        self.tree = Some(tree);
+    }
 
     pub fn build(self) -> RtResult<Forester> {
-        let project = match (self.main, self.root.clone(), self.main_file) {
-            (None, Some(root), Some(mf)) => Project::build(mf, root)?,
-            (Some(mt), Some(root), Some(mf)) => Project::build_with_root(mf, mt, root)?,
-            _ => {
-                return Err(RuntimeError::UnImplementedAction(format!(
-                    "not enough arguments to initialize the project"
-                )))
+        let (mut tree, action_params) = match self.tree {
+            Some(tree) => (tree, HashMap::new()),
+            None => {
+                let project = match (self.main, self.root.clone(), self.main_file) {
+                    (mt, Some(root), Some(mf)) => {
+                        Project::build_with_libs(mf, mt, root, self.libs)?
+                    }
+                    _ => {
+                        return Err(RuntimeError::UnImplementedAction(format!(
+                            "not enough arguments to initialize the project"
+                        )))
+                    }
+                };
+                check_types(&project)?;
+                let (tree, resolved) = RuntimeTree::build_resolved(&project)?;
+                (tree, resolved.action_params)
             }
         };
-        let tree = RuntimeTree::build(project)?;
+        if self.optimize {
+            tree.optimize()?;
+        }
         let mut actions = self.actions;
 
         for action_name in tree.std_nodes.iter() {
@@ -74,25 +132,33 @@ impl ForesterBuilder {
             actions.insert(action_name.clone(), action);
         }
 
+        let keeper = ActionKeeper::with_decorators(actions, self.decorators);
+        keeper.validate(&tree, &action_params)?;
+
         let mut bb = BlackBoard::default();
         if let Some(bb_load_dump) = self.bb_load {
             let file = PathBuf::from(bb_load_dump);
-            let file = if file.is_relative() {
-                let mut r = self.root.unwrap().clone();
-                r.push(file);
-                r
-            } else {
-                file
+            // the compiled tree does not need the root, thus it is checked only here
+            let file = match (file.is_relative(), self.root) {
+                (false, _) => file,
+                (true, Some(mut root)) => {
+                    root.push(file);
+                    root
+                }
+                (true, None) => {
+                    return Err(RuntimeError::IOError(format!(
+                        "the relative path {file:?} of the blackboard dump needs the root to be set"
+                    )))
+                }
             };
             bb.load(&file)?;
         };
 
-        Forester::new(
-            tree,
-            BlackBoard::default(),
-            ActionKeeper::new(actions),
-            self.tracer,
-        )
+        let mut tracer = self.tracer;
+        tracer.set_paths(&tree);
+        tracer.set_origins(&tree);
+
+        Forester::new(tree, BlackBoard::default(), keeper, tracer, self.clock)
     }
 }
 
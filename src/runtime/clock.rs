@@ -0,0 +1,66 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// The source of time for the tree.
+/// All time-related decorators (timeout, delay, etc) measure time in milliseconds using the clock
+/// that is available in the `TreeContext`.
+pub trait Clock {
+    /// the milliseconds passed since the clock was started.
+    /// The value should never go back.
+    fn millis(&self) -> i64;
+
+    /// waits for the given amount of milliseconds.
+    fn sleep(&self, millis: i64);
+}
+
+/// The monotonic clock based on `Instant`. It is used by default.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn millis(&self) -> i64 {
+        self.start.elapsed().as_millis() as i64
+    }
+
+    fn sleep(&self, millis: i64) {
+        if millis > 0 {
+            sleep(Duration::from_millis(millis as u64))
+        }
+    }
+}
+
+/// The clock that moves only when it is told to.
+/// The clones share the same time so the clock can be passed to the forester
+/// and advanced from the outside (tests, simulator).
+/// The method `sleep` does not block but advances the time instantly.
+#[derive(Default, Clone)]
+pub struct VirtualClock {
+    time: Rc<Cell<i64>>,
+}
+
+impl VirtualClock {
+    pub fn advance(&self, millis: i64) {
+        self.time.set(self.time.get() + millis.max(0));
+    }
+}
+
+impl Clock for VirtualClock {
+    fn millis(&self) -> i64 {
+        self.time.get()
+    }
+
+    fn sleep(&self, millis: i64) {
+        self.advance(millis)
+    }
+}
@@ -2,6 +2,7 @@ use crate::runtime::action::flow::REASON;
 use crate::runtime::action::Tick;
 use crate::runtime::args::{RtArgs, RtValue};
 use crate::runtime::blackboard::BlackBoard;
+use crate::runtime::clock::Clock;
 use crate::runtime::rtree::rnode::RNodeId;
 use crate::runtime::{RtOk, RtResult, RuntimeError, TickResult};
 use crate::tracer::Event::NewState;
@@ -17,6 +18,9 @@ pub struct TreeContext<'a> {
 
     tracer: &'a mut Tracer,
 
+    /// The source of time for the time-related nodes
+    clock: &'a dyn Clock,
+
     /// The call stack
     stack: VecDeque<RNodeId>,
 
@@ -31,21 +35,38 @@ pub struct TreeContext<'a> {
 
     /// the max amount of ticks
     tick_limit: Timestamp,
+
+    /// the least time in milliseconds the waiting nodes (delay for example) need in the current tick
+    waiting: Option<i64>,
+
+    /// some action is ticked in the current tick
+    busy: bool,
 }
 
 impl<'a> TreeContext<'a> {
     pub fn bb(&mut self) -> &mut BlackBoard {
         self.bb
     }
-    pub fn new(bb: &'a mut BlackBoard, tracer: &'a mut Tracer, tick_limit: Timestamp) -> Self {
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock
+    }
+    pub fn new(
+        bb: &'a mut BlackBoard,
+        tracer: &'a mut Tracer,
+        clock: &'a dyn Clock,
+        tick_limit: Timestamp,
+    ) -> Self {
         Self {
             bb,
             tracer,
+            clock,
             stack: Default::default(),
             state: Default::default(),
             ts_map: Default::default(),
             curr_ts: 1,
             tick_limit,
+            waiting: None,
+            busy: false,
         }
     }
 }
@@ -54,7 +75,25 @@ impl<'a> TreeContext<'a> {
     pub fn trace(&mut self, ev: Event) {
         self.tracer.trace(self.curr_ts, ev)
     }
+    /// the node waits for the given time to pass before it proceeds
+    pub(crate) fn wait(&mut self, millis: i64) {
+        self.waiting = Some(self.waiting.map_or(millis, |w| w.min(millis)));
+    }
+
+    /// the action is ticked, thus the tick is not spent waiting
+    pub(crate) fn busy(&mut self) {
+        self.busy = true;
+    }
+
+    /// If nothing but the waiting nodes is left in the tick,
+    /// the clock sleeps until the earliest of them can proceed
+    /// instead of spinning the ticks (and exhausting the limit of ticks) meanwhile.
     pub(crate) fn next_tick(&mut self) -> RtOk {
+        if let (Some(millis), false) = (self.waiting.take(), self.busy) {
+            debug!(target:"root", "only the waiting nodes are left, sleep for {millis}ms");
+            self.clock.sleep(millis);
+        }
+        self.busy = false;
         self.curr_ts += 1;
         self.trace(Event::NextTick);
         debug!(target:"root", "tick up the flow to:{}",self.curr_ts);
@@ -112,6 +151,10 @@ impl<'a> TreeContext<'a> {
         self.trace(NewState(id, state.clone()));
         Ok(self.state.insert(id, state))
     }
+    /// the latest state of the node regardless of the tick it is set in
+    pub(crate) fn latest_state(&self, id: RNodeId) -> Option<&RNodeState> {
+        self.state.get(&id)
+    }
     pub(crate) fn state_in_ts(&self, id: RNodeId) -> RNodeState {
         let actual_state = self
             .state
@@ -1,10 +1,12 @@
 use crate::runtime::action::flow::{read_cursor, run_with, CURSOR, LEN, P_CURSOR};
 use crate::runtime::action::keeper::ActionKeeper;
-use crate::runtime::action::{decorator, flow, Tick};
+use crate::runtime::action::{decorator, flow, Action, Tick};
+use crate::runtime::args::eval::eval_args;
 use crate::runtime::args::{RtArgs, RtValue};
 use crate::runtime::blackboard::BlackBoard;
+use crate::runtime::clock::Clock;
 use crate::runtime::context::{RNodeState, TreeContext};
-use crate::runtime::rtree::rnode::{FlowType, Name, RNode};
+use crate::runtime::rtree::rnode::{FlowType, Name, RNode, RNodeId};
 use crate::runtime::rtree::RuntimeTree;
 use crate::runtime::{RtOk, RtResult, RuntimeError, TickResult};
 use crate::tracer::Tracer;
@@ -18,6 +20,7 @@ pub struct Forester {
     pub bb: BlackBoard,
     pub keeper: ActionKeeper,
     pub tracer: Tracer,
+    pub clock: Box<dyn Clock>,
 }
 
 impl Forester {
@@ -26,12 +29,14 @@ impl Forester {
         bb: BlackBoard,
         keeper: ActionKeeper,
         tracer: Tracer,
+        clock: Box<dyn Clock>,
     ) -> RtResult<Self> {
         Ok(Self {
             tree,
             bb,
             keeper,
             tracer,
+            clock,
         })
     }
 
@@ -42,8 +47,12 @@ impl Forester {
     pub fn run_until(&mut self, max_tick: Option<usize>) -> Tick {
         // The ctx has a call stack to manage the flow.
         // When the flow goes up it pops the current element and leaps to the parent.
-        let mut ctx =
-            TreeContext::new(&mut self.bb, &mut self.tracer, max_tick.unwrap_or_default());
+        let mut ctx = TreeContext::new(
+            &mut self.bb,
+            &mut self.tracer,
+            self.clock.as_ref(),
+            max_tick.unwrap_or_default(),
+        );
         ctx.push(self.tree.root)?;
         // starts from root and pops up the element when either it is finished
         // or the root needs to make a new tick
@@ -92,7 +101,8 @@ impl Forester {
                                     ctx.push(child)?;
                                 } else {
                                     let next_state =
-                                        flow::monitor(tpe, args.clone(), tick_args, &mut ctx)?;
+                                        flow::monitor(tpe, args.clone(), tick_args, &mut ctx)
+                                            .map_err(|e| self.tree.locate(&id, e))?;
                                     debug!(target:"flow[run]", "tick:{}, {tpe}. Go up with the new state: {:?}",ctx.curr_ts(),&next_state);
                                     ctx.new_state(id, next_state)?;
                                     ctx.pop()?;
@@ -108,7 +118,8 @@ impl Forester {
                                     tick_args.clone(),
                                     s.clone().try_into()?,
                                     &mut ctx,
-                                )?;
+                                )
+                                .map_err(|e| self.tree.locate(&id, e))?;
                                 debug!(target:"flow[run]", "tick:{}, {tpe}. The '{}' is finished as {:?}, the new state: {:?} ",ctx.curr_ts(),child,s, &new_state);
                                 ctx.new_state(id, new_state)?;
                             }
@@ -126,8 +137,18 @@ impl Forester {
                     // since it is ready we need to prepare decorator to start
                     // But then we do nothing but switch the state to running in the current tick.
                     RNodeState::Ready(tick_args) => {
-                        let new_state =
-                            decorator::prepare(tpe, init_args.clone(), tick_args, &mut ctx)?;
+                        let new_state = decorator::prepare(
+                            tpe,
+                            init_args.clone(),
+                            tick_args,
+                            &mut ctx,
+                            &self.keeper,
+                        )
+                        .map_err(|e| self.tree.locate(&id, e))?;
+                        // the decorator finished before its child stops the child that is still running
+                        if !new_state.is_running() {
+                            halt(&self.tree, &self.keeper, *child, &mut ctx)?;
+                        }
                         debug!(target:"decorator[ready]", "tick:{}, the new_state: {:?}",ctx.curr_ts(),&new_state);
                         ctx.new_state(id, new_state)?;
                     }
@@ -138,9 +159,20 @@ impl Forester {
                     RNodeState::Running(tick_args) => match ctx.state_in_ts(*child) {
                         // we are about to kick off the child.
                         // Just pass the control to the child
+                        // unless the decorator keeps it waiting (delay for example),
+                        // then the control goes back to the parent until the next tick.
                         RNodeState::Ready(..) => {
-                            debug!(target:"decorator[run]", "tick:{}, The '{}' is ready, push it on the stack",ctx.curr_ts(),&child);
-                            ctx.push(*child)?;
+                            if let Some(left) =
+                                decorator::is_waiting(tpe, init_args, &tick_args, &mut ctx)
+                                    .map_err(|e| self.tree.locate(&id, e))?
+                            {
+                                debug!(target:"decorator[run]", "tick:{}, The '{}' is waiting for {left}ms, go up",ctx.curr_ts(),&child);
+                                ctx.wait(left);
+                                ctx.pop()?;
+                            } else {
+                                debug!(target:"decorator[run]", "tick:{}, The '{}' is ready, push it on the stack",ctx.curr_ts(),&child);
+                                ctx.push(*child)?;
+                            }
                         }
                         // child is already running and since the flow is here in the parent,
                         // he decided that it is a final state for the tick,
@@ -148,8 +180,17 @@ impl Forester {
                         // we can use this to monitor the progress and make a decision
                         // (for Timeout for example)
                         RNodeState::Running { .. } => {
-                            let new_state =
-                                decorator::monitor(tpe, init_args.clone(), tick_args, &mut ctx)?;
+                            let new_state = decorator::monitor(
+                                tpe,
+                                init_args.clone(),
+                                tick_args,
+                                &mut ctx,
+                                &self.keeper,
+                            )
+                            .map_err(|e| self.tree.locate(&id, e))?;
+                            if !new_state.is_running() {
+                                halt(&self.tree, &self.keeper, *child, &mut ctx)?;
+                            }
                             debug!(target:"decorator[run]", "tick:{},The '{}' is running, the new state: {:?} ",ctx.curr_ts(),child, &new_state);
                             ctx.new_state(id, new_state)?;
                             ctx.pop()?;
@@ -164,7 +205,9 @@ impl Forester {
                                 init_args.clone(),
                                 s.to_tick_result()?,
                                 &mut ctx,
-                            )?;
+                                &self.keeper,
+                            )
+                            .map_err(|e| self.tree.locate(&id, e))?;
                             debug!(target:"decorator[run]", "tick:{},The '{}' is finished, the new state: {:?} ",ctx.curr_ts(),child, &new_state);
                             ctx.new_state(id, new_state)?;
                             ctx.pop()?;
@@ -181,8 +224,17 @@ impl Forester {
                 RNode::Leaf(f_name, args) => {
                     debug!(target:"leaf","args :{:?}",args);
                     if ctx.state_in_ts(id).is_ready() {
-                        let mut action = self.keeper.get(f_name.name()?)?;
-                        let res = action.tick(args.clone(), &mut ctx)?;
+                        let mut action = self
+                            .keeper
+                            .get(f_name.name()?)
+                            .map_err(|e| self.tree.locate(&id, e))?;
+                        // the expressions and references are evaluated against the current state of the blackboard
+                        let tick_args = eval_args(args.clone(), ctx.bb())
+                            .map_err(|e| self.tree.locate(&id, e))?;
+                        ctx.busy();
+                        let res = action
+                            .tick(tick_args, &mut ctx)
+                            .map_err(|e| self.tree.locate(&id, e))?;
                         let new_state = RNodeState::from(args.clone(), res);
                         debug!(target:"leaf", "tick:{}, the new state: {:?}",ctx.curr_ts(),&new_state);
                         ctx.new_state(id, new_state)?;
@@ -207,3 +259,26 @@ fn read_cursor_as_usize(args: RtArgs) -> RtResult<usize> {
     usize::try_from(read_cursor(args)?)
         .map_err(|e| RuntimeError::uex(format!("cursor is not usize")))
 }
+
+/// halts the actions under the node (the node included) that are left running.
+/// The async actions get [`halt`](crate::runtime::action::ImplAsync::halt) called,
+/// the actions get ready to start over.
+fn halt(tree: &RuntimeTree, keeper: &ActionKeeper, id: RNodeId, ctx: &mut TreeContext) -> RtOk {
+    let mut queue = vec![id];
+    while let Some(id) = queue.pop() {
+        let node = tree.node(&id)?;
+        queue.extend(node.children());
+        if let RNode::Leaf(name, _) = node {
+            if let Some(RNodeState::Running(args)) = ctx.latest_state(id).cloned() {
+                debug!(target:"halt", "tick:{}, the action {} gets halted",ctx.curr_ts(),id);
+                if let Action::Async(action) =
+                    keeper.get(name.name()?).map_err(|e| tree.locate(&id, e))?
+                {
+                    action.halt(ctx).map_err(|e| tree.locate(&id, e))?;
+                }
+                ctx.new_state(id, RNodeState::Ready(args))?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,6 @@
 mod builder;
+mod compiled;
+mod optimizer;
 pub mod rnode;
 
 use crate::runtime::action::keeper::ActionKeeper;
@@ -6,27 +8,67 @@ use crate::runtime::action::ActionName;
 use crate::runtime::args::transform::{to_dec_rt_args, to_rt_args};
 use crate::runtime::blackboard::BlackBoard;
 use crate::runtime::rtree::builder::{Builder, StackItem};
-use crate::runtime::rtree::rnode::{DecoratorType, RNode, RNodeId};
+use crate::runtime::rtree::rnode::{DecoratorType, Name, RNode, RNodeId, RNodeMeta};
 use crate::runtime::{RtResult, RuntimeError};
+use crate::tree::diagnostic::Location;
 use crate::tree::parser::ast::arg::{Argument, Arguments, Param, Params};
 use crate::tree::parser::ast::call::{Call, Calls};
 use crate::tree::parser::ast::Tree;
 use crate::tree::project::file::File;
 use crate::tree::project::imports::ImportMap;
-use crate::tree::project::{FileName, Project};
+use crate::tree::project::{FileName, Project, TreeName};
 use crate::tree::{cerr, TreeError};
+pub(crate) use compiled::{sorted_map, sorted_set};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 
+/// The definitions the runtime tree is built from.
+/// They are not kept in the tree since the tree can be loaded without the project.
 #[derive(Default, Debug, PartialEq)]
+pub struct Resolved {
+    /// the parameters of the `impl` definitions the leaves are resolved to,
+    /// see [`ActionKeeper::validate`]
+    pub action_params: HashMap<ActionName, Params>,
+    /// the files and the names of the definitions the nodes are built from
+    pub definitions: HashSet<(FileName, TreeName)>,
+}
+
+#[derive(Default, Debug, PartialEq, Deserialize, Serialize)]
 pub struct RuntimeTree {
     pub root: RNodeId,
+    #[serde(serialize_with = "sorted_map")]
     pub nodes: HashMap<RNodeId, RNode>,
+    #[serde(serialize_with = "sorted_set")]
     pub std_nodes: HashSet<ActionName>,
+    /// the nodes removed by the [optimization](RuntimeTree::optimize) and the nodes that take their places
+    #[serde(serialize_with = "sorted_map")]
+    pub folded: HashMap<RNodeId, RNodeId>,
+    /// the paths and the places in the source of the nodes, see [`RNodeMeta`]
+    #[serde(serialize_with = "sorted_map")]
+    pub meta: HashMap<RNodeId, RNodeMeta>,
+    /// the nodes by their paths, see [`RuntimeTree::find_by_path`].
+    /// It is not stored, since it is restored from the meta when the tree is loaded.
+    #[serde(skip)]
+    pub(crate) by_path: HashMap<String, RNodeId>,
 }
 
 impl RuntimeTree {
     pub fn build(project: Project) -> Result<RuntimeTree, TreeError> {
+        RuntimeTree::build_resolved(&project).map(|(tree, _)| tree)
+    }
+
+    /// builds the tree along with the definitions the nodes are resolved to, see [`Resolved`]
+    pub fn build_resolved(project: &Project) -> Result<(RuntimeTree, Resolved), TreeError> {
         let (file, name) = &project.main;
+        RuntimeTree::build_root(project, file, name)
+    }
+
+    /// builds the tree starting from the given root instead of the main one of the project
+    pub fn build_root(
+        project: &Project,
+        file: &FileName,
+        name: &TreeName,
+    ) -> Result<(RuntimeTree, Resolved), TreeError> {
         let root = project.find_root(name, file)?;
         let mut builder = Builder::default();
         let mut r_tree = RuntimeTree::default();
@@ -39,115 +81,240 @@ impl RuntimeTree {
         r_tree.root = root_id;
         r_tree.nodes.insert(root_id, root_node);
 
+        let mut locations = HashMap::from([(root_id, project.location(file, root.span))]);
         while let Some(item) = builder.pop() {
-            let StackItem {
-                id,
-                call,
-                parent_id,
-                file_name,
-            } = item;
-
-            let curr_file = &project.find_file(file_name.as_str())?;
-            let import_map = ImportMap::build(curr_file)?;
-
-            match call {
-                // for lambda there is not many actions since it does not have arguments so just grab a type and children
-                Call::Lambda(tpe, calls) => {
-                    let children = builder.push_vec(calls, id, file_name.clone());
-                    builder.add_chain_lambda(id, parent_id);
-                    r_tree
-                        .nodes
-                        .insert(id, RNode::lambda(tpe.try_into()?, children));
-                }
-                // for higher order invocation there are two possible cases:
-                // - the invocation is passed as an argument from the parent (this chain can be long up)
-                //   So we need to find the initially passed call.
-                // - since we found it we transform it into a simple invocation call and process it at the next step.
-                Call::HoInvocation(key) => {
-                    let call = builder.find_ho_call(&parent_id, &key)?;
-                    let k = call
-                        .key()
-                        .ok_or(cerr(format!("the call {:?} does not have a name", call)))?;
-
-                    builder.push_front(
-                        id,
-                        Call::invocation(&k, call.arguments()),
-                        id,
-                        file_name.clone(),
-                    );
+            let (file_name, span) = (item.file_name.clone(), item.call.span());
+            // the higher order invocation is replaced with the passed call further on, so is the location
+            locations.insert(item.id, project.location(&file_name, span));
+            r_tree
+                .add_node(item, &mut builder, project)
+                .map_err(|e| project.locate(&file_name, span, e))?;
+        }
+        r_tree.add_meta(locations);
+
+        Ok((r_tree, builder.resolved()))
+    }
+
+    /// sets the paths going down from the root along with the locations.
+    /// The segment of the child is its label, marked with the index among the siblings
+    /// if the child is a lambda or the siblings have the same label: `main/sequence[0]/move`
+    fn add_meta(&mut self, mut locations: HashMap<RNodeId, Location>) {
+        let mut queue = VecDeque::new();
+        if let Some(root) = self.nodes.get(&self.root) {
+            queue.push_back((self.root, root.label()));
+        }
+        while let Some((id, path)) = queue.pop_front() {
+            let children = self.nodes.get(&id).map(RNode::children).unwrap_or_default();
+            let labels: Vec<(RNodeId, String, bool)> = children
+                .iter()
+                .flat_map(|c| self.nodes.get(c).map(|n| (*c, n.label(), n.is_lambda())))
+                .collect();
+            for (idx, (child, label, lambda)) in labels.iter().enumerate() {
+                let same = labels.iter().filter(|(_, l, _)| l == label).count();
+                let segment = if *lambda || same > 1 {
+                    format!("{label}[{idx}]")
+                } else {
+                    label.clone()
+                };
+                queue.push_back((*child, format!("{path}/{segment}")));
+            }
+            let location = locations.remove(&id).unwrap_or_default();
+            self.by_path.insert(path.clone(), id);
+            self.meta.insert(id, RNodeMeta { path, location });
+        }
+    }
+
+    /// restores the index of the paths from the meta of the loaded tree
+    pub(crate) fn index_paths(&mut self) {
+        self.by_path = self
+            .meta
+            .iter()
+            .map(|(id, m)| (m.path.clone(), *id))
+            .collect();
+    }
+
+    /// the path of the node from the root, e.g. `main/patrol/sequence[2]/move_to`
+    pub fn path(&self, id: &RNodeId) -> Option<&str> {
+        self.meta.get(id).map(|m| m.path.as_str())
+    }
+
+    /// the place in the source the node is called at
+    pub fn location(&self, id: &RNodeId) -> Option<&Location> {
+        self.meta.get(id).map(|m| &m.location)
+    }
+
+    /// the node by its path, see [`RuntimeTree::path`]
+    pub fn find_by_path(&self, path: &str) -> Option<RNodeId> {
+        self.by_path
+            .get(path)
+            .filter(|id| self.nodes.contains_key(id))
+            .copied()
+    }
+
+    /// adds the path and the location of the node to the error occurred while ticking it
+    pub fn locate(&self, id: &RNodeId, error: RuntimeError) -> RuntimeError {
+        let (path, location) = match self.meta.get(id) {
+            Some(RNodeMeta { path, location }) => (path, location),
+            None => return error,
+        };
+        let at = |msg: String| format!("{msg} (the node {path} at {location})");
+        match error {
+            RuntimeError::UnImplementedAction(m) => RuntimeError::UnImplementedAction(at(m)),
+            RuntimeError::BlackBoardError(m) => RuntimeError::BlackBoardError(at(m)),
+            RuntimeError::IOError(m) => RuntimeError::IOError(at(m)),
+            RuntimeError::Unexpected(m) => RuntimeError::Unexpected(at(m)),
+            RuntimeError::WrongArgument(m) => RuntimeError::WrongArgument(at(m)),
+            // the stop is not a failure of the node and the compile errors point to the source already
+            e @ (RuntimeError::Stopped(_) | RuntimeError::CompileError(_)) => e,
+        }
+    }
+
+    /// transforms the call from the stack into the runtime node pushing the children further
+    fn add_node(
+        &mut self,
+        item: StackItem,
+        builder: &mut Builder,
+        project: &Project,
+    ) -> Result<(), TreeError> {
+        let StackItem {
+            id,
+            call,
+            parent_id,
+            file_name,
+        } = item;
+
+        let curr_file = project.find_file(file_name.as_str())?;
+        let import_map = ImportMap::build(curr_file)?;
+
+        match call {
+            // for lambda there is not many actions since it does not have arguments so just grab a type and children
+            Call::Lambda(tpe, calls, _) => {
+                let children = builder.push_vec(calls, id, file_name.clone());
+                builder.add_chain_lambda(id, parent_id);
+                self.nodes
+                    .insert(id, RNode::lambda(tpe.try_into()?, children));
+            }
+            // for higher order invocation there are two possible cases:
+            // - the invocation is passed as an argument from the parent (this chain can be long up)
+            //   So we need to find the initially passed call.
+            // - since we found it we process it at the next step in the scope and the file it was written in,
+            //   so the lambdas and the arguments of the call see the parameters of the enclosing tree.
+            Call::HoInvocation(key, _) => {
+                let (call, scope) = builder.find_ho_call(&parent_id, &key)?;
+                let file = builder.body_file(&scope).cloned().unwrap_or(file_name);
+                builder.push_front(id, call, scope, file);
+            }
+            // just take the arguments and transform them into runtime args and push further
+            // the decorator does not have parameters, so the children see the parameters of the enclosing tree
+            Call::Decorator(tpe, decor_args, call, _) => {
+                builder.add_chain_lambda(id, parent_id);
+                let child = builder.push(*call, id, file_name.clone());
+                let d_tpe: DecoratorType = tpe.try_into()?;
+                let rt_args = to_dec_rt_args(&d_tpe, decor_args)?;
+                self.nodes
+                    .insert(id, RNode::decorator(d_tpe, rt_args, child));
+            }
+            // the same as the decorator above but the arguments are defined by the declaration,
+            // that can be found either in the file or in the imports
+            Call::CustomDecorator(name, decor_args, call, _) => {
+                let (decl, decl_file) = match curr_file.definitions.get(&name) {
+                    Some(tree) => (tree, &file_name),
+                    None => import_map.find(&name, project)?,
+                };
+                builder.add_definition(decl_file, &decl.name);
+                if !decl.tpe.is_custom_decorator() {
+                    return Err(cerr(format!("the {name} is not declared as a decorator")));
                 }
-                // just take the arguments and transform them into runtime args and push further
-                Call::Decorator(tpe, decor_args, call) => {
-                    let (_, parent_args, parent_params) =
-                        builder.get_chain_skip_lambda(&parent_id)?.get_tree();
-                    builder.add_chain(id, parent_id, parent_args.clone(), parent_params.clone());
-                    let child = builder.push(*call, id, file.clone());
-                    let d_tpe: DecoratorType = tpe.try_into()?;
-                    let rt_args = to_dec_rt_args(&d_tpe, decor_args)?;
-                    r_tree
-                        .nodes
-                        .insert(id, RNode::decorator(d_tpe, rt_args, child));
+                let rt_args = to_rt_args(name.as_str(), decor_args, decl.params.clone())?;
+                builder.add_chain_lambda(id, parent_id);
+                let child = builder.push(*call, id, file_name.clone());
+                self.nodes.insert(
+                    id,
+                    RNode::decorator(DecoratorType::Custom(decl.name.clone()), rt_args, child),
+                );
+            }
+            // firstly we need to find the definition either in the file or in the imports
+            // with a consideration of a possible alias and transform the args
+            Call::Invocation(name, args, _) => match curr_file.definitions.get(&name) {
+                Some(tree) => {
+                    builder.add_definition(&file_name, &tree.name);
+                    let rt_args = to_rt_args(name.as_str(), args.clone(), tree.params.clone())?;
+                    builder.add_chain(id, parent_id, args.clone(), tree.params.clone());
+                    if tree.tpe.is_action() {
+                        builder.add_action(&name, &file_name, &tree.params)?;
+                        self.nodes.insert(id, RNode::action(name, rt_args));
+                    } else {
+                        let children = builder.push_vec(tree.calls.clone(), id, file_name.clone());
+                        self.nodes.insert(
+                            id,
+                            RNode::flow(tree.tpe.try_into()?, name, rt_args, children),
+                        );
+                    }
                 }
-                // firstly we need to find the definition either in the file or in the imports
-                // with a consideration of a possible alias and transform the args
-                Call::Invocation(name, args) => match curr_file.definitions.get(&name) {
-                    Some(tree) => {
-                        let rt_args = to_rt_args(name.as_str(), args.clone(), tree.params.clone())?;
-                        builder.add_chain(id, parent_id, args.clone(), tree.params.clone());
+                None => {
+                    let (tree, file) = import_map.find(&name, project)?;
+                    builder.add_definition(file, &tree.name);
+                    if file == "std::actions" {
+                        self.std_nodes.insert(tree.name.clone());
+                    }
+                    let rt_args = to_rt_args(name.as_str(), args.clone(), tree.params.clone())?;
+                    builder.add_chain(id, parent_id, args.clone(), tree.params.clone());
+                    // the body of the imported tree refers to the definitions of its own file
+                    let children = builder.push_vec(tree.calls.clone(), id, file.clone());
+                    if tree.tpe.is_action() {
+                        builder.add_action(&tree.name, &file, &tree.params)?;
+                    }
+
+                    if &tree.name != &name {
                         if tree.tpe.is_action() {
-                            r_tree.nodes.insert(id, RNode::action(name, rt_args));
+                            self.nodes
+                                .insert(id, RNode::action_alias(tree.name.clone(), name, rt_args));
                         } else {
-                            let children =
-                                builder.push_vec(tree.calls.clone(), id, file_name.clone());
-                            r_tree.nodes.insert(
+                            self.nodes.insert(
                                 id,
-                                RNode::flow(tree.tpe.try_into()?, name, rt_args, children),
+                                RNode::flow_alias(
+                                    tree.tpe.try_into()?,
+                                    tree.name.clone(),
+                                    name,
+                                    rt_args,
+                                    children,
+                                ),
                             );
                         }
-                    }
-                    None => {
-                        let (tree, file) = import_map.find(&name, &project)?;
-                        if file == "std::actions" {
-                            r_tree.std_nodes.insert(tree.name.clone());
-                        }
-                        let rt_args = to_rt_args(name.as_str(), args.clone(), tree.params.clone())?;
-                        builder.add_chain(id, parent_id, args.clone(), tree.params.clone());
-                        let children = builder.push_vec(tree.calls.clone(), id, file_name.clone());
-
-                        if &tree.name != &name {
-                            if tree.tpe.is_action() {
-                                r_tree.nodes.insert(
-                                    id,
-                                    RNode::action_alias(tree.name.clone(), name, rt_args),
-                                );
-                            } else {
-                                r_tree.nodes.insert(
-                                    id,
-                                    RNode::flow_alias(
-                                        tree.tpe.try_into()?,
-                                        tree.name.clone(),
-                                        name,
-                                        rt_args,
-                                        children,
-                                    ),
-                                );
-                            }
+                    } else {
+                        if tree.tpe.is_action() {
+                            self.nodes.insert(id, RNode::action(name, rt_args));
                         } else {
-                            if tree.tpe.is_action() {
-                                r_tree.nodes.insert(id, RNode::action(name, rt_args));
-                            } else {
-                                r_tree.nodes.insert(
-                                    id,
-                                    RNode::flow(tree.tpe.try_into()?, name, rt_args, children),
-                                );
-                            }
-                        };
-                    }
-                },
-            }
+                            self.nodes.insert(
+                                id,
+                                RNode::flow(tree.tpe.try_into()?, name, rt_args, children),
+                            );
+                        }
+                    };
+                }
+            },
         }
-
-        Ok(r_tree)
+        Ok(())
+    }
+    /// the names of the user-defined decorators that are used in the tree.
+    pub fn custom_decorators(&self) -> HashSet<&Name> {
+        self.nodes
+            .values()
+            .filter_map(|n| match n {
+                RNode::Decorator(DecoratorType::Custom(name), _, _) => Some(name),
+                _ => None,
+            })
+            .collect()
+    }
+    /// the names of the actions that the leaves of the tree invoke.
+    pub fn leaves(&self) -> HashSet<&ActionName> {
+        self.nodes
+            .values()
+            .filter_map(|n| match n {
+                RNode::Leaf(name, _) => name.name().ok(),
+                _ => None,
+            })
+            .collect()
     }
     pub fn node(&self, id: &RNodeId) -> RtResult<&RNode> {
         self.nodes.get(id).ok_or(RuntimeError::uex(format!(
@@ -1,20 +1,28 @@
 use crate::runtime::args::transform::find_arg_value;
+use crate::runtime::rtree::Resolved;
 use crate::runtime::RuntimeError;
 use crate::tree::parser::ast::arg::{Arguments, Params};
 use crate::tree::parser::ast::call::{Call, Calls};
 use crate::tree::parser::ast::Key;
+use crate::tree::project::FileName;
 use crate::tree::{cerr, TreeError};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// The temp struct helps to transform ast to runtime tree
 /// Basically, it needs to keep 2 things
 ///  - the bfs iter order stack  
 ///  - the stack trace of the calls
+///  - the files the children of the nodes are written in
 #[derive(Default)]
 pub struct Builder {
     gen: usize,
     stack: VecDeque<StackItem>,
     chain_map: HashMap<usize, ChainItem>,
+    body_files: HashMap<usize, FileName>,
+    /// the definitions the action leaves are resolved to
+    actions: HashMap<Key, (FileName, Params)>,
+    /// the files and the names of the definitions the nodes are built from
+    definitions: HashSet<(FileName, Key)>,
 }
 // to help in traversing the call stack
 pub struct StackItem {
@@ -60,6 +68,44 @@ impl Builder {
         self.gen
     }
 
+    /// remembers the definition the action leaf is resolved to.
+    /// The actions are registered by name,
+    /// thus the definitions of the same action in different files should have the same parameters.
+    pub fn add_action(
+        &mut self,
+        name: &Key,
+        file: &FileName,
+        params: &Params,
+    ) -> Result<(), TreeError> {
+        match self.actions.get(name) {
+            Some((other, defined)) if !defined.same_signature(params) => Err(cerr(format!(
+                "the action {name} is defined with other parameters in the files {other} and {file}"
+            ))),
+            Some(_) => Ok(()),
+            None => {
+                self.actions
+                    .insert(name.clone(), (file.clone(), params.clone()));
+                Ok(())
+            }
+        }
+    }
+
+    /// remembers the definition the node is built from
+    pub fn add_definition(&mut self, file: &FileName, name: &Key) {
+        self.definitions.insert((file.clone(), name.clone()));
+    }
+
+    pub fn resolved(self) -> Resolved {
+        Resolved {
+            action_params: self
+                .actions
+                .into_iter()
+                .map(|(name, (_, params))| (name, params))
+                .collect(),
+            definitions: self.definitions,
+        }
+    }
+
     pub fn get_chain(&self, id: &usize) -> Result<&ChainItem, TreeError> {
         if *id == 0 {
             Ok(&ChainItem::Root)
@@ -76,7 +122,15 @@ impl Builder {
     ///  sequence another(n:tree) n(..)  
     /// ```
     /// To process `n(..)` in `another` we have to climb up after  `n=op(..)` until the last ho call.
-    pub fn find_ho_call(&self, parent_id: &usize, key: &Key) -> Result<Call, TreeError> {
+    ///
+    /// Along with the call, it returns the id of the node the call was written in (the scope).
+    /// The call is processed in this scope, thus a lambda passed as an argument
+    /// resolves the parameters it captures against the enclosing tree:
+    /// ```norun
+    ///  sequence wrapper(item:tree) item(..)
+    ///  sequence logged(task:tree) wrapper(sequence { log() task(..) })
+    /// ```
+    pub fn find_ho_call(&self, parent_id: &usize, key: &Key) -> Result<(Call, usize), TreeError> {
         let (mut grand_parent, mut parent_args, mut parent_params) =
             self.get_chain_skip_lambda(&parent_id)?.get_tree();
 
@@ -87,7 +141,8 @@ impl Builder {
                 self.get_chain_skip_lambda(&grand_parent)?.get_tree();
             call = find_arg_value(&key, &parent_params, &parent_args)?.get_call();
         }
-        call.ok_or(cerr(format!("the argument {key} should be a tree")))
+        call.map(|c| (c, grand_parent))
+            .ok_or(cerr(format!("the argument {key} should be a tree")))
     }
 
     ///goes up on the stacktrace skipping lambda
@@ -112,7 +167,13 @@ impl Builder {
         self.chain_map.insert(id, ChainItem::Lambda(parent));
     }
 
+    /// the file the children of the node are written in
+    pub fn body_file(&self, id: &usize) -> Option<&FileName> {
+        self.body_files.get(id)
+    }
+
     pub fn push(&mut self, call: Call, parent_id: usize, file_name: String) -> usize {
+        self.body_files.insert(parent_id, file_name.clone());
         let id = self.next();
         self.stack.push_back(StackItem {
             id,
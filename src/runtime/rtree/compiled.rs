@@ -0,0 +1,114 @@
+use crate::runtime::rtree::rnode::{RNode, RNodeId};
+use crate::runtime::rtree::RuntimeTree;
+use crate::runtime::{RtOk, RtResult, RuntimeError};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// The compiled tree can be stored and loaded in another process,
+/// that runs it without the sources of the project, see [`ForesterBuilder::runtime_tree`].
+///
+/// There are two formats:
+///  - json, that keeps the nodes with the types, the names and the arguments in the readable form
+///  - the compact binary form for caching the compilation
+///
+/// The file with the extension `json` is stored in json, any other file is stored in the binary form.
+/// The maps and the sets are stored with the sorted keys,
+/// so the same tree is always stored into the same bytes.
+///
+/// [`ForesterBuilder::runtime_tree`]: crate::runtime::builder::ForesterBuilder::runtime_tree
+impl RuntimeTree {
+    pub fn to_json(&self) -> RtResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(src: &str) -> RtResult<RuntimeTree> {
+        let mut tree: RuntimeTree = serde_json::from_str(src)?;
+        tree.check()?;
+        tree.index_paths();
+        Ok(tree)
+    }
+
+    pub fn to_bytes(&self) -> RtResult<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> RtResult<RuntimeTree> {
+        let mut tree: RuntimeTree = bincode::deserialize(bytes)?;
+        tree.check()?;
+        tree.index_paths();
+        Ok(tree)
+    }
+
+    /// stores the tree in the file, the format is chosen by the extension
+    pub fn save(&self, file: &PathBuf) -> RtOk {
+        if is_json(file) {
+            fs::write(file, self.to_json()?)?;
+        } else {
+            fs::write(file, self.to_bytes()?)?;
+        }
+        Ok(())
+    }
+
+    /// loads the tree stored with [`RuntimeTree::save`]
+    pub fn load(file: &PathBuf) -> RtResult<RuntimeTree> {
+        let bytes = fs::read(file)
+            .map_err(|e| RuntimeError::IOError(format!("error:{}, file:{:?}", e, file)))?;
+        if is_json(file) {
+            let src = String::from_utf8(bytes).map_err(|e| {
+                RuntimeError::IOError(format!("the file {file:?} is not a valid json: {e}"))
+            })?;
+            RuntimeTree::from_json(src.as_str())
+        } else {
+            RuntimeTree::from_bytes(bytes.as_slice())
+        }
+    }
+
+    /// the loaded tree has to refer only to the nodes it has
+    fn check(&self) -> RtOk {
+        let absent = |id: &RNodeId| {
+            RuntimeError::IOError(format!(
+                "the compiled tree is broken: the node {id} is not found"
+            ))
+        };
+        if !self.nodes.contains_key(&self.root) {
+            return Err(absent(&self.root));
+        }
+        for node in self.nodes.values() {
+            if let Some(child) = node.children().iter().find(|c| !self.nodes.contains_key(c)) {
+                return Err(absent(child));
+            }
+        }
+        match self.nodes.get(&self.root) {
+            Some(RNode::Flow(tpe, ..)) if tpe.is_root() => Ok(()),
+            _ => Err(RuntimeError::IOError(format!(
+                "the compiled tree is broken: the node {} is not a root",
+                self.root
+            ))),
+        }
+    }
+}
+
+fn is_json(file: &PathBuf) -> bool {
+    file.extension().map_or(false, |ext| ext == "json")
+}
+
+/// serializes the map with the keys in order, see [`RuntimeTree::to_json`]
+pub(crate) fn sorted_map<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// serializes the set with the elements in order, see [`RuntimeTree::to_json`]
+pub(crate) fn sorted_set<K, S>(set: &HashSet<K>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    S: Serializer,
+{
+    set.iter().collect::<BTreeSet<_>>().serialize(serializer)
+}
@@ -0,0 +1,126 @@
+use crate::runtime::rtree::rnode::{DecoratorType, FlowType, RNode, RNodeId, RNodeName};
+use crate::runtime::rtree::RuntimeTree;
+use crate::runtime::{RtResult, RuntimeError};
+use std::mem;
+
+/// The pass removes the redundant layers of the runtime tree, that cost ticks and clutter the traces:
+///  - the sequences and the fallbacks nested into the flows of the same kind get flattened into the parents:
+///    `sequence { a() sequence { b() c() } }` turns into `sequence { a() b() c() }`
+///  - the lambdas with the only child are replaced with the child (except `parallel`)
+///  - the nested inverters are folded: `inverter inverter a()` turns into `a()`
+///  - the aliases of the imported definitions are inlined, so the nodes refer to the definitions by the names
+///
+/// The remaining nodes keep their ids, and the removed nodes are recorded in [`RuntimeTree::folded`]
+/// along with the nodes taking their places, see [`RuntimeTree::origins`].
+impl RuntimeTree {
+    pub fn optimize(&mut self) -> RtResult<()> {
+        let root = self.root;
+        self.optimize_node(root).map(|_| ())
+    }
+
+    /// optimizes the subtree and returns the id of the node that takes the place of the given one.
+    fn optimize_node(&mut self, id: RNodeId) -> RtResult<RNodeId> {
+        let node = self.nodes.remove(&id).ok_or(RuntimeError::uex(format!(
+            "the node {id} is not found in the rt tree"
+        )))?;
+        match node {
+            RNode::Leaf(name, args) => {
+                self.nodes.insert(id, RNode::Leaf(inline(name), args));
+                Ok(id)
+            }
+            RNode::Flow(tpe, name, args, children) => {
+                let mut new_children = vec![];
+                for child in children {
+                    let child = self.optimize_node(child)?;
+                    match self.nodes.get_mut(&child) {
+                        Some(RNode::Flow(child_tpe, _, _, grand_children))
+                            if flattens(&tpe)
+                                && *child_tpe == tpe
+                                && !grand_children.is_empty() =>
+                        {
+                            new_children.extend(mem::take(grand_children));
+                            self.fold(child, id);
+                        }
+                        _ => new_children.push(child),
+                    }
+                }
+
+                if name == RNodeName::Lambda && new_children.len() == 1 && removable(&tpe) {
+                    let child = new_children[0];
+                    self.folded.insert(id, child);
+                    Ok(child)
+                } else {
+                    self.nodes
+                        .insert(id, RNode::Flow(tpe, inline(name), args, new_children));
+                    Ok(id)
+                }
+            }
+            RNode::Decorator(tpe, args, child) => {
+                let child = self.optimize_node(child)?;
+                match (&tpe, self.nodes.get(&child)) {
+                    (
+                        DecoratorType::Inverter,
+                        Some(RNode::Decorator(DecoratorType::Inverter, _, grand_child)),
+                    ) => {
+                        let grand_child = *grand_child;
+                        self.fold(child, grand_child);
+                        self.folded.insert(id, grand_child);
+                        Ok(grand_child)
+                    }
+                    _ => {
+                        self.nodes.insert(id, RNode::Decorator(tpe, args, child));
+                        Ok(id)
+                    }
+                }
+            }
+        }
+    }
+
+    /// removes the node recording the one that takes its place
+    fn fold(&mut self, id: RNodeId, into: RNodeId) {
+        self.nodes.remove(&id);
+        self.folded.insert(id, into);
+    }
+
+    /// the node that takes the place of the given one after the optimization.
+    /// The node that is not removed stands for itself.
+    pub fn replacement(&self, id: &RNodeId) -> RNodeId {
+        let mut id = *id;
+        while let Some(next) = self.folded.get(&id) {
+            id = *next;
+        }
+        id
+    }
+
+    /// the ids of the original nodes the node stands for after the optimization:
+    /// the node itself and the removed nodes that are folded into it, in ascending order.
+    pub fn origins(&self, id: &RNodeId) -> Vec<RNodeId> {
+        let mut origins: Vec<RNodeId> = self
+            .folded
+            .keys()
+            .filter(|removed| self.replacement(removed) == *id)
+            .copied()
+            .chain([*id])
+            .collect();
+        origins.sort();
+        origins
+    }
+}
+
+/// the flows that give the same result when the children of the nested flow of the same kind are placed instead of it.
+/// The empty flow is not flattened since it finishes at once with success.
+fn flattens(tpe: &FlowType) -> bool {
+    matches!(tpe, FlowType::Sequence | FlowType::Fallback)
+}
+
+/// the flows that give the same result as their only child
+fn removable(tpe: &FlowType) -> bool {
+    !matches!(tpe, FlowType::Root | FlowType::Parallel)
+}
+
+fn inline(name: RNodeName) -> RNodeName {
+    match name {
+        RNodeName::Alias(name, _) => RNodeName::Name(name),
+        name => name,
+    }
+}
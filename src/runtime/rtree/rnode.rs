@@ -1,17 +1,21 @@
 use crate::runtime::action::ActionName;
 use crate::runtime::args::RtArgs;
 use crate::runtime::{RtResult, RuntimeError, TickResult};
+use crate::tree::diagnostic::Location;
 use crate::tree::parser::ast::{Tree, TreeType};
 
 use crate::tree::{cerr, TreeError};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display as FmtDisplay, Formatter};
 use strum_macros::Display;
 use strum_macros::EnumString;
+use strum_macros::IntoStaticStr;
 
 pub type RNodeId = usize;
 pub type Name = String;
 pub type Alias = String;
 
-#[derive(Display, Debug, Clone, Copy, Eq, PartialEq, EnumString)]
+#[derive(Debug, Clone, Eq, PartialEq, EnumString, IntoStaticStr, Deserialize, Serialize)]
 #[strum(serialize_all = "snake_case")]
 pub enum DecoratorType {
     Inverter,
@@ -21,8 +25,29 @@ pub enum DecoratorType {
     Retry,
     Timeout,
     Delay,
+    RetryBackoff,
+    Cooldown,
+    RateLimit,
+    Guard,
+    RunOnce,
+    KeepRunningUntilFailure,
+    KeepRunningUntilSuccess,
+    /// The user-defined decorator, registered by the name in the `ForesterBuilder`
+    Custom(Name),
 }
-#[derive(Display, Debug, Clone, Copy, Eq, PartialEq, EnumString)]
+
+impl FmtDisplay for DecoratorType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecoratorType::Custom(name) => f.write_str(name),
+            tpe => {
+                let name: &'static str = tpe.into();
+                f.write_str(name)
+            }
+        }
+    }
+}
+#[derive(Display, Debug, Clone, Copy, Eq, PartialEq, EnumString, Deserialize, Serialize)]
 #[strum(serialize_all = "snake_case")]
 pub enum FlowType {
     Root,
@@ -55,6 +80,13 @@ impl TryFrom<TreeType> for DecoratorType {
             TreeType::Retry => Ok(DecoratorType::Retry),
             TreeType::Timeout => Ok(DecoratorType::Timeout),
             TreeType::Delay => Ok(DecoratorType::Delay),
+            TreeType::RetryBackoff => Ok(DecoratorType::RetryBackoff),
+            TreeType::Cooldown => Ok(DecoratorType::Cooldown),
+            TreeType::RateLimit => Ok(DecoratorType::RateLimit),
+            TreeType::Guard => Ok(DecoratorType::Guard),
+            TreeType::RunOnce => Ok(DecoratorType::RunOnce),
+            TreeType::KeepRunningUntilFailure => Ok(DecoratorType::KeepRunningUntilFailure),
+            TreeType::KeepRunningUntilSuccess => Ok(DecoratorType::KeepRunningUntilSuccess),
             e => Err(cerr(format!("unexpected type {e} for decorator"))),
         }
     }
@@ -76,7 +108,7 @@ impl TryFrom<TreeType> for FlowType {
         }
     }
 }
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub enum RNodeName {
     Lambda,
     Name(Name),
@@ -95,7 +127,18 @@ impl RNodeName {
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// The stable identity of the node.
+/// Unlike the id, that depends on the order the tree is built in,
+/// the path is made of the names of the nodes from the root: `main/patrol/sequence[2]/move_to`.
+/// The lambdas and the nodes named the same as their siblings get the index among the siblings.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RNodeMeta {
+    pub path: String,
+    /// the place of the call in the source
+    pub location: Location,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub enum RNode {
     Leaf(RNodeName, RtArgs),
     Flow(FlowType, RNodeName, RtArgs, Vec<RNodeId>),
@@ -103,6 +146,34 @@ pub enum RNode {
 }
 
 impl RNode {
+    /// the name of the node in the path, the aliases are taken as they are written in the call
+    pub fn label(&self) -> String {
+        match self {
+            RNode::Leaf(RNodeName::Name(name) | RNodeName::Alias(_, name), _)
+            | RNode::Flow(_, RNodeName::Name(name) | RNodeName::Alias(_, name), _, _) => {
+                name.clone()
+            }
+            RNode::Flow(tpe, RNodeName::Lambda, _, _) => tpe.to_string(),
+            RNode::Leaf(RNodeName::Lambda, _) => "lambda".to_string(),
+            RNode::Decorator(tpe, _, _) => tpe.to_string(),
+        }
+    }
+
+    pub fn children(&self) -> Vec<RNodeId> {
+        match self {
+            RNode::Leaf(_, _) => vec![],
+            RNode::Flow(_, _, _, children) => children.clone(),
+            RNode::Decorator(_, _, child) => vec![*child],
+        }
+    }
+
+    pub fn is_lambda(&self) -> bool {
+        matches!(
+            self,
+            RNode::Flow(_, RNodeName::Lambda, _, _) | RNode::Leaf(RNodeName::Lambda, _)
+        )
+    }
+
     pub fn decorator(t: DecoratorType, args: RtArgs, child: RNodeId) -> Self {
         RNode::Decorator(t, args, child)
     }
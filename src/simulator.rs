@@ -1,6 +1,7 @@
 use crate::runtime::action::Action as RtAction;
 use crate::runtime::action::Tick;
 use crate::runtime::builder::ForesterBuilder;
+use crate::runtime::clock::VirtualClock;
 use crate::runtime::forester::Forester;
 use crate::runtime::{RtOk, RtResult};
 use crate::simulator::actions::SimAction;
@@ -15,6 +16,8 @@ use crate::get_pb;
 
 pub struct Simulator {
     pub root: PathBuf,
+    /// the folder the relative outputs of the profile relate to
+    pub output: PathBuf,
     pub profile: SimProfile,
     pub forester: Forester,
 }
@@ -27,21 +30,37 @@ impl Simulator {
         main_tree: Option<String>,
     ) -> RtResult<Self> {
         let mut fb = ForesterBuilder::new();
-        let pr = profile.clone();
         fb.root(root.clone());
         fb.main_file(main_file);
 
         if let Some(m_tree) = main_tree {
             fb.main_tree(m_tree);
         }
+        Simulator::build_with(profile, root.clone(), root, fb)
+    }
+
+    /// builds the simulator on top of the prepared builder of the engine,
+    /// registering the stubs of the actions from the profile.
+    /// The relative outputs of the profile (trace, graph, blackboard dump) relate to the `output` folder.
+    pub fn build_with(
+        profile: SimProfile,
+        root: PathBuf,
+        output: PathBuf,
+        mut fb: ForesterBuilder,
+    ) -> RtResult<Self> {
+        let pr = profile.clone();
 
         if let Some(trace_dump_file) = profile.config.trace {
             fb.tracer(Tracer::create(TracerConfiguration::in_file(get_pb(
                 &trace_dump_file,
-                root.clone(),
+                output.clone(),
             )))?)
         }
 
+        if profile.config.virtual_time {
+            fb.clock(VirtualClock::default());
+        }
+
         if let Some(bb_load_path) = profile.config.bb.load {
             fb.bb_load(bb_load_path);
         }
@@ -64,6 +83,7 @@ impl Simulator {
 
         Ok(Simulator {
             root,
+            output,
             profile: pr,
             forester,
         })
@@ -75,13 +95,15 @@ impl Simulator {
 
         if let Some(viz_file) = &cfg.graph {
             let tree = &self.forester.tree;
-            Visualizer::svg_file(tree, get_pb(viz_file, self.root.clone()))?;
+            Visualizer::svg_file(tree, get_pb(viz_file, self.output.clone()))?;
         }
 
         let result = self.forester.run_until(max);
 
         if let Some(bb_dump) = &cfg.bb.dump {
-            self.forester.bb.dump(get_pb(bb_dump, self.root.clone()))?;
+            self.forester
+                .bb
+                .dump(get_pb(bb_dump, self.output.clone()))?;
         }
 
         result
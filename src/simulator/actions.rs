@@ -27,15 +27,15 @@ impl Impl for SimAction {
     fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
         match self {
             SimAction::Success(d) => {
-                std::thread::sleep(Duration::from_millis(*d as u64));
+                ctx.clock().sleep(*d as i64);
                 Ok(TickResult::success())
             }
             SimAction::Failure(d) => {
-                std::thread::sleep(Duration::from_millis(*d as u64));
+                ctx.clock().sleep(*d as i64);
                 Ok(TickResult::failure_empty())
             }
             SimAction::Random(d) => {
-                std::thread::sleep(Duration::from_millis(*d as u64));
+                ctx.clock().sleep(*d as i64);
                 let num = SystemTime::now()
                     .duration_since(SystemTime::UNIX_EPOCH)
                     .unwrap()
@@ -29,6 +29,8 @@ pub struct SimProfileConfig {
     pub bb: BbConfig,
     pub graph: Option<String>,
     pub max_ticks: Option<usize>,
+    #[serde(default)]
+    pub virtual_time: bool,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -44,6 +46,7 @@ impl Default for SimProfileConfig {
             bb: BbConfig::default(),
             graph: None,
             max_ticks: None,
+            virtual_time: false,
         }
     }
 }
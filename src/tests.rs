@@ -1,10 +1,15 @@
 mod actions;
+mod analysis;
+mod btcpp;
 mod decorator;
+mod diagnostics;
 mod flow;
+mod manifest;
 mod project;
 mod rtree;
 mod simulator;
 mod tracer;
+mod typecheck;
 mod vizualizer;
 
 use crate::runtime::builder::ForesterBuilder;
@@ -1,5 +1,12 @@
-use crate::runtime::TickResult;
+use crate::runtime::action::builtin::data::StoreData;
+use crate::runtime::action::builtin::ReturnResult;
+use crate::runtime::action::{Action, Impl, Tick};
+use crate::runtime::args::{RtArgs, RtValue};
+use crate::runtime::context::TreeContext;
+use crate::runtime::forester::Forester;
+use crate::runtime::{RuntimeError, TickResult};
 use crate::tests::{fb, turn_on_logs};
+use crate::tree::parser::ast::arg::{MesType, Param, Params};
 
 #[test]
 fn builtin_actions() {
@@ -10,3 +17,233 @@ fn builtin_actions() {
     let result = f.run();
     assert_eq!(result, Ok(TickResult::failure("test".to_string())));
 }
+
+#[test]
+fn missing_actions() {
+    let mut fb = fb("actions/missing");
+    fb.register_action("approach", Action::sync(ReturnResult::success()));
+
+    let result = fb.build().map(|_| ());
+    assert_eq!(
+        result,
+        Err(RuntimeError::UnImplementedAction(
+            "the actions grasp, ready are not registered".to_string()
+        ))
+    );
+}
+
+struct StoreStr;
+
+impl Impl for StoreStr {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        StoreData.tick(args, ctx)
+    }
+
+    fn params(&self) -> Option<Params> {
+        Some(Params::new(vec![
+            Param::new("key", MesType::String),
+            Param::new("value", MesType::String),
+        ]))
+    }
+}
+
+#[test]
+fn action_params() {
+    let mut fb = fb("actions/params");
+    fb.register_action("store", Action::sync(StoreStr));
+
+    let result = fb.build().map(|_| ());
+    assert_eq!(
+        result,
+        Err(RuntimeError::WrongArgument(
+            "the action store expects (key:String,value:String) but the definition has (key:String,value:Num)"
+                .to_string()
+        ))
+    );
+
+    let mut fb = crate::tests::fb("actions/params");
+    fb.register_action("store", Action::sync(StoreData));
+    let mut f = fb.build().unwrap();
+    assert_eq!(f.run(), Ok(TickResult::success()));
+
+    let mut fb = crate::tests::fb("actions/params");
+    fb.register_action("store", Action::sync(StoreNamed));
+    let result = fb.build().map(|_| ());
+    assert_eq!(
+        result,
+        Err(RuntimeError::WrongArgument(
+            "the action store expects (name:String,value:Num) but the definition has (key:String,value:Num)"
+                .to_string()
+        ))
+    );
+}
+
+#[test]
+fn duplicate_action() {
+    let mut fb = fb("actions/duplicate");
+    fb.register_action("store", Action::sync(StoreData));
+
+    let error = format!("{:?}", fb.build().map(|_| ()).unwrap_err());
+    assert!(
+        error.contains(
+            "the action store is defined with other parameters in the files main.tree and store.tree"
+        ),
+        "{error}"
+    );
+}
+
+struct StoreNamed;
+
+impl Impl for StoreNamed {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        StoreData.tick(args, ctx)
+    }
+
+    fn params(&self) -> Option<Params> {
+        Some(Params::new(vec![
+            Param::new("name", MesType::String),
+            Param::new("value", MesType::Num),
+        ]))
+    }
+}
+
+#[test]
+fn expressions() {
+    let prepare = |tree: &str| {
+        let mut builder = fb("actions/expressions");
+        builder.main_tree(tree.to_string());
+        let mut f = builder.build().unwrap();
+        f.bb.put("x".to_string(), RtValue::int(3)).unwrap();
+        f.bb.put("limit".to_string(), RtValue::int(2)).unwrap();
+        f.bb.put("name".to_string(), RtValue::str("robot".to_string()))
+            .unwrap();
+        f
+    };
+
+    let mut f = prepare("main");
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+
+    let mut f = prepare("wrong");
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::failure("3 != -4".to_string())));
+}
+
+// adds the numbers and writes the sum into the output
+struct Add;
+
+impl Impl for Add {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        let a = args.find("a".to_string()).and_then(RtValue::as_int);
+        let b = args.find("b".to_string()).and_then(RtValue::as_int);
+        let res = args.find("res".to_string()).and_then(RtValue::as_pointer);
+        match (a, b, res) {
+            (Some(a), Some(b), Some(res)) => {
+                ctx.bb().put(res, RtValue::int(a + b))?;
+                Ok(TickResult::success())
+            }
+            _ => Ok(TickResult::failure(format!(
+                "the arguments {args} are wrong"
+            ))),
+        }
+    }
+}
+
+#[test]
+fn bb_refs() {
+    let prepare = |tree: &str| {
+        let mut builder = fb("actions/bb_refs");
+        builder.main_tree(tree.to_string());
+        builder.register_action("add", Action::sync(Add));
+        builder.build()
+    };
+
+    let mut f = prepare("main").unwrap();
+    f.bb.put("x".to_string(), RtValue::int(3)).unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    let double = f.bb.get("double".to_string()).unwrap().cloned();
+    assert_eq!(double, Some(RtValue::int(10)));
+
+    let mut f = prepare("absent").unwrap();
+    let result = f.run();
+    assert_eq!(
+        result,
+        Err(RuntimeError::bb(
+            "the key y is absent in the blackboard (the node absent/add at main.tree:8:13)"
+                .to_string()
+        ))
+    );
+
+    let mut f = prepare("wrong_type").unwrap();
+    f.bb.put("name".to_string(), RtValue::str("robot".to_string()))
+        .unwrap();
+    let result = f.run();
+    assert_eq!(
+        result,
+        Err(RuntimeError::WrongArgument(
+            "the value robot of the key name does not fit the type num of the parameter a \
+             (the node wrong_type/add at main.tree:10:17)"
+                .to_string()
+        ))
+    );
+
+    let mut builder = fb("actions/bb_refs");
+    builder.main_file("output.tree".to_string());
+    builder.register_action("add", Action::sync(Add));
+    match builder.build().map(|_| ()) {
+        Err(RuntimeError::CompileError(e)) => assert_eq!(
+            e.to_string(),
+            r#"error: the output parameter res of the call add expects a reference to the blackboard but got 3
+ --> output.tree:3:23
+  |
+3 | root main add({x}, 2, 3)
+  |                       ^"#
+        ),
+        r => panic!("the output parameter should not accept the literal: {r:?}"),
+    }
+}
+
+// remembers the arguments in the blackboard
+struct Move;
+
+impl Impl for Move {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        let speed = match args.find("speed".to_string()) {
+            Some(v) => v.cast(ctx.bb()).int()?,
+            None => None,
+        };
+        let zone = args.find("zone".to_string()).and_then(RtValue::as_string);
+        match (speed, zone) {
+            (Some(speed), Some(zone)) => {
+                ctx.bb().put(format!("speed_{zone}"), RtValue::int(speed))?;
+                Ok(TickResult::success())
+            }
+            _ => Ok(TickResult::failure(format!(
+                "the arguments {args} are wrong"
+            ))),
+        }
+    }
+}
+
+#[test]
+fn consts() {
+    let prepare = |tree: &str| {
+        let mut builder = fb("actions/consts");
+        builder.main_tree(tree.to_string());
+        builder.register_action("move", Action::sync(Move));
+        builder.build().unwrap()
+    };
+
+    let mut f = prepare("main");
+    assert_eq!(f.run(), Ok(TickResult::success()));
+    let speed = |f: &Forester, zone: &str| f.bb.get(format!("speed_{zone}")).unwrap().cloned();
+    assert_eq!(speed(&f, "Kitchen"), Some(RtValue::int(10)));
+    assert_eq!(speed(&f, "Hall"), Some(RtValue::int(10)));
+    assert_eq!(speed(&f, "Garage"), Some(RtValue::int(15)));
+
+    let mut f = prepare("shadowed");
+    f.bb.put("speed".to_string(), RtValue::int(7)).unwrap();
+    assert_eq!(f.run(), Ok(TickResult::success()));
+    assert_eq!(speed(&f, "Kitchen"), Some(RtValue::int(7)));
+}
@@ -0,0 +1,172 @@
+use crate::analysis::{analyze, Rule};
+use crate::tests::test_folder;
+use crate::tree::project::Project;
+
+fn lints(folder: &str) -> Vec<(Rule, String, usize, usize, String)> {
+    let project = Project::build("main.tree".to_string(), test_folder(folder)).unwrap();
+    analyze(&project)
+        .unwrap()
+        .into_iter()
+        .map(|l| {
+            let d = l.diagnostic;
+            (l.rule, d.file, d.line, d.column, d.message)
+        })
+        .collect()
+}
+
+fn lint(
+    rule: Rule,
+    file: &str,
+    line: usize,
+    column: usize,
+    message: &str,
+) -> (Rule, String, usize, usize, String) {
+    (rule, file.to_string(), line, column, message.to_string())
+}
+
+#[test]
+fn lints_in_project() {
+    assert_eq!(
+        lints("analysis/lints"),
+        vec![
+            lint(
+                Rule::ShadowedAlias,
+                "main.tree",
+                3,
+                1,
+                "the alias report shadows the name imported with the file nav.tree"
+            ),
+            lint(
+                Rule::UnusedImport,
+                "main.tree",
+                3,
+                1,
+                "the alias helper of the imported name unused_helper is not used"
+            ),
+            lint(
+                Rule::UnusedDefinition,
+                "main.tree",
+                10,
+                1,
+                "the definition unused_action is not used by any root"
+            ),
+            lint(
+                Rule::UnusedDefinition,
+                "main.tree",
+                12,
+                1,
+                "the definition unused_tree is not used by any root"
+            ),
+            lint(
+                Rule::UnreachableChild,
+                "main.tree",
+                20,
+                9,
+                "the child is unreachable since the previous child of the fallback never fails"
+            ),
+            lint(
+                Rule::UselessDecorator,
+                "main.tree",
+                22,
+                5,
+                "the decorator inverter cancels the nested inverter"
+            ),
+            lint(
+                Rule::UselessDecorator,
+                "main.tree",
+                23,
+                5,
+                "the decorator force_success overrides the result of the nested force_fail"
+            ),
+            lint(
+                Rule::InfiniteRepeat,
+                "main.tree",
+                24,
+                5,
+                "the repeat is infinite but none of its children can return running, so the tree spins without waiting"
+            ),
+            lint(
+                Rule::UselessDecorator,
+                "main.tree",
+                28,
+                5,
+                "the decorator inverter wraps the empty sequence"
+            ),
+            lint(
+                Rule::UnusedDefinition,
+                "nav.tree",
+                2,
+                1,
+                "the definition report is not used by any root"
+            ),
+            lint(
+                Rule::UnusedDefinition,
+                "utils.tree",
+                2,
+                1,
+                "the definition unused_helper is not used by any root"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn same_names_in_files() {
+    assert_eq!(
+        lints("analysis/same_names"),
+        vec![lint(
+            Rule::UnusedDefinition,
+            "base.tree",
+            1,
+            1,
+            "the definition check is not used by any root"
+        )]
+    );
+}
+
+#[test]
+fn unbuilt_root() {
+    assert_eq!(
+        lints("analysis/unbuilt"),
+        vec![
+            lint(
+                Rule::UnbuiltRoot,
+                "main.tree",
+                5,
+                5,
+                "the call missing can not be found among the file in the project"
+            ),
+            lint(
+                Rule::InfiniteRepeat,
+                "main.tree",
+                8,
+                13,
+                "the repeat is infinite but none of its children can return running, so the tree spins without waiting"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn clean_project() {
+    assert_eq!(lints("analysis/clean"), vec![]);
+}
+
+#[test]
+fn render() {
+    let project = Project::build("main.tree".to_string(), test_folder("analysis/lints")).unwrap();
+    let lints = analyze(&project).unwrap();
+    let unused = lints
+        .iter()
+        .find(|l| l.rule == Rule::UnusedDefinition)
+        .unwrap();
+    assert_eq!(
+        unused.to_string(),
+        r#"warning: the definition unused_action is not used by any root
+  --> main.tree:10:1
+   |
+10 | impl unused_action();
+   | ^^^^^^^^^^^^^^^^^^^^^
+   = note: lint unused_definition"#
+    );
+}
@@ -0,0 +1,243 @@
+use crate::btcpp::{export, export_tree, import, import_source};
+use crate::runtime::args::RtValue;
+use crate::runtime::rtree::rnode::RNode;
+use crate::runtime::rtree::RuntimeTree;
+use crate::tests::test_folder;
+use crate::tree::project::Project;
+
+fn project(folder: &str) -> Project {
+    Project::build("main.tree".to_string(), test_folder(folder)).unwrap()
+}
+
+#[test]
+fn export_project() {
+    let xml = export(&project("btcpp/export")).unwrap();
+    assert_eq!(
+        xml,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<root BTCPP_format="4" main_tree_to_execute="main">
+    <BehaviorTree ID="main">
+        <ReactiveSequence>
+            <Sequence name="patrol">
+                <Action ID="move_to" name="go" target="dock"/>
+                <Fallback>
+                    <Condition ID="is_ready" tag="a &amp; &lt;b&gt;"/>
+                    <AlwaysFailure/>
+                </Fallback>
+            </Sequence>
+            <RetryUntilSuccessful num_attempts="3">
+                <Timeout msec="2000">
+                    <Action ID="pick" items="a;b" picked="{picked}"/>
+                </Timeout>
+            </RetryUntilSuccessful>
+            <Precondition if="(speed &gt; 1)" else="FAILURE">
+                <Decorator ID="cooldown" duration="500">
+                    <Decorator ID="only_when" key="ready">
+                        <Action ID="move_to" name="go" target="{picked}"/>
+                    </Decorator>
+                </Decorator>
+            </Precondition>
+            <Repeat num_cycles="-1">
+                <AlwaysSuccess/>
+            </Repeat>
+            <AlwaysSuccess/>
+        </ReactiveSequence>
+    </BehaviorTree>
+    <TreeNodesModel>
+        <Decorator ID="cooldown">
+            <input_port name="duration"/>
+        </Decorator>
+        <Condition ID="is_ready">
+            <input_port name="tag" type="std::string"/>
+        </Condition>
+        <Action ID="move_to">
+            <input_port name="target" type="std::string"/>
+        </Action>
+        <Decorator ID="only_when">
+            <input_port name="key" type="std::string"/>
+        </Decorator>
+        <Action ID="pick">
+            <input_port name="items" type="std::vector&lt;std::string&gt;"/>
+            <output_port name="picked" type="std::string"/>
+        </Action>
+    </TreeNodesModel>
+</root>
+"#
+    );
+}
+
+#[test]
+fn export_params() {
+    let xml = export(&project("btcpp/params")).unwrap();
+    // the parameters take the values of the calls, the blackboard references stay as they are
+    assert_eq!(
+        xml,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<root BTCPP_format="4" main_tree_to_execute="main">
+    <BehaviorTree ID="main">
+        <Sequence>
+            <Sequence name="patrol">
+                <Sequence name="go">
+                    <RetryUntilSuccessful num_attempts="3">
+                        <Action ID="move_to" target="dock"/>
+                    </RetryUntilSuccessful>
+                </Sequence>
+                <Action ID="move_to" target="{point}"/>
+            </Sequence>
+            <Sequence name="patrol">
+                <Sequence name="go">
+                    <RetryUntilSuccessful num_attempts="3">
+                        <Action ID="move_to" target="gate"/>
+                    </RetryUntilSuccessful>
+                </Sequence>
+                <Action ID="move_to" target="{point}"/>
+            </Sequence>
+        </Sequence>
+    </BehaviorTree>
+    <TreeNodesModel>
+        <Action ID="move_to">
+            <input_port name="target" type="std::string"/>
+        </Action>
+    </TreeNodesModel>
+</root>
+"#
+    );
+}
+
+#[test]
+fn export_runtime_tree() {
+    let tree = RuntimeTree::build(project("btcpp/export")).unwrap();
+    let xml = export_tree(&tree).unwrap();
+
+    // without the definitions the conditions are exported as the actions with the untyped ports
+    assert!(xml.contains(r#"<Action ID="is_ready" tag="a &amp; &lt;b&gt;"/>"#));
+    assert!(xml.contains(
+        r#"        <Action ID="pick">
+            <input_port name="items"/>
+            <input_port name="picked"/>
+        </Action>"#
+    ));
+    assert!(!xml.contains("Condition"));
+}
+
+#[test]
+fn import_xml() {
+    let folder = test_folder("btcpp/import");
+    let xml = std::fs::read_to_string(folder.join("main.xml")).unwrap();
+    let expected = std::fs::read_to_string(folder.join("main.tree")).unwrap();
+    assert_eq!(import_source(&xml).unwrap(), expected);
+
+    // the imported file is the valid project
+    let tree = RuntimeTree::build(project("btcpp/import")).unwrap();
+    assert_eq!(
+        tree.find_by_path(
+            "MainTree/r_sequence[0]/fallback[1]/guard/Patrol/retry/timeout/FollowPath"
+        ),
+        Some(18)
+    );
+}
+
+#[test]
+fn export_import() {
+    let xml = export(&project("btcpp/export")).unwrap();
+    let src = import_source(&xml).unwrap();
+    assert!(src.contains("impl pick(items:array<string>, out picked:string);"));
+    assert!(src.contains("decorator only_when(key:string);"));
+    assert!(
+        src.contains("    retry(3) timeout(2000) pick(items = [\"a\", \"b\"], picked = {picked})")
+    );
+    assert!(src.contains(
+        "    guard(speed > 1) cooldown(500) only_when(key = \"ready\") move_to(target = {picked})"
+    ));
+
+    let mut root = std::env::temp_dir();
+    root.push("forester_btcpp_import");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("main.tree"), src).unwrap();
+    let imported = Project::build("main.tree".to_string(), root.clone());
+    std::fs::remove_dir_all(root).unwrap();
+    let xml_again = export(&imported.unwrap()).unwrap();
+    // the names of the instances are dropped while importing
+    let unnamed = xml
+        .replace(r#" name="patrol""#, "")
+        .replace(r#" name="go""#, "");
+    assert_eq!(xml_again, unnamed);
+}
+
+#[test]
+fn import_errors() {
+    let error = |tree: &str| {
+        let xml = format!(
+            r#"<root BTCPP_format="4"><BehaviorTree ID="main">{tree}</BehaviorTree></root>"#
+        );
+        import(&xml).unwrap_err().to_string()
+    };
+    assert_eq!(
+        error(r#"<SubTree ID="nav" target="{goal}"/>"#),
+        "the port target of the subtree nav is remapped to {goal} but the trees share the blackboard"
+    );
+    assert_eq!(
+        error(r#"<IfThenElse><A/><B/><C/></IfThenElse>"#),
+        "the control node IfThenElse has no counterpart in the tree language"
+    );
+    assert_eq!(
+        error(r#"<Precondition if="ready" else="SUCCESS"><A/></Precondition>"#),
+        "the attribute else=\"SUCCESS\" of the node Precondition has no counterpart in the tree language"
+    );
+    assert_eq!(
+        error(r#"<Action ID="pick" _skipIf="done"/>"#),
+        "the attribute _skipIf of the node Action has no counterpart in the tree language"
+    );
+    assert_eq!(
+        error(r#"<Inverter><A/><B/></Inverter>"#),
+        "the decorator Inverter should have only one child"
+    );
+    assert_eq!(
+        error(r#"<Sequence><Action ID="move-to"/><Action ID="move_to"/></Sequence>"#),
+        "the trees and nodes move-to and move_to both become move_to in the tree language"
+    );
+    assert_eq!(
+        error(r#"<Action ID="pick" item-id="1" item_id="2"/>"#),
+        "the ports of the node pick item-id and item_id both become item_id in the tree language"
+    );
+    assert_eq!(
+        error(r#"<Sequence><Action ID="a" k="{goal.x}"/><Action ID="b" k="{goal_x}"/></Sequence>"#),
+        "the blackboard keys goal.x and goal_x both become goal_x in the tree language"
+    );
+    assert!(import("<root><BehaviorTree").is_err());
+}
+
+#[test]
+fn import_quotes() {
+    let xml = r#"<root BTCPP_format="4">
+    <BehaviorTree ID="main">
+        <Action ID="say" text="say &quot;hi&quot; to C:\home"/>
+    </BehaviorTree>
+</root>"#;
+    let src = import_source(xml).unwrap();
+    assert!(src.contains(r#"say(text = "say \"hi\" to C:\\home")"#));
+
+    // the quotes and the backslashes are unescaped by the lexer
+    let mut root = std::env::temp_dir();
+    root.push("forester_btcpp_quotes");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("main.tree"), src).unwrap();
+    let imported = Project::build("main.tree".to_string(), root.clone());
+    std::fs::remove_dir_all(root).unwrap();
+    let tree = RuntimeTree::build(imported.unwrap()).unwrap();
+    let args = tree
+        .nodes
+        .values()
+        .find_map(|n| match n {
+            RNode::Leaf(_, args) => Some(args.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        args.find("text".to_string()),
+        Some(RtValue::str(r#"say "hi" to C:\home"#.to_string()))
+    );
+    assert!(export_tree(&tree)
+        .unwrap()
+        .contains(r#"text="say &quot;hi&quot; to C:\home""#));
+}
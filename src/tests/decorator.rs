@@ -1,9 +1,17 @@
 use crate::runtime::action::builtin::data::{GenerateData, StoreData};
 use crate::runtime::action::builtin::ReturnResult;
-use crate::runtime::action::Action;
-use crate::runtime::args::RtValue;
-use crate::runtime::TickResult;
+use crate::runtime::action::decorator::Decorator;
+use crate::runtime::action::flow::{run_with, REASON};
+use crate::runtime::action::{Action, Impl, ImplAsync, Tick};
+use crate::runtime::args::{RtArgs, RtValue};
+use crate::runtime::builder::ForesterBuilder;
+use crate::runtime::clock::{Clock, VirtualClock};
+use crate::runtime::context::{RNodeState, TreeContext};
+use crate::runtime::forester::Forester;
+use crate::runtime::{RtResult, RuntimeError, TickResult};
 use crate::tests::fb;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::time::SystemTime;
 
 #[test]
@@ -80,3 +88,447 @@ fn simple_delay() {
             .unwrap();
     assert_eq!(x.as_str(), "1")
 }
+
+struct OnlyWhen;
+
+impl Decorator for OnlyWhen {
+    fn prepare(
+        &self,
+        init_args: RtArgs,
+        tick_args: RtArgs,
+        ctx: &mut TreeContext,
+    ) -> RtResult<RNodeState> {
+        let key = init_args.first_as(RtValue::as_string).unwrap();
+        if ctx.bb().contains(key.clone())? {
+            Ok(RNodeState::Running(run_with(tick_args, 0, 1)))
+        } else {
+            Ok(RNodeState::Failure(
+                run_with(tick_args, 0, 1).with(REASON, RtValue::str(format!("no {key}"))),
+            ))
+        }
+    }
+
+    fn finalize(
+        &self,
+        init_args: RtArgs,
+        tick_args: RtArgs,
+        child_res: TickResult,
+        ctx: &mut TreeContext,
+    ) -> RtResult<RNodeState> {
+        Ok(RNodeState::from(run_with(tick_args, 0, 1), child_res))
+    }
+}
+
+#[test]
+fn custom() {
+    let mut fb = fb("decorators/custom");
+
+    fb.register_action("store", Action::sync(StoreData));
+    fb.register_decorator("only_when", OnlyWhen);
+
+    let mut f = fb.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+
+    assert!(f.bb.contains("first".to_string()).unwrap());
+    assert!(!f.bb.contains("second".to_string()).unwrap());
+}
+
+#[test]
+fn custom_imported() {
+    let mut fb = fb("decorators/imported");
+
+    fb.register_action("store", Action::sync(StoreData));
+    fb.register_decorator("only_when", OnlyWhen);
+
+    let mut f = fb.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+
+    assert!(f.bb.contains("first".to_string()).unwrap());
+    assert!(!f.bb.contains("second".to_string()).unwrap());
+}
+
+// fails as soon as the mode is switched to manual
+struct WhileAuto;
+
+impl Decorator for WhileAuto {
+    fn prepare(
+        &self,
+        _init_args: RtArgs,
+        tick_args: RtArgs,
+        ctx: &mut TreeContext,
+    ) -> RtResult<RNodeState> {
+        match ctx.bb().get("mode".to_string())?.cloned() {
+            Some(RtValue::String(mode)) if mode == "manual" => Ok(RNodeState::Failure(
+                run_with(tick_args, 0, 1).with(REASON, RtValue::str(format!("mode {mode}"))),
+            )),
+            _ => Ok(RNodeState::Running(run_with(tick_args, 0, 1))),
+        }
+    }
+
+    fn finalize(
+        &self,
+        _init_args: RtArgs,
+        tick_args: RtArgs,
+        child_res: TickResult,
+        _ctx: &mut TreeContext,
+    ) -> RtResult<RNodeState> {
+        Ok(RNodeState::from(run_with(tick_args, 0, 1), child_res))
+    }
+}
+
+#[test]
+fn custom_halts_child() {
+    let mut fb = fb("decorators/custom_halt");
+    fb.register_action("switch_mode", Action::Async(Box::new(SwitchMode)));
+    fb.register_decorator("while_auto", WhileAuto);
+
+    let mut f = fb.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::failure("mode manual".to_string())));
+    // the running child is halted when the decorator finishes before it
+    assert!(f.bb.contains("halted".to_string()).unwrap());
+}
+
+#[test]
+fn custom_not_registered() {
+    let mut fb = fb("decorators/custom");
+
+    fb.register_action("store", Action::sync(StoreData));
+
+    let result = fb.build().map(|_| ());
+    assert_eq!(
+        result,
+        Err(RuntimeError::UnImplementedAction(
+            "the decorators only_when are not registered".to_string()
+        ))
+    );
+}
+
+// advances the clock on every tick and finishes when 500 millis are passed
+struct SlowJob(VirtualClock);
+
+impl Impl for SlowJob {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        self.0.advance(100);
+        if self.0.millis() >= 500 {
+            Ok(TickResult::success())
+        } else {
+            Ok(TickResult::running())
+        }
+    }
+}
+
+#[test]
+fn timeout() {
+    let clock = VirtualClock::default();
+    let mut builder = fb("decorators/timeout");
+    builder.main_tree("main".to_string());
+
+    builder.register_action("job", Action::sync(SlowJob(clock.clone())));
+    builder.clock(clock.clone());
+
+    let mut f = builder.build().unwrap();
+    let result = f.run();
+    assert_eq!(
+        result,
+        Ok(TickResult::failure("the timeout 250 exceeded".to_string()))
+    );
+    assert_eq!(clock.millis(), 300);
+
+    let clock = VirtualClock::default();
+    let mut builder = fb("decorators/timeout");
+    builder.main_tree("main_long".to_string());
+
+    builder.register_action("job", Action::sync(SlowJob(clock.clone())));
+    builder.clock(clock.clone());
+
+    let mut f = builder.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    assert_eq!(clock.millis(), 500);
+}
+
+struct TickClock(VirtualClock);
+
+impl Impl for TickClock {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        self.0.advance(100);
+        Ok(TickResult::success())
+    }
+}
+
+#[test]
+fn delay_non_blocking() {
+    let clock = VirtualClock::default();
+    let mut fb = fb("decorators/delay_non_blocking");
+
+    fb.register_action("tick_clock", Action::sync(TickClock(clock.clone())));
+    fb.clock(clock.clone());
+
+    let mut f = fb.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+
+    let tick =
+        f.bb.get("delayed".to_string())
+            .unwrap()
+            .unwrap()
+            .clone()
+            .as_int()
+            .unwrap();
+    assert_eq!(tick, 4);
+    assert_eq!(clock.millis(), 400);
+}
+
+#[test]
+fn delay_paced_in_real_time() {
+    let mut fb = fb("decorators/simple_delay");
+    fb.register_action("store", Action::sync(StoreData));
+
+    let before = SystemTime::now();
+    let mut f = fb.build().unwrap();
+    // the ticks are not spent while the delay is waiting
+    let result = f.run_until(Some(10));
+    assert_eq!(result, Ok(TickResult::success()));
+
+    let duration = SystemTime::now().duration_since(before).unwrap();
+    assert!(duration.as_millis() >= 2000);
+}
+
+#[test]
+fn delay_paced() {
+    let clock = VirtualClock::default();
+    let mut fb = fb("decorators/simple_delay");
+    fb.register_action("store", Action::sync(StoreData));
+    fb.clock(clock.clone());
+
+    let mut f = fb.build().unwrap();
+    let result = f.run_until(Some(3));
+    assert_eq!(result, Ok(TickResult::success()));
+    assert_eq!(clock.millis(), 2000);
+}
+
+// fails twice and then succeeds, remembering the time of every attempt
+struct Flaky(VirtualClock, Rc<RefCell<Vec<i64>>>);
+
+impl Impl for Flaky {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        let mut attempts = self.1.borrow_mut();
+        attempts.push(self.0.millis());
+        if attempts.len() < 3 {
+            Ok(TickResult::failure_empty())
+        } else {
+            Ok(TickResult::success())
+        }
+    }
+}
+
+struct Count;
+
+impl Impl for Count {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        let curr = ctx
+            .bb()
+            .get("count".to_string())?
+            .and_then(|v| v.clone().as_int())
+            .unwrap_or(0);
+        ctx.bb().put("count".to_string(), RtValue::int(curr + 1))?;
+        Ok(TickResult::success())
+    }
+}
+
+// every tree advances the clock on 100 millis every tick
+fn time_limits(tree: &str, clock: &VirtualClock) -> ForesterBuilder {
+    let mut fb = fb("decorators/time_limits");
+    fb.main_tree(tree.to_string());
+    fb.register_action("tick_clock", Action::sync(TickClock(clock.clone())));
+    fb.register_action("count", Action::sync(Count));
+    fb.clock(clock.clone());
+    fb
+}
+
+fn count(f: &Forester) -> i64 {
+    f.bb.get("count".to_string())
+        .unwrap()
+        .and_then(|v| v.clone().as_int())
+        .unwrap_or(0)
+}
+
+#[test]
+fn retry_backoff() {
+    let clock = VirtualClock::default();
+    let attempts = Rc::new(RefCell::new(vec![]));
+    let mut fb = time_limits("backoff", &clock);
+    fb.register_action(
+        "flaky",
+        Action::sync(Flaky(clock.clone(), attempts.clone())),
+    );
+
+    let mut f = fb.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    // waits 100 and then 200 millis between the attempts
+    assert_eq!(*attempts.borrow(), vec![100, 200, 400]);
+}
+
+#[test]
+fn retry_backoff_by_keys() {
+    let clock = VirtualClock::default();
+    let attempts = Rc::new(RefCell::new(vec![]));
+    let mut fb = time_limits("backoff_by_keys", &clock);
+    fb.register_action(
+        "flaky",
+        Action::sync(Flaky(clock.clone(), attempts.clone())),
+    );
+
+    let mut f = fb.build().unwrap();
+    f.bb.put("attempts".to_string(), RtValue::int(3)).unwrap();
+    f.bb.put("base".to_string(), RtValue::int(100)).unwrap();
+    f.bb.put("factor".to_string(), RtValue::float(2.0)).unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    assert_eq!(*attempts.borrow(), vec![100, 200, 400]);
+}
+
+#[test]
+fn cooldown() {
+    let clock = VirtualClock::default();
+    let mut fb = time_limits("cooldown", &clock);
+
+    let mut f = fb.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    // runs at 100 and 400
+    assert_eq!(count(&f), 2);
+}
+
+#[test]
+fn rate_limit() {
+    let clock = VirtualClock::default();
+    let mut fb = time_limits("rate_limit", &clock);
+
+    let mut f = fb.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    // runs at 100, 200, 400, 500
+    assert_eq!(count(&f), 4);
+}
+
+// switches the mode and keeps running until it gets halted
+struct SwitchMode;
+
+impl ImplAsync for SwitchMode {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        ctx.bb()
+            .put("mode".to_string(), RtValue::str("manual".to_string()))?;
+        Ok(TickResult::running())
+    }
+
+    fn halt(&self, ctx: &mut TreeContext) -> Tick {
+        ctx.bb().put("halted".to_string(), RtValue::Bool(true))?;
+        Ok(TickResult::success())
+    }
+}
+
+#[test]
+fn guard() {
+    let mut builder = fb("decorators/guard");
+    builder.main_tree("main".to_string());
+
+    let mut f = builder.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    assert!(f.bb.contains("first".to_string()).unwrap());
+    assert!(!f.bb.contains("second".to_string()).unwrap());
+
+    let mut builder = fb("decorators/guard");
+    builder.main_tree("halt".to_string());
+    builder.register_action("switch_mode", Action::Async(Box::new(SwitchMode)));
+
+    let mut f = builder.build().unwrap();
+    let result = f.run();
+    assert_eq!(
+        result,
+        Ok(TickResult::failure(
+            r#"the guard (mode == "auto") is false"#.to_string()
+        ))
+    );
+    // the running child is halted when the guard turns false
+    assert!(f.bb.contains("halted".to_string()).unwrap());
+
+    let mut builder = fb("decorators/guard");
+    builder.main_tree("wrong_type".to_string());
+
+    let mut f = builder.build().unwrap();
+    let result = f.run();
+    assert_eq!(
+        result,
+        Err(RuntimeError::WrongArgument(
+            "the values auto and 1 can not be compared with > \
+             (the node wrong_type/sequence[0]/guard at main.tree:20:5)"
+                .to_string()
+        ))
+    );
+}
+
+// increments the counter and fails when it reaches the limit
+struct CountBelow;
+
+impl Impl for CountBelow {
+    fn tick(&self, args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        let limit = args.first_as(RtValue::as_int).unwrap_or(0);
+        Count.tick(args, ctx)?;
+        let curr = ctx
+            .bb()
+            .get("count".to_string())?
+            .and_then(|v| v.clone().as_int())
+            .unwrap_or(0);
+        if curr < limit {
+            Ok(TickResult::success())
+        } else {
+            Ok(TickResult::failure(format!("the limit {limit} is reached")))
+        }
+    }
+}
+
+fn loops(tree: &str) -> ForesterBuilder {
+    let mut fb = fb("decorators/loops");
+    fb.main_tree(tree.to_string());
+    fb.register_action("count", Action::sync(Count));
+    fb.register_action("count_below", Action::sync(CountBelow));
+    fb
+}
+
+#[test]
+fn keep_running() {
+    let mut f = loops("until_failure").build().unwrap();
+    let result = f.run();
+    assert_eq!(
+        result,
+        Ok(TickResult::failure("the limit 3 is reached".to_string()))
+    );
+    assert_eq!(count(&f), 3);
+
+    let mut f = loops("until_success").build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    assert_eq!(count(&f), 3);
+}
+
+#[test]
+fn run_once() {
+    let mut f = loops("once").build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+    assert_eq!(count(&f), 1);
+}
+
+#[test]
+fn repeat_infinite() {
+    let mut f = loops("infinite").build().unwrap();
+    let result = f.run_until(Some(10));
+    assert!(matches!(result, Err(RuntimeError::Stopped(_))));
+    assert_eq!(count(&f), 9);
+}
@@ -0,0 +1,130 @@
+use crate::runtime::rtree::RuntimeTree;
+use crate::tests::test_folder;
+use crate::tree::project::Project;
+use crate::tree::TreeError;
+
+fn build(folder: &str) -> Result<Project, TreeError> {
+    Project::build("main.tree".to_string(), test_folder(folder))
+}
+
+fn errors(folder: &str) -> Vec<(String, usize, usize, String)> {
+    match build(folder) {
+        Err(TreeError::Diagnostics(ds)) => ds
+            .into_iter()
+            .map(|d| (d.file, d.line, d.column, d.message))
+            .collect(),
+        r => panic!("the errors are expected: {r:?}"),
+    }
+}
+
+fn error(file: &str, line: usize, column: usize, message: &str) -> (String, usize, usize, String) {
+    (file.to_string(), line, column, message.to_string())
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        errors("diagnostics/parse"),
+        vec![
+            error("main.tree", 4, 6, "the token main is unexpected"),
+            error("main.tree", 6, 1, "the token } is unexpected"),
+            error("main.tree", 10, 10, "the token , is unexpected"),
+        ]
+    );
+}
+
+#[test]
+fn errors_in_files() {
+    assert_eq!(
+        errors("diagnostics/files"),
+        vec![
+            error("main.tree", 4, 12, "the token ) is unexpected"),
+            error("other.tree", 4, 18, "the token ) is unexpected"),
+        ]
+    );
+}
+
+#[test]
+fn missing_import() {
+    assert_eq!(
+        errors("diagnostics/missing"),
+        vec![
+            error("main.tree", 5, 12, "the token ) is unexpected"),
+            error("main.tree", 1, 1, "the file absent.tree can not be read"),
+            error("other.tree", 4, 18, "the token ) is unexpected"),
+        ]
+    );
+}
+
+#[test]
+fn unknown_token() {
+    let err = build("diagnostics/token").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"error: the token # is unknown
+ --> main.tree:2:10
+  |
+2 |     move(#1)
+  |          ^"#
+    );
+}
+
+#[test]
+fn duplicate_definition() {
+    let err = build("diagnostics/duplicate").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"error: the tree 'move' is already presented
+ --> main.tree:2:1
+  |
+2 | impl move(speed:num);
+  | ^^^^^^^^^^^^^^^^^^^^^"#
+    );
+}
+
+#[test]
+fn builder() {
+    let project = build("diagnostics/builder").unwrap();
+    let err = RuntimeTree::build(project).map(|_| ()).unwrap_err();
+    match err {
+        TreeError::Diagnostics(ds) => {
+            assert_eq!(ds.len(), 1);
+            assert_eq!((ds[0].line, ds[0].column), (5, 5));
+        }
+        e => panic!("the error should point to the call: {e:?}"),
+    }
+}
+
+#[test]
+fn import_cycle() {
+    assert_eq!(
+        errors("diagnostics/cycle"),
+        vec![error(
+            "b.tree",
+            3,
+            1,
+            "the import cycle is detected: a.tree -> b.tree -> a.tree"
+        )]
+    );
+}
+
+#[test]
+fn import_conflicts() {
+    assert_eq!(
+        errors("diagnostics/conflicts"),
+        vec![
+            error(
+                "main.tree",
+                3,
+                1,
+                "the name job is defined in both imported files a.tree and b.tree"
+            ),
+            error(
+                "main.tree",
+                8,
+                1,
+                "the definition run conflicts with the name imported from the file c.tree"
+            ),
+        ]
+    );
+}
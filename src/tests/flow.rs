@@ -2,9 +2,13 @@ use crate::runtime::action::builtin::data::GenerateData;
 use crate::runtime::action::builtin::ReturnResult;
 use crate::runtime::action::{Action, Impl, Tick};
 use crate::runtime::args::{RtArgs, RtValue};
+use crate::runtime::builder::ForesterBuilder;
 use crate::runtime::context::TreeContext;
-use crate::runtime::TickResult;
+use crate::runtime::rtree::RuntimeTree;
+use crate::runtime::{RuntimeError, TickResult};
 use crate::tests::{fb, test_folder, turn_on_logs};
+use crate::tracer::Tracer;
+use crate::tree::project::Project;
 
 struct StoreTick;
 
@@ -203,3 +207,89 @@ fn fallback_retry() {
     let result = f.run();
     assert_eq!(result, Ok(TickResult::success()));
 }
+
+struct Step;
+
+impl Impl for Step {
+    fn tick(&self, _args: RtArgs, ctx: &mut TreeContext) -> Tick {
+        let steps = ctx
+            .bb()
+            .get("steps".to_string())?
+            .and_then(|v| v.clone().as_int())
+            .unwrap_or_default();
+        ctx.bb().put("steps".to_string(), RtValue::int(steps + 1))?;
+        Ok(TickResult::success())
+    }
+}
+
+#[test]
+fn optimized() {
+    let run = |optimize: bool| {
+        let mut fb = fb("units/optimizer");
+        fb.optimize(optimize);
+        fb.tracer(Tracer::default());
+        fb.register_action("step", Action::sync(Step));
+
+        let mut f = fb.build().unwrap();
+        let result = f.run();
+        let steps =
+            f.bb.get("steps".to_string())
+                .ok()
+                .flatten()
+                .and_then(|v| v.clone().as_int());
+        (result, steps, f.tracer.to_string())
+    };
+
+    let (result, steps, trace) = run(false);
+    let (opt_result, opt_steps, opt_trace) = run(true);
+    assert_eq!(result, Ok(TickResult::success()));
+    assert_eq!(opt_result, result);
+    assert_eq!(steps, Some(5));
+    assert_eq!(opt_steps, steps);
+    assert!(opt_trace.lines().count() < trace.lines().count());
+    assert!(!trace.contains("folds"));
+    assert!(opt_trace.contains("    2 (folds 3,7) main/sequence[0] : Running(cursor=0,len=5)"));
+    assert!(opt_trace.contains(
+        "17 (folds 9,15) main/sequence[0]/fallback[1]/inverter/inverter/walk : Success()"
+    ));
+}
+
+#[test]
+fn compiled() {
+    let project = Project::build("main.tree".to_string(), test_folder("units/optimizer")).unwrap();
+    let json = RuntimeTree::build(project).unwrap().to_json().unwrap();
+
+    let mut fb = ForesterBuilder::new();
+    fb.runtime_tree(RuntimeTree::from_json(json.as_str()).unwrap());
+    fb.register_action("step", Action::sync(Step));
+
+    let mut f = fb.build().unwrap();
+    assert_eq!(f.run(), Ok(TickResult::success()));
+    let steps =
+        f.bb.get("steps".to_string())
+            .ok()
+            .flatten()
+            .and_then(|v| v.clone().as_int());
+    assert_eq!(steps, Some(5));
+
+    let mut fb = ForesterBuilder::new();
+    fb.runtime_tree(RuntimeTree::from_json(json.as_str()).unwrap());
+    assert_eq!(
+        fb.build().err(),
+        Some(RuntimeError::UnImplementedAction(
+            "the actions step are not registered".to_string()
+        ))
+    );
+
+    let mut fb = ForesterBuilder::new();
+    fb.runtime_tree(RuntimeTree::from_json(json.as_str()).unwrap());
+    fb.register_action("step", Action::sync(Step));
+    fb.bb_load("bb.json".to_string());
+    assert_eq!(
+        fb.build().err(),
+        Some(RuntimeError::IOError(
+            "the relative path \"bb.json\" of the blackboard dump needs the root to be set"
+                .to_string()
+        ))
+    );
+}
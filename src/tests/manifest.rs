@@ -0,0 +1,79 @@
+use crate::manifest::Manifest;
+use crate::runtime::TickResult;
+use crate::tests::test_folder;
+use std::path::PathBuf;
+
+fn manifest() -> Manifest {
+    Manifest::load(&test_folder("manifest/forester.toml")).unwrap()
+}
+
+#[test]
+fn load() {
+    let manifest = manifest();
+    let dir = test_folder("manifest");
+    assert_eq!(manifest.root(), dir.join("trees"));
+    assert_eq!(manifest.libs().paths, vec![dir.join("../libs/shared")]);
+    assert_eq!(manifest.output_dir(), dir.join("gen"));
+    assert_eq!(manifest.svg(), dir.join("gen/main.svg"));
+    assert!(manifest.tracer().is_none());
+
+    let project = manifest.project().unwrap();
+    assert_eq!(project.main, ("main.tree".to_string(), "main".to_string()));
+    assert!(project.files.contains_key("nav::follow.tree"));
+}
+
+#[test]
+fn profiles() {
+    let manifest = manifest();
+    assert_eq!(manifest.profile(None).unwrap().actions.len(), 2);
+    assert_eq!(
+        manifest.profile(Some("failing")).unwrap().config.max_ticks,
+        None
+    );
+    assert!(manifest.profile(Some("fast")).is_err());
+
+    let mut several = manifest.clone();
+    several.simulation.remove("default");
+    several
+        .simulation
+        .insert("slow".to_string(), PathBuf::from("sim/default.yaml"));
+    assert!(several.profile(None).is_err());
+}
+
+#[test]
+fn simulation() {
+    let manifest = manifest();
+    let mut sim = manifest.simulator(None).unwrap();
+    assert_eq!(sim.output, test_folder("manifest/gen"));
+    assert_eq!(sim.run(), Ok(TickResult::success()));
+
+    let mut sim = manifest.simulator(Some("failing")).unwrap();
+    assert!(matches!(sim.run(), Ok(TickResult::Failure(_))));
+}
+
+#[test]
+fn defaults() {
+    let dir = PathBuf::from("project");
+    let manifest = Manifest::parse(
+        r#"
+        [output]
+        svg = "/tmp/tree.svg"
+
+        [tracer]
+        file = "main.trace"
+        indent = 4
+        paths = true
+        "#,
+        dir.clone(),
+    )
+    .unwrap();
+    assert_eq!(manifest.root(), dir);
+    assert_eq!(manifest.main_file(), "main.tree");
+    assert_eq!(manifest.svg(), PathBuf::from("/tmp/tree.svg"));
+    let tracer = manifest.tracer().unwrap();
+    assert_eq!(tracer.to_file, Some(dir.join("main.trace")));
+    assert_eq!(tracer.indent, 4);
+    assert!(tracer.paths);
+
+    assert!(Manifest::parse("libs = 1", dir).is_err());
+}
@@ -165,6 +165,7 @@ fn smoke() {
         Some(&File {
             name: "nested/impls.tree".to_string(),
             imports: Default::default(),
+            import_spans: Default::default(),
             definitions: HashMap::from_iter(vec![
                 (
                     "is_approachable".to_string(),
@@ -248,6 +249,36 @@ fn smoke() {
                     )
                 ),
             ]),
+            consts: Default::default(),
+            enums: Default::default(),
         })
     );
 }
+
+#[test]
+fn libs() {
+    let root = crate::tests::test_folder("libs/project");
+    let project = Project::build("main.tree".to_string(), root.clone()).unwrap();
+    let mut files: Vec<_> = project.files.keys().cloned().collect();
+    files.sort();
+    assert_eq!(
+        files,
+        vec!["main.tree", "nav::follow.tree", "nav::utils.tree"]
+    );
+    assert_eq!(
+        project.files["nav::follow.tree"]
+            .imports
+            .keys()
+            .collect::<Vec<_>>(),
+        vec!["nav::utils.tree"]
+    );
+    assert!(crate::runtime::rtree::RuntimeTree::build(project).is_ok());
+
+    // the missing package is reported at the import
+    let error = Project::build("missing.tree".to_string(), root)
+        .map(|_| ())
+        .unwrap_err()
+        .to_string();
+    assert!(error.starts_with("error: the package vision is not found in the library paths"));
+    assert!(error.contains("--> missing.tree:1:1"));
+}
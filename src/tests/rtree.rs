@@ -4,9 +4,12 @@ mod tests {
     use crate::runtime::rtree::rnode::RNodeName::Name;
     use crate::runtime::rtree::rnode::{FlowType, RNode};
     use crate::runtime::rtree::RuntimeTree;
+    use crate::runtime::RuntimeError;
+    use crate::tree::diagnostic::Span;
     use crate::tree::parser::ast::call::Call;
     use crate::tree::project::Project;
     use graphviz_rust::attributes::arrowhead::vee;
+    use itertools::Itertools;
     use std::collections::HashMap;
     use std::path::PathBuf;
 
@@ -36,7 +39,10 @@ mod tests {
                             "wrapper".to_string(),
                             RtArgs(vec![RtArgument::new(
                                 "operation".to_string(),
-                                RtValue::Call(Call::ho_invocation("op")),
+                                // the calls keep the places they are written at
+                                RtValue::Call(
+                                    Call::ho_invocation("op").with_span(Span::new(58, 64))
+                                ),
                             )]),
                             vec![4]
                         )
@@ -48,17 +54,108 @@ mod tests {
                             "id".to_string(),
                             RtArgs(vec![RtArgument::new(
                                 "op".to_string(),
-                                RtValue::Call(Call::invocation("say_hi", Default::default()))
+                                RtValue::Call(
+                                    Call::invocation("say_hi", Default::default())
+                                        .with_span(Span::new(13, 21))
+                                )
                             )]),
                             vec![3]
                         )
                     )
                 ]),
                 std_nodes: Default::default(),
+                folded: Default::default(),
+                meta: tree.meta.clone(),
+                by_path: tree.by_path.clone(),
             }
         )
     }
 
+    #[test]
+    fn default_params() {
+        let tree = test_tree("units/defaults", "main.tree");
+        let speed = |v: f64| RtArgument::new("speed".to_string(), RtValue::float(v));
+        let target = RtArgument::new(
+            "target".to_string(),
+            RtValue::Object(HashMap::from_iter(vec![("x".to_string(), RtValue::int(1))])),
+        );
+
+        let mut leaves: Vec<_> = tree
+            .nodes
+            .iter()
+            .filter_map(|(id, n)| match n {
+                RNode::Leaf(_, args) => Some((*id, args.clone())),
+                _ => None,
+            })
+            .collect();
+        leaves.sort_by_key(|(id, _)| *id);
+        let leaves: Vec<_> = leaves.into_iter().map(|(_, args)| args).collect();
+
+        assert_eq!(
+            leaves,
+            vec![
+                RtArgs(vec![speed(1.0)]),
+                RtArgs(vec![speed(2.0)]),
+                RtArgs(vec![target, speed(1.0)]),
+            ]
+        );
+    }
+
+    // renders the node and its children like `main(sequence(job))`
+    fn shape(tree: &RuntimeTree, id: usize) -> String {
+        let children = |ids: Vec<usize>| ids.into_iter().map(|c| shape(tree, c)).join(",");
+        match &tree.nodes[&id] {
+            RNode::Leaf(name, args) if args.0.is_empty() => name.name().unwrap().to_string(),
+            RNode::Leaf(name, args) => format!("{}<{}>", name.name().unwrap(), args),
+            RNode::Flow(_, Name(name), _, ch) => format!("{name}({})", children(ch.clone())),
+            RNode::Flow(tpe, _, _, ch) => format!("{tpe}({})", children(ch.clone())),
+            RNode::Decorator(tpe, _, ch) => format!("{tpe}({})", shape(tree, *ch)),
+        }
+    }
+
+    #[test]
+    fn lambda_captures() {
+        let tree = test_tree("units/lambda", "main.tree");
+        assert_eq!(
+            shape(&tree, tree.root),
+            "main(sequence(\
+                logged(wrapper(log<id=start>,sequence(log<id=task>,job))),\
+                logged(wrapper(log<id=start>,sequence(log<id=task>,inverter(job)))),\
+                retried(retry(wrapper(log<id=start>,job)))\
+            ))"
+        );
+    }
+
+    #[test]
+    fn optimize() {
+        let mut tree = test_tree("units/optimizer", "main.tree");
+        assert_eq!(
+            shape(&tree, tree.root),
+            "main(sequence(\
+                route(step,sequence(step,sequence(step))),\
+                fallback(fallback(fail<reason=blocked>,fail<reason=closed>),inverter(inverter(step))),\
+                sequence(step)\
+            ))"
+        );
+
+        tree.optimize().unwrap();
+        assert_eq!(
+            shape(&tree, tree.root),
+            "main(sequence(step,step,step,fallback(fail<reason=blocked>,fail<reason=closed>,step),step))"
+        );
+        // the alias walk is inlined
+        assert_eq!(
+            tree.nodes[&6],
+            RNode::action("step".to_string(), RtArgs::default())
+        );
+        // the lambda and the named sequences are flattened into the root sequence
+        assert_eq!(tree.origins(&2), vec![2, 3, 7]);
+        // the inverters are folded into the action
+        assert_eq!(tree.origins(&17), vec![9, 15, 17]);
+        assert_eq!(tree.replacement(&12), 16);
+        assert_eq!(tree.replacement(&6), 6);
+    }
+
     #[test]
     fn std_action() {
         let tree = test_tree("actions", "std_actions.tree");
@@ -75,4 +172,65 @@ mod tests {
         let tree = test_tree("plain_project", "main.tree");
         println!("{:?}", tree);
     }
+    #[test]
+    fn paths() {
+        let tree = test_tree("units/lambda", "main.tree");
+        assert_eq!(tree.path(&1), Some("main"));
+        assert_eq!(tree.path(&4), Some("main/sequence[0]/logged[1]"));
+        assert_eq!(
+            tree.path(&20),
+            Some("main/sequence[0]/logged[1]/wrapper/sequence[1]/inverter/job")
+        );
+        assert_eq!(
+            tree.find_by_path("main/sequence[0]/retried/retry/wrapper/job"),
+            Some(19)
+        );
+        assert_eq!(tree.find_by_path("main/sequence[0]/logged"), None);
+
+        // the calls passed as arguments are located at the place they are written
+        let location = tree.location(&15).unwrap();
+        assert_eq!(location.to_string(), "main.tree:19:12");
+        assert_eq!(tree.location(&9).unwrap().to_string(), "main.tree:5:5");
+
+        // the paths are the same for the same project
+        assert_eq!(test_tree("units/lambda", "main.tree").meta, tree.meta);
+    }
+
+    #[test]
+    fn compiled() {
+        let mut tree = test_tree("units/lambda", "main.tree");
+        tree.optimize().unwrap();
+
+        let json = tree.to_json().unwrap();
+        assert_eq!(RuntimeTree::from_json(json.as_str()).unwrap(), tree);
+
+        let bytes = tree.to_bytes().unwrap();
+        assert!(bytes.len() < json.len());
+        assert_eq!(RuntimeTree::from_bytes(bytes.as_slice()).unwrap(), tree);
+
+        let mut file = std::env::temp_dir();
+        file.push("forester_compiled_lambda.json");
+        tree.save(&file).unwrap();
+        assert_eq!(RuntimeTree::load(&file).unwrap(), tree);
+        std::fs::remove_file(file).unwrap();
+
+        let mut broken = test_tree("units/lambda", "main.tree");
+        broken.nodes.remove(&20);
+        let error = RuntimeTree::from_json(broken.to_json().unwrap().as_str()).unwrap_err();
+        assert_eq!(
+            error,
+            RuntimeError::IOError(
+                "the compiled tree is broken: the node 20 is not found".to_string()
+            )
+        );
+        assert!(RuntimeTree::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn compiled_deterministic() {
+        let tree = test_tree("units/lambda", "main.tree");
+        let same = test_tree("units/lambda", "main.tree");
+        assert_eq!(tree.to_json().unwrap(), same.to_json().unwrap());
+        assert_eq!(tree.to_bytes().unwrap(), same.to_bytes().unwrap());
+    }
 }
@@ -17,6 +17,7 @@ fn file() {
                 trace: Some(String::from("main.trace")),
                 graph: Some(String::from("main.svg")),
                 max_ticks: Some(10),
+                virtual_time: false,
                 bb: BbConfig {
                     dump: Some(String::from("bb.dump")),
                     load: Some(String::from("bb.json")),
@@ -1,4 +1,5 @@
 use crate::runtime::action::builtin::data::{GenerateData, StoreTick};
+use crate::runtime::action::builtin::ReturnResult;
 use crate::runtime::action::{Action, Impl, Tick};
 use crate::runtime::args::{RtArgs, RtValue};
 use crate::runtime::context::TreeContext;
@@ -13,7 +14,13 @@ use std::time::SystemTime;
 #[test]
 fn smoke() {
     let mut fb = fb("flow/sequence_running");
-    fb.tracer(Tracer::default());
+    fb.tracer(
+        Tracer::create(TracerConfiguration {
+            paths: false,
+            ..TracerConfiguration::in_memory()
+        })
+        .unwrap(),
+    );
     fb.register_action(
         "incr",
         Action::sync(GenerateData::new(|v| {
@@ -214,24 +221,24 @@ fn custom_state() {
     let trace = f.tracer.to_string();
     assert_eq!(
         trace,
-        r#"[1]  1 : Running(cursor=0,len=1)
-[1]    2 : Running(len=1)
+        r#"[1]  1 main : Running(cursor=0,len=1)
+[1]    2 main/repeat : Running(len=1)
 [1]      i = 0
-[1]      3 : Success()
-[1]    2 : Running(arg=2,cursor=0,len=1)
+[1]      3 main/repeat/custom_state : Success()
+[1]    2 main/repeat : Running(arg=2,cursor=0,len=1)
 [2]  next tick
-[2]    2 : Running(arg=2,cursor=0,len=1)
+[2]    2 main/repeat : Running(arg=2,cursor=0,len=1)
 [2]      i = 1
-[2]      3 : Success()
-[2]    2 : Running(arg=3,cursor=0,len=1)
-[2]  1 : Running(cursor=0,len=1)
+[2]      3 main/repeat/custom_state : Success()
+[2]    2 main/repeat : Running(arg=3,cursor=0,len=1)
+[2]  1 main : Running(cursor=0,len=1)
 [3]  next tick
-[3]    2 : Running(arg=3,cursor=0,len=1)
+[3]    2 main/repeat : Running(arg=3,cursor=0,len=1)
 [3]      i = 2
-[3]      3 : Success()
-[3]    2 : Success(arg=3,cursor=0,len=1)
-[3]  1 : Running(cursor=0,len=1)
-[3]  1 : Success(cursor=0,len=1)
+[3]      3 main/repeat/custom_state : Success()
+[3]    2 main/repeat : Success(arg=3,cursor=0,len=1)
+[3]  1 main : Running(cursor=0,len=1)
+[3]  1 main : Success(cursor=0,len=1)
 "#
         .replace("\n", tracer::LINE_ENDING)
     )
@@ -265,6 +272,7 @@ fn file() {
         Tracer::create(TracerConfiguration {
             indent: 2,
             to_file: Some(tracer_log.clone()),
+            paths: false,
         })
         .unwrap(),
     );
@@ -300,3 +308,42 @@ fn file() {
         .replace("\n", tracer::LINE_ENDING)
     );
 }
+
+#[test]
+fn paths() {
+    let mut fb = fb("tracer/custom");
+    fb.register_action("custom_state", Action::sync(ReturnResult::success()));
+    fb.tracer(
+        Tracer::create(TracerConfiguration {
+            paths: true,
+            ..TracerConfiguration::in_memory()
+        })
+        .unwrap(),
+    );
+
+    let mut f = fb.build().unwrap();
+    let result = f.run();
+    assert_eq!(result, Ok(TickResult::success()));
+
+    let trace = f.tracer.to_string();
+    assert_eq!(
+        trace,
+        r#"[1]  1 main : Running(cursor=0,len=1)
+[1]    2 main/repeat : Running(len=1)
+[1]      3 main/repeat/custom_state : Success()
+[1]    2 main/repeat : Running(arg=2,cursor=0,len=1)
+[2]  next tick
+[2]    2 main/repeat : Running(arg=2,cursor=0,len=1)
+[2]      3 main/repeat/custom_state : Success()
+[2]    2 main/repeat : Running(arg=3,cursor=0,len=1)
+[2]  1 main : Running(cursor=0,len=1)
+[3]  next tick
+[3]    2 main/repeat : Running(arg=3,cursor=0,len=1)
+[3]      3 main/repeat/custom_state : Success()
+[3]    2 main/repeat : Success(arg=3,cursor=0,len=1)
+[3]  1 main : Running(cursor=0,len=1)
+[3]  1 main : Success(cursor=0,len=1)
+"#
+        .replace("\n", tracer::LINE_ENDING)
+    );
+}
@@ -0,0 +1,77 @@
+use crate::tests::test_folder;
+use crate::tree::project::typecheck::check_types;
+use crate::tree::project::Project;
+use crate::tree::TreeError;
+
+fn check(folder: &str) -> Result<(), TreeError> {
+    let project = Project::build("main.tree".to_string(), test_folder(folder)).unwrap();
+    check_types(&project)
+}
+
+#[test]
+fn valid() {
+    assert_eq!(check("typecheck/valid"), Ok(()));
+}
+
+#[test]
+fn errors() {
+    let errors = vec![
+        (11, 13, "the argument [\"1\", \"2\"] of the call move has the type array but the parameter points has the type array<num>"),
+        (12, 10, "the argument fast of the call move has the type string but the parameter speed has the type num"),
+        (13, 5, "the call move doesn't have the same number of arguments and parameters"),
+        (14, 5, "the call log doesn't have the same number of arguments and parameters"),
+        (16, 13, "the argument task of the call wrapper has the type string but the parameter task has the type tree"),
+        (17, 10, "the argument (1 > 2) of the call move has the type bool but the parameter speed has the type num"),
+        (6, 5, "the parameter name has the type num and can not be invoked"),
+        (7, 9, "the argument name of the call log has the type num but the parameter info has the type string"),
+    ];
+    match check("typecheck/errors") {
+        Err(TreeError::Diagnostics(ds)) => {
+            let found: Vec<_> = ds
+                .iter()
+                .map(|d| (d.line, d.column, d.message.as_str()))
+                .collect();
+            assert_eq!(found, errors);
+            assert!(ds.iter().all(|d| d.file == "main.tree"));
+        }
+        e => panic!("the errors should point to the source: {e:?}"),
+    }
+}
+
+#[test]
+fn enums() {
+    let errors = vec![
+        (7, 10, "the enum Zone does not have the variant Garage"),
+        (8, 10, "the enum Area is not declared"),
+        (8, 10, "the argument Area::Hall of the call move has the type Area but the parameter zone has the type Zone"),
+        (9, 10, "the argument Hall of the call move has the type string but the parameter zone has the type Zone"),
+    ];
+    match check("typecheck/enums") {
+        Err(TreeError::Diagnostics(ds)) => {
+            let found: Vec<_> = ds
+                .iter()
+                .map(|d| (d.line, d.column, d.message.as_str()))
+                .collect();
+            assert_eq!(found, errors);
+        }
+        e => panic!("the unknown enums should be reported: {e:?}"),
+    }
+}
+
+#[test]
+fn unknown_types() {
+    let errors = vec![
+        (4, 1, "the type Area of the parameter zones is unknown"),
+        (3, 1, "the type nmu of the parameter speed is unknown"),
+    ];
+    match Project::build("main.tree".to_string(), test_folder("typecheck/unknown")) {
+        Err(TreeError::Diagnostics(ds)) => {
+            let found: Vec<_> = ds
+                .iter()
+                .map(|d| (d.line, d.column, d.message.as_str()))
+                .collect();
+            assert_eq!(found, errors);
+        }
+        e => panic!("the unknown types should be reported: {e:?}"),
+    }
+}
@@ -1,6 +1,9 @@
 use crate::runtime::context::RNodeState;
 use crate::runtime::rtree::rnode::RNodeId;
+use crate::runtime::rtree::RuntimeTree;
 use crate::runtime::RtResult;
+use itertools::Itertools;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::fs;
 use std::fs::OpenOptions;
@@ -21,11 +24,15 @@ pub enum Tracer {
         events: Vec<Trace>,
         level: usize,
         indent: usize,
+        paths: Option<HashMap<RNodeId, String>>,
+        origins: HashMap<RNodeId, Vec<RNodeId>>,
     },
     InFile {
         level: usize,
         indent: usize,
         file: PathBuf,
+        paths: Option<HashMap<RNodeId, String>>,
+        origins: HashMap<RNodeId, Vec<RNodeId>>,
     },
 }
 
@@ -73,21 +80,68 @@ impl Tracer {
             }
         }
     }
+    /// takes the paths of the nodes from the tree if the tracer is configured to show them
+    pub fn set_paths(&mut self, tree: &RuntimeTree) {
+        match self {
+            Tracer::Noop => {}
+            Tracer::InMemory { paths, .. } | Tracer::InFile { paths, .. } => {
+                if let Some(paths) = paths {
+                    for (id, meta) in tree.meta.iter() {
+                        paths.insert(*id, meta.path.clone());
+                    }
+                }
+            }
+        }
+    }
+    /// takes the nodes removed by the [optimization](RuntimeTree::optimize) from the tree,
+    /// grouped by the nodes they are folded into, see [`RuntimeTree::origins`]
+    pub fn set_origins(&mut self, tree: &RuntimeTree) {
+        match self {
+            Tracer::Noop => {}
+            Tracer::InMemory { origins, .. } | Tracer::InFile { origins, .. } => {
+                for removed in tree.folded.keys() {
+                    origins
+                        .entry(tree.replacement(removed))
+                        .or_default()
+                        .push(*removed);
+                }
+                for removed in origins.values_mut() {
+                    removed.sort();
+                }
+            }
+        }
+    }
     pub fn trace(&mut self, tick: usize, ev: Event) {
         match self {
             Tracer::Noop => {}
-            Tracer::InMemory { events, level, .. } => {
+            Tracer::InMemory {
+                events,
+                level,
+                paths,
+                origins,
+                ..
+            } => {
                 let trace = Trace {
                     level: *level,
                     tick,
+                    path: path(paths, &ev),
+                    origins: folded(origins, &ev),
                     ev,
                 };
                 events.push(trace);
             }
-            Tracer::InFile { file, level, .. } => {
+            Tracer::InFile {
+                file,
+                level,
+                paths,
+                origins,
+                ..
+            } => {
                 let trace = Trace {
                     level: *level,
                     tick,
+                    path: path(paths, &ev),
+                    origins: folded(origins, &ev),
                     ev,
                 };
                 let mut file = OpenOptions::new()
@@ -106,11 +160,18 @@ impl Tracer {
         Tracer::Noop
     }
     pub fn create(cfg: TracerConfiguration) -> RtResult<Self> {
+        let paths = if cfg.paths {
+            Some(HashMap::new())
+        } else {
+            None
+        };
         match cfg.to_file {
             None => Ok(Tracer::InMemory {
                 events: vec![],
                 level: 0,
                 indent: cfg.indent,
+                paths,
+                origins: HashMap::new(),
             }),
             Some(file) => {
                 // println!(" >> {:?}", file);
@@ -125,16 +186,35 @@ impl Tracer {
                     level: 0,
                     indent: cfg.indent,
                     file,
+                    paths,
+                    origins: HashMap::new(),
                 })
             }
         }
     }
 }
 
+fn path(paths: &Option<HashMap<RNodeId, String>>, ev: &Event) -> Option<String> {
+    match (paths, ev) {
+        (Some(paths), Event::NewState(id, _)) => paths.get(id).cloned(),
+        _ => None,
+    }
+}
+
+fn folded(origins: &HashMap<RNodeId, Vec<RNodeId>>, ev: &Event) -> Vec<RNodeId> {
+    match ev {
+        Event::NewState(id, _) => origins.get(id).cloned().unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
 #[derive(Debug)]
 pub struct TracerConfiguration {
     pub indent: usize,
     pub to_file: Option<PathBuf>,
+    /// adds the paths of the nodes to their ids, see [`RuntimeTree::path`].
+    /// It is turned on by default.
+    pub paths: bool,
 }
 
 impl TracerConfiguration {
@@ -142,12 +222,14 @@ impl TracerConfiguration {
         TracerConfiguration {
             indent: 2,
             to_file: Some(file),
+            paths: true,
         }
     }
     pub fn in_memory() -> TracerConfiguration {
         TracerConfiguration {
             indent: 2,
             to_file: None,
+            paths: true,
         }
     }
 }
@@ -157,6 +239,7 @@ impl Default for TracerConfiguration {
         TracerConfiguration {
             indent: 2,
             to_file: None,
+            paths: true,
         }
     }
 }
@@ -191,6 +274,10 @@ pub struct Trace {
     pub level: usize,
     pub tick: usize,
     pub ev: Event,
+    /// the path of the node whose state is changed if the paths are turned on
+    pub path: Option<String>,
+    /// the ids of the nodes folded into the node whose state is changed by the optimization
+    pub origins: Vec<RNodeId>,
 }
 
 impl Display for Trace {
@@ -200,7 +287,22 @@ impl Display for Trace {
             "",
             tick = self.tick,
             indent = self.level,
-            v = format!("{}{}", self.ev, LINE_ENDING),
+            v = match &self.ev {
+                Event::NewState(id, s) => {
+                    let folded = if self.origins.is_empty() {
+                        "".to_string()
+                    } else {
+                        format!(" (folds {})", self.origins.iter().join(","))
+                    };
+                    let path = self
+                        .path
+                        .as_ref()
+                        .map(|p| format!(" {p}"))
+                        .unwrap_or_default();
+                    format!("{id}{folded}{path} : {s}{LINE_ENDING}")
+                }
+                ev => format!("{ev}{LINE_ENDING}"),
+            },
         );
         f.write_str(v.as_str())?;
         Ok(())
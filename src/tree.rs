@@ -1,8 +1,13 @@
 use crate::runtime::RuntimeError;
+use crate::tree::diagnostic::{Diagnostic, Span};
+use itertools::Itertools;
 use parsit::error::ParseError;
 use parsit::step::Step;
+use std::fmt::{Display, Formatter};
 
+pub mod diagnostic;
 pub mod parser;
+pub mod printer;
 pub mod project;
 
 pub fn cerr(v: String) -> TreeError {
@@ -15,6 +20,33 @@ pub enum TreeError {
     CompileError(String),
     VisualizationError(String),
     IOError(String),
+    /// the errors pointing to the places in the source files
+    Diagnostics(Vec<Diagnostic>),
+}
+
+impl TreeError {
+    /// Attaches the position in the source to the error.
+    /// The errors that already have positions are kept as they are.
+    pub fn at(self, file: &str, src: &str, span: Span) -> TreeError {
+        match self {
+            TreeError::ParseError(s) | TreeError::CompileError(s) => {
+                TreeError::Diagnostics(vec![Diagnostic::new(file, src, span, s)])
+            }
+            e => e,
+        }
+    }
+}
+
+impl Display for TreeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeError::ParseError(s)
+            | TreeError::CompileError(s)
+            | TreeError::VisualizationError(s)
+            | TreeError::IOError(s) => write!(f, "{s}"),
+            TreeError::Diagnostics(ds) => write!(f, "{}", ds.iter().join("\n\n")),
+        }
+    }
 }
 
 impl From<ParseError<'_>> for TreeError {
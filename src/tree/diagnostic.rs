@@ -0,0 +1,164 @@
+use crate::tree::project::FileName;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use strum_macros::Display;
+
+/// The position of a node in the source file, the byte range `start..end`.
+///
+/// The positions take part in the comparison of the ast nodes,
+/// the structure alone is compared after the positions are dropped, see [`AstFile::without_spans`].
+///
+/// [`AstFile::without_spans`]: crate::tree::parser::ast::AstFile::without_spans
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+impl From<Range<usize>> for Span {
+    fn from(value: Range<usize>) -> Self {
+        Span::new(value.start, value.end)
+    }
+}
+
+/// The position in the source file, the line and the column start from 1: `main.tree:3:5`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Location {
+    pub file: FileName,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    pub fn new(file: &str, src: &str, span: Span) -> Self {
+        let (start, line_start) = start_of(src, span);
+        Location {
+            file: file.to_string(),
+            line: src[..start].matches('\n').count() + 1,
+            column: src[line_start..start].chars().count() + 1,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// The severity of the diagnostic.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "snake_case")]
+pub enum Severity {
+    Error,
+    /// the warnings of the [analysis](crate::analysis) do not stop the tree from being built
+    Warning,
+}
+
+/// The error or the warning pointing to the place in the source file.
+/// It is rendered with the line of the source and the underlined part of it:
+/// ```text
+/// error: the call move doesn't have the same number of arguments and parameters
+///  --> main.tree:3:5
+///   |
+/// 3 |     move(1)
+///   |     ^^^^^^^
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: FileName,
+    /// the line and the column starting from 1
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    /// the additional line under the source, e.g. the lint that produced the warning
+    pub note: Option<String>,
+    /// the line of the source the span starts at
+    source_line: String,
+    /// the part of the source line before the span and the span itself (in chars)
+    offset: usize,
+    width: usize,
+}
+
+impl Diagnostic {
+    pub fn new(file: &str, src: &str, span: Span, message: String) -> Self {
+        let Location { file, line, column } = Location::new(file, src, span);
+        let (start, line_start) = start_of(src, span);
+        let line_end = src[start..]
+            .find('\n')
+            .map(|i| start + i)
+            .unwrap_or(src.len());
+        let end = floor_char_boundary(src, span.end.clamp(start, line_end));
+
+        Diagnostic {
+            severity: Severity::Error,
+            file,
+            line,
+            column,
+            message,
+            note: None,
+            source_line: src[line_start..line_end].trim_end_matches('\r').to_string(),
+            offset: column - 1,
+            width: src[start..end].trim_end().chars().count().max(1),
+        }
+    }
+
+    pub fn warning(file: &str, src: &str, span: Span, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::new(file, src, span, message)
+        }
+    }
+
+    pub fn with_note(self, note: String) -> Self {
+        Diagnostic {
+            note: Some(note),
+            ..self
+        }
+    }
+}
+
+/// the start of the span and the start of its line, both on the char boundaries
+fn start_of(src: &str, span: Span) -> (usize, usize) {
+    let start = floor_char_boundary(src, span.start.min(src.len()));
+    let line_start = src[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (start, line_start)
+}
+
+fn floor_char_boundary(src: &str, mut idx: usize) -> usize {
+    while !src.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        // the tabs are kept to place the carets under the span
+        let indent: String = self
+            .source_line
+            .chars()
+            .take(self.offset)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        writeln!(f, "{}: {}", self.severity, self.message)?;
+        writeln!(f, "{gutter}--> {}:{}:{}", self.file, self.line, self.column)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{gutter} | {indent}{}", "^".repeat(self.width))?;
+        if let Some(note) = &self.note {
+            write!(f, "\n{gutter} = note: {note}")?;
+        }
+        Ok(())
+    }
+}
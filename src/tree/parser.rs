@@ -2,25 +2,39 @@ pub mod ast;
 mod lexer;
 mod tests;
 
+use crate::tree::diagnostic::{Diagnostic, Span};
 use crate::tree::parser::ast::*;
-use crate::tree::parser::lexer::Token;
+use crate::tree::parser::lexer::{Token, Trivia};
 use crate::tree::TreeError;
 use ast::arg::{Argument, ArgumentRhs, Arguments, MesType, Param, Params};
 use ast::call::{Call, Calls};
+use ast::expr::{BinOp, Expr};
 use ast::message::{Bool, Message, Number, StringLit};
+use logos::Logos;
 use parsit::error::ParseError;
 use parsit::parser::{EmptyToken, Parsit};
 use parsit::step::Step;
 use parsit::{seq, token, wrap};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::env::Args;
 use std::fs;
+use std::ops::Range;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 pub struct Parser<'a> {
     inner: Parsit<'a, Token>,
+    src: &'a str,
+    /// the positions of the tokens in the source, the parser addresses the tokens by the indexes
+    spans: Vec<Range<usize>>,
+    /// the furthest token the parser has looked at, the failures are reported there
+    furthest: Cell<usize>,
+    /// the errors the parser has recovered from
+    errors: RefCell<Vec<ParseError<'a>>>,
+    /// the names of the user-defined decorators declared in the file
+    decorators: HashSet<Key>,
 }
 
 impl<'a> Parser<'a> {
@@ -71,9 +85,22 @@ impl<'a> Parser<'a> {
     fn str(&self, pos: usize) -> Step<'a, StringLit> {
         token!(self.token(pos) => Token::StringLit(v) => StringLit(v.clone()) )
     }
-    fn num(&self, pos: usize) -> Step<'a, Number> {
+    fn digit(&self, pos: usize) -> Step<'a, Number> {
         token!(self.token(pos) => Token::Digit(n) => n.clone() )
     }
+    fn num(&self, pos: usize) -> Step<'a, Number> {
+        self.minus(pos)
+            .then(|p| self.digit(p))
+            .map(|n| match n {
+                Number::Int(v) => Number::Int(-v),
+                Number::Float(v) => Number::Float(-v),
+                Number::Hex(v) => Number::Hex(-v),
+                Number::Binary(v) => Number::Binary(-v),
+            })
+            .or_from(pos)
+            .or(|p| self.digit(p))
+            .into()
+    }
     fn bool(&self, pos: usize) -> Step<'a, Bool> {
         token!(self.token(pos) =>
                 Token::True => Bool::True ,
@@ -109,16 +136,33 @@ impl<'a> Parser<'a> {
 
         wrap!(pos => l; elems or def; r)
     }
-    fn params(&self, pos: usize) -> Step<'a, Params> {
+    fn params(&'a self, pos: usize) -> Step<'a, Params> {
         let l = |p| self.l_pr(p);
         let r = |p| self.r_pr(p);
         let comma = |p| self.comma(p);
 
+        let default = |p| self.assign(p).then(|p| self.message(p)).or_none();
         let param = |p| {
-            self.id(p)
+            self.out(p)
+                .or_none()
+                .then_zip(|p| self.id(p))
+                .then_zip(|p| self.question(p).or_none())
                 .then_skip(|p| self.colon(p))
                 .then_zip(|p| self.mes_type(p))
-                .map(|(name, tpe)| Param { name, tpe })
+                .then_zip(default)
+                .validate(|(((_, _), tpe), default)| match default {
+                    Some(m) if !m.same(tpe) => {
+                        Err("the default value does not coincide with the type of the parameter")
+                    }
+                    _ => Ok(()),
+                })
+                .map(|((((out, name), question), tpe), default)| Param {
+                    name,
+                    tpe,
+                    output: out.is_some(),
+                    optional: question.is_some(),
+                    default,
+                })
         };
 
         let elems = |p| seq!(p => param, comma,).map(|params| Params { params });
@@ -127,47 +171,60 @@ impl<'a> Parser<'a> {
         wrap!(pos => l; elems or def; r)
     }
 
+    // the reference to the blackboard key: `{key}`
+    fn bb_ref(&self, pos: usize) -> Step<'a, Key> {
+        self.l_brc(pos)
+            .then(|p| self.id(p))
+            .then_skip(|p| self.r_brc(p))
+    }
+
     fn arg(&'a self, pos: usize) -> Step<'a, Argument> {
         let assign = |p| self.assign(p);
         let assigned = |p| self.id(p).then_skip(assign);
 
-        let assign_id = |p| {
-            assigned(p)
-                .then_zip(|p| self.id(p).map(ArgumentRhs::Id))
-                .map(|(a, b)| Argument::Assigned(a, b))
-        };
-
-        let assign_mes = |p| {
+        // the plain ids and messages are parsed as the expressions as well
+        // and get unwrapped back by `ArgumentRhs::from`
+        let assign_expr = |p| {
             assigned(p)
-                .then_zip(|p| self.message(p).map(ArgumentRhs::Mes))
-                .map(|(a, b)| Argument::Assigned(a, b))
+                .then_zip(|p| self.expr(p).map(ArgumentRhs::from))
+                .map(|(a, b)| Argument::Assigned(a, b, Span::default()))
         };
         let assign_call = |p| {
             assigned(p)
                 .then_zip(|p| self.call(p).map(ArgumentRhs::Call))
-                .map(|(a, b)| Argument::Assigned(a, b))
+                .map(|(a, b)| Argument::Assigned(a, b, Span::default()))
+        };
+        let assign_bb_ref = |p| {
+            assigned(p)
+                .then_zip(|p| self.bb_ref(p).map(ArgumentRhs::BBRef))
+                .map(|(a, b)| Argument::Assigned(a, b, Span::default()))
         };
 
-        let mes = |p| {
-            self.message(p)
-                .map(ArgumentRhs::Mes)
-                .map(Argument::Unassigned)
+        let expr = |p| {
+            self.expr(p)
+                .map(ArgumentRhs::from)
+                .map(|v| Argument::Unassigned(v, Span::default()))
         };
-        let id = |p| self.id(p).map(ArgumentRhs::Id).map(Argument::Unassigned);
         let call = |p| {
             self.call(p)
                 .map(ArgumentRhs::Call)
-                .map(Argument::Unassigned)
+                .map(|v| Argument::Unassigned(v, Span::default()))
+        };
+        let bb_ref = |p| {
+            self.bb_ref(p)
+                .map(ArgumentRhs::BBRef)
+                .map(|v| Argument::Unassigned(v, Span::default()))
         };
 
-        assign_mes(pos)
+        let arg = assign_call(pos)
             .or_from(pos)
-            .or(assign_call)
-            .or(assign_id)
+            .or(assign_bb_ref)
+            .or(assign_expr)
             .or(call)
-            .or(mes)
-            .or(id)
-            .into()
+            .or(bb_ref)
+            .or(expr)
+            .into();
+        self.spanned(pos, arg).map(|(a, span)| a.with_span(span))
     }
 
     fn args(&'a self, pos: usize) -> Step<'a, Arguments> {
@@ -188,17 +245,46 @@ impl<'a> Parser<'a> {
             .flat_map(|p| TreeType::from_str(&p), |pe| Step::Fail(pos))
     }
 
-    fn mes_type(&self, pos: usize) -> Step<'a, MesType> {
-        token!(self.token(pos) =>
-                Token::StringT => MesType::String ,
-                Token::NumT => MesType::Num,
-                Token::ArrayT => MesType::Array,
-                Token::BoolT => MesType::Bool,
-                Token::TreeT => MesType::Tree,
-                Token::ObjectT => MesType::Object
+    fn custom_decorator(&self, pos: usize) -> Step<'a, Key> {
+        self.id(pos).flat_map(
+            |id| {
+                if self.decorators.contains(&id) {
+                    Ok(id)
+                } else {
+                    Err(id)
+                }
+            },
+            |_| Step::Fail(pos),
         )
     }
 
+    fn mes_type(&'a self, pos: usize) -> Step<'a, MesType> {
+        let elem = |p| {
+            self.l_angle(p)
+                .then(|p| self.mes_type(p))
+                .then_skip(|p| self.r_angle(p))
+                .or_none()
+        };
+        token!(self.token(pos) => Token::ArrayT)
+            .then_or_none_zip(elem)
+            .map(|(_, elem)| match elem {
+                Some(tpe) => MesType::array_of(tpe),
+                None => MesType::Array,
+            })
+            .or_from(pos)
+            .or(|p| {
+                token!(self.token(p) =>
+                        Token::StringT => MesType::String ,
+                        Token::NumT => MesType::Num,
+                        Token::BoolT => MesType::Bool,
+                        Token::TreeT => MesType::Tree,
+                        Token::ObjectT => MesType::Object
+                )
+            })
+            .or(|p| self.id(p).map(MesType::Enum))
+            .into()
+    }
+
     fn message(&'a self, pos: usize) -> Step<'a, Message> {
         self.str(pos)
             .map(Message::String)
@@ -207,6 +293,122 @@ impl<'a> Parser<'a> {
             .or(|p| self.bool(p).map(Message::Bool))
             .or(|p| self.array(p).map(Message::Array))
             .or(|p| self.object(p).map(Message::Object))
+            .or(|p| self.enum_value(p))
+            .into()
+    }
+
+    // the variant of the enum: `Zone::Kitchen`
+    fn enum_value(&self, pos: usize) -> Step<'a, Message> {
+        self.id(pos)
+            .then_skip(|p| token!(self.token(p) => Token::ColonColon))
+            .then_zip(|p| self.id(p))
+            .map(|(tpe, variant)| Message::Enum(tpe, variant))
+    }
+
+    fn cmp_op(&self, pos: usize) -> Step<'a, BinOp> {
+        token!(self.token(pos) =>
+                Token::Eq => BinOp::Eq,
+                Token::NotEq => BinOp::Ne,
+                Token::Gt => BinOp::Gt,
+                Token::Ge => BinOp::Ge,
+                Token::Lt => BinOp::Lt,
+                Token::Le => BinOp::Le
+        )
+    }
+    fn and_op(&self, pos: usize) -> Step<'a, BinOp> {
+        token!(self.token(pos) => Token::And => BinOp::And )
+    }
+    fn or_op(&self, pos: usize) -> Step<'a, BinOp> {
+        token!(self.token(pos) => Token::Or => BinOp::Or )
+    }
+    fn not(&self, pos: usize) -> Step<'a, EmptyToken> {
+        token!(self.token(pos) => Token::Not )
+    }
+    fn l_angle(&self, pos: usize) -> Step<'a, EmptyToken> {
+        token!(self.token(pos) => Token::Lt )
+    }
+    fn r_angle(&self, pos: usize) -> Step<'a, EmptyToken> {
+        token!(self.token(pos) => Token::Gt )
+    }
+    fn question(&self, pos: usize) -> Step<'a, EmptyToken> {
+        token!(self.token(pos) => Token::Question )
+    }
+    // the `const` and the `enum` are the keywords only at the beginning of the entity in the file
+    // followed by the name, thus they can be the names of the trees, the parameters etc.
+    fn entity_keyword(&self, pos: usize, keyword: &str) -> Step<'a, EmptyToken> {
+        match self.inner.token(pos + 1) {
+            Ok((Token::Id(_), _)) => token!(self.token(pos) => Token::Id(w) if w == keyword),
+            _ => Step::Fail(pos),
+        }
+    }
+
+    // the `out` is a keyword only before the name of the parameter, thus `out:num` is a parameter named out
+    fn out(&self, pos: usize) -> Step<'a, EmptyToken> {
+        match self.inner.token(pos + 1) {
+            Ok((Token::Id(_), _)) => token!(self.token(pos) => Token::Id(w) if w == "out"),
+            _ => Step::Fail(pos),
+        }
+    }
+    fn minus(&self, pos: usize) -> Step<'a, EmptyToken> {
+        token!(self.token(pos) => Token::Minus )
+    }
+    fn add_op(&self, pos: usize) -> Step<'a, BinOp> {
+        token!(self.token(pos) =>
+                Token::Plus => BinOp::Add,
+                Token::Minus => BinOp::Sub
+        )
+    }
+    fn mul_op(&self, pos: usize) -> Step<'a, BinOp> {
+        token!(self.token(pos) =>
+                Token::Star => BinOp::Mul,
+                Token::Slash => BinOp::Div,
+                Token::Percent => BinOp::Mod
+        )
+    }
+
+    // the precedence from the lowest: ||, &&, comparison, (+ -), (* / %), (! -)
+    fn expr(&'a self, pos: usize) -> Step<'a, Expr> {
+        self.and_expr(pos)
+            .then_multi_zip(|p| self.or_op(p).then_zip(|p| self.and_expr(p)))
+            .map(fold_binary)
+    }
+    fn and_expr(&'a self, pos: usize) -> Step<'a, Expr> {
+        self.cmp_expr(pos)
+            .then_multi_zip(|p| self.and_op(p).then_zip(|p| self.cmp_expr(p)))
+            .map(fold_binary)
+    }
+    fn cmp_expr(&'a self, pos: usize) -> Step<'a, Expr> {
+        self.add_expr(pos)
+            .then_or_none_zip(|p| self.cmp_op(p).then_zip(|p| self.add_expr(p)).or_none())
+            .map(|(lhs, rhs)| match rhs {
+                Some((op, rhs)) => Expr::binary(lhs, op, rhs),
+                None => lhs,
+            })
+    }
+    fn add_expr(&'a self, pos: usize) -> Step<'a, Expr> {
+        self.mul_expr(pos)
+            .then_multi_zip(|p| self.add_op(p).then_zip(|p| self.mul_expr(p)))
+            .map(fold_binary)
+    }
+    fn mul_expr(&'a self, pos: usize) -> Step<'a, Expr> {
+        self.unary_expr(pos)
+            .then_multi_zip(|p| self.mul_op(p).then_zip(|p| self.unary_expr(p)))
+            .map(fold_binary)
+    }
+    fn unary_expr(&'a self, pos: usize) -> Step<'a, Expr> {
+        let group = |p| {
+            self.l_pr(p)
+                .then(|p| self.expr(p))
+                .then_skip(|p| self.r_pr(p))
+        };
+        self.not(pos)
+            .then(|p| self.unary_expr(p))
+            .map(Expr::not)
+            .or_from(pos)
+            .or(group)
+            .or(|p| self.message(p).map(Expr::Mes))
+            .or(|p| self.minus(p).then(|p| self.unary_expr(p)).map(Expr::neg))
+            .or(|p| self.id(p).map(Expr::Id))
             .into()
     }
 
@@ -221,9 +423,12 @@ impl<'a> Parser<'a> {
         let inv = |p| {
             self.id(p)
                 .then_zip(|p| self.args(p))
-                .map(|(id, args)| Call::Invocation(id, args))
+                .map(|(id, args)| Call::invocation(id.as_str(), args))
                 .or_from(p)
-                .or(|p| self.call_partial(p).map(Call::HoInvocation))
+                .or(|p| {
+                    self.call_partial(p)
+                        .map(|id| Call::ho_invocation(id.as_str()))
+                })
                 .into()
         };
 
@@ -241,33 +446,85 @@ impl<'a> Parser<'a> {
                 })
         };
 
-        anon(pos).or_from(pos).or(inv).into()
-    }
-    fn calls(&'a self, pos: usize) -> Step<'a, Calls> {
-        let calls = |p| {
-            let l = |p| self.l_brc(p);
-            let r = |p| self.r_brc(p);
-            let calls = |p| self.inner.zero_or_more(p, |p| self.call(p));
-            wrap!(p => l;calls;r).map(Calls::new)
+        let guard = |p| {
+            self.tree_type(p)
+                .flat_map(
+                    |t| match t {
+                        TreeType::Guard => Ok(t),
+                        _ => Err(t),
+                    },
+                    |_| Step::Fail(p),
+                )
+                .then_skip(|p| self.l_pr(p))
+                .then_zip(|p| self.expr(p))
+                .then_skip(|p| self.r_pr(p))
+                .map(|(t, e)| (t, Arguments::new(vec![Argument::expr(e)])))
+                .then_zip(|p| self.calls(p))
+                .validate(|((t, args), calls)| validate_lambda(t, args, calls))
+                .map(|((t, args), calls)| Call::decorator(t, args, calls.elems[0].clone()))
         };
 
-        calls(pos)
+        let custom = |p| {
+            self.custom_decorator(p)
+                .then_or_default_zip(|p| self.args(p))
+                .then_zip(|p| self.calls(p))
+                .validate(|(_, calls)| validate_custom_decorator(calls))
+                .map(|((name, args), calls)| {
+                    Call::custom_decorator(name.as_str(), args, calls.elems[0].clone())
+                })
+        };
+
+        let call = guard(pos).or_from(pos).or(anon).or(custom).or(inv).into();
+        self.spanned(pos, call).map(|(c, span)| c.with_span(span))
+    }
+    fn calls(&'a self, pos: usize) -> Step<'a, Calls> {
+        self.block(pos)
             .or_from(pos)
             .or(|p| self.call(p).map(|c| Calls { elems: vec![c] }))
             .into()
     }
 
     fn tree(&'a self, pos: usize) -> Step<'a, Tree> {
-        self.tree_type(pos)
+        let tree = self
+            .tree_type(pos)
             .then_zip(|p| self.id(p))
             .then_or_default_zip(|p| self.params(p))
             .then_or_default_zip(|p| self.semi(p).map(|_| Calls::default()).or(|p| self.calls(p)))
-            .map(|(((tpe, name), params), calls)| Tree {
-                tpe,
+            .map(|(((tpe, name), params), calls)| Tree::new(tpe, name, params, calls));
+        self.spanned(pos, tree)
+            .map(|(tree, span)| Tree { span, ..tree })
+    }
+
+    // the constant: `const speed = 10;`
+    fn const_def(&'a self, pos: usize) -> Step<'a, Const> {
+        let def = self
+            .entity_keyword(pos, "const")
+            .then(|p| self.id(p))
+            .then_skip(|p| self.assign(p))
+            .then_zip(|p| self.message(p))
+            .then_skip(|p| self.semi(p))
+            .map(|(name, value)| Const::new(name.as_str(), value));
+        self.spanned(pos, def).map(|(c, span)| Const { span, ..c })
+    }
+
+    // the enum: `enum Zone { Kitchen, Hall }`
+    fn enum_def(&'a self, pos: usize) -> Step<'a, Enum> {
+        let l = |p| self.l_brc(p);
+        let r = |p| self.r_brc(p);
+        let comma = |p| self.comma(p);
+        let id = |p| self.id(p);
+        let variants = |p| seq!(p => id, comma,);
+
+        let def = self
+            .entity_keyword(pos, "enum")
+            .then(|p| self.id(p))
+            .then_zip(|p| wrap!(p => l; variants; r))
+            .map(|(name, variants)| Enum {
                 name,
-                params,
-                calls,
-            })
+                variants,
+                span: Span::default(),
+            });
+        self.spanned(pos, def).map(|(e, span)| Enum { span, ..e })
     }
 
     fn import(&'a self, pos: usize) -> Step<'a, Import> {
@@ -289,16 +546,46 @@ impl<'a> Parser<'a> {
             wrap!(p => l;names or def; r ).or_none()
         };
 
-        self.import_tk(pos)
+        let import = self
+            .import_tk(pos)
             .then_zip(|p| self.str(p))
             .take_right()
             .then_or_none_zip(part)
             .map(|(file, parts)| match parts {
                 None => Import::file(file.0.as_str()),
-                Some(names) => Import(file.0, names),
-            })
+                Some(names) => Import::names_mixed(file.0.as_str(), names),
+            });
+        self.spanned(pos, import)
+            .map(|(Import(file, names, _), span)| Import(file, names, span))
+    }
+
+    // the calls in the braces: `{ call() call() }`.
+    // When a call can not be parsed, the error is recorded
+    // and the rest of the block up to the closing brace is skipped.
+    fn block(&'a self, pos: usize) -> Step<'a, Calls> {
+        self.l_brc(pos).then(|mut p| {
+            let mut elems = vec![];
+            loop {
+                match self.call(p) {
+                    Step::Success(call, next) => {
+                        elems.push(call);
+                        p = next;
+                    }
+                    Step::Fail(_) if matches!(self.token(p), Ok((Token::RBrace, _))) => break,
+                    failed => {
+                        self.recover(p, failed.error());
+                        p = self.skip(p, false);
+                        break;
+                    }
+                }
+            }
+            self.r_brc(p).map(|_| Calls::new(elems))
+        })
     }
 
+    // the definitions and imports of the file.
+    // When an entity can not be parsed, the error is recorded
+    // and the parser goes on from the end of the statement (`;` or `}`) or the next definition.
     fn file(&'a self, pos: usize) -> Step<'a, AstFile> {
         let entity = |p| {
             let entity: Step<FileEntity> = self
@@ -306,26 +593,285 @@ impl<'a> Parser<'a> {
                 .map(FileEntity::Tree)
                 .or_from(p)
                 .or(|p| self.import(p).map(FileEntity::Import))
+                .or(|p| self.const_def(p).map(FileEntity::Const))
+                .or(|p| self.enum_def(p).map(FileEntity::Enum))
                 .into();
             entity
         };
 
-        self.inner.zero_or_more(pos, entity).map(AstFile::new)
+        let mut entities = vec![];
+        let mut p = pos;
+        while p < self.spans.len() {
+            match entity(p) {
+                Step::Success(e, next) => {
+                    entities.push(e);
+                    p = next;
+                }
+                failed => {
+                    self.recover(p, failed.error());
+                    p = self.skip(p, true);
+                }
+            }
+        }
+        Step::Success(AstFile::new(entities), p)
+    }
+
+    /// records the error of the parser.
+    /// The failure without an error is reported at the furthest token the parser has looked at.
+    fn recover(&self, pos: usize, error: Option<ParseError<'a>>) {
+        let error = error.unwrap_or_else(|| {
+            let last = self.furthest.get().max(pos);
+            match self.spans.get(last) {
+                Some(span) => ParseError::ExternalError(
+                    format!("the token {} is unexpected", &self.src[span.clone()]),
+                    last,
+                ),
+                None => ParseError::ReachedEOF(last),
+            }
+        });
+        let mut errors = self.errors.borrow_mut();
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
+    }
+
+    /// skips the tokens of the statement that can not be parsed.
+    /// The statement ends either with `;` or with the closing brace,
+    /// or, on the top level, before the next definition or import.
+    fn skip(&self, pos: usize, top_level: bool) -> usize {
+        let mut depth = 0;
+        let mut p = pos;
+        while let Ok((token, _)) = self.token(p) {
+            match token {
+                Token::LBrace => depth += 1,
+                Token::RBrace if depth == 0 => break,
+                Token::RBrace if depth == 1 && top_level => {
+                    p += 1;
+                    break;
+                }
+                Token::RBrace => depth -= 1,
+                Token::Semi if depth == 0 && top_level => {
+                    p += 1;
+                    break;
+                }
+                _ if depth == 0 && top_level && p > pos && self.starts_entity(p) => break,
+                _ => {}
+            }
+            p += 1;
+        }
+        // the unexpected closing brace on the top level is skipped as well
+        if top_level && p == pos {
+            p += 1;
+        }
+        self.furthest.set(p);
+        p
+    }
+
+    /// the import, the constant, the enum or the beginning of a definition `type name`
+    fn starts_entity(&self, pos: usize) -> bool {
+        match (self.token(pos), self.token(pos + 1)) {
+            (Ok((Token::Import, _)), _) => true,
+            (Ok((Token::Id(tpe), _)), Ok((Token::Id(_), _))) => {
+                TreeType::from_str(tpe).is_ok() || tpe == "const" || tpe == "enum"
+            }
+            _ => false,
+        }
     }
 }
 
 impl<'a> Parser<'a> {
     pub fn new(src: &'a str) -> Result<Self, TreeError> {
+        Ok(Parser::lex(src, &HashSet::new())?)
+    }
+
+    fn lex(src: &'a str, imported: &HashSet<Key>) -> Result<Self, ParseError<'a>> {
+        let inner = Parsit::new(src)?;
+        let mut decorators = find_decorators(&inner);
+        decorators.extend(imported.iter().cloned());
+        let spans = Token::lexer(src).spanned().map(|(_, span)| span).collect();
         Ok(Parser {
-            inner: Parsit::new(src)?,
+            inner,
+            src,
+            spans,
+            furthest: Cell::new(0),
+            errors: RefCell::new(vec![]),
+            decorators,
         })
     }
 
+    /// the position in the source of the tokens from `start` up to `end` exclusively.
+    fn span(&self, start: usize, end: usize) -> Span {
+        let eof = self.src.len();
+        let from = self.spans.get(start).map(|r| r.start).unwrap_or(eof);
+        let to = end
+            .checked_sub(1)
+            .filter(|last| *last >= start)
+            .and_then(|last| self.spans.get(last))
+            .map(|r| r.end)
+            .unwrap_or(from);
+        Span::new(from, to)
+    }
+
+    /// attaches the position in the source to the parsed node
+    fn spanned<T>(&self, pos: usize, step: Step<'a, T>) -> Step<'a, (T, Span)> {
+        match step {
+            Step::Success(v, end) => Step::Success((v, self.span(pos, end)), end),
+            Step::Fail(p) => Step::Fail(p),
+            Step::Error(e) => Step::Error(e),
+        }
+    }
+
+    /// turns the error of the parser into the diagnostic pointing to the place in the file
+    fn diagnostic(&self, file: &str, e: ParseError) -> Diagnostic {
+        let (span, msg) = match e {
+            ParseError::BadToken(t, range) => return unknown_token(file, self.src, t, range),
+            ParseError::FailedOnValidation(msg, pos) => (self.span(pos, pos + 1), msg.to_string()),
+            ParseError::ExternalError(msg, pos) => (self.span(pos, pos + 1), msg),
+            ParseError::ReachedEOF(pos) => (
+                self.span(pos, pos + 1),
+                "the file ends unexpectedly".to_string(),
+            ),
+            ParseError::UnreachedEOF(pos) => (
+                self.span(pos, pos + 1),
+                "the definition can not be parsed".to_string(),
+            ),
+            ParseError::FinishedOnFail => {
+                (self.span(0, 1), "the file can not be parsed".to_string())
+            }
+        };
+        Diagnostic::new(file, self.src, span, msg)
+    }
+
     fn token(&self, pos: usize) -> Result<(&Token, usize), ParseError<'a>> {
+        if pos > self.furthest.get() {
+            self.furthest.set(pos);
+        }
         self.inner.token(pos)
     }
 
-    pub fn parse(&'a self) -> Result<AstFile, ParseError<'a>> {
-        self.inner.validate_eof(self.file(0)).into()
+    /// Parses the whole file. The parser recovers from the errors and goes on,
+    /// thus all the errors in the file are returned at once.
+    pub fn parse(&'a self) -> Result<AstFile, Vec<ParseError<'a>>> {
+        match self.parse_recovering() {
+            (file, errors) if errors.is_empty() => Ok(file),
+            (_, errors) => Err(errors),
+        }
+    }
+
+    /// the entities that are parsed successfully and the errors the parser has recovered from
+    fn parse_recovering(&'a self) -> (AstFile, Vec<ParseError<'a>>) {
+        let file = match self.file(0) {
+            Step::Success(file, _) => file,
+            // the file skips everything that can not be parsed, thus it does not fail
+            _ => AstFile::default(),
+        };
+        (file, self.errors.take())
+    }
+}
+
+fn fold_binary((head, tail): (Expr, Vec<(BinOp, Expr)>)) -> Expr {
+    tail.into_iter()
+        .fold(head, |lhs, (op, rhs)| Expr::binary(lhs, op, rhs))
+}
+
+/// The user-defined decorators are parsed like the built-in ones, thus they need to be known in advance.
+/// The function looks through the tokens for the declarations `decorator name`.
+fn find_decorators(inner: &Parsit<Token>) -> HashSet<Key> {
+    let mut decorators = HashSet::new();
+    let mut pos = 0;
+    while let Ok((token, _)) = inner.token(pos) {
+        if let (Token::Id(tpe), Ok((Token::Id(name), _))) = (token, inner.token(pos + 1)) {
+            if TreeType::from_str(tpe).map(|t| t.is_custom_decorator()) == Ok(true) {
+                decorators.insert(name.clone());
+            }
+        }
+        pos += 1;
+    }
+    decorators
+}
+
+/// Parses the text of the file recovering from the errors.
+/// Returns the entities that are parsed and the errors pointing to the places in the source.
+pub fn parse_file(file: &str, src: &str) -> (AstFile, Vec<Diagnostic>) {
+    parse_file_with(file, src, &HashSet::new())
+}
+
+/// Parses the text of the file knowing the decorators imported from the other files,
+/// since they are parsed like the ones declared in the file.
+pub fn parse_file_with(
+    file: &str,
+    src: &str,
+    decorators: &HashSet<Key>,
+) -> (AstFile, Vec<Diagnostic>) {
+    let parser = match Parser::lex(src, decorators) {
+        Ok(parser) => parser,
+        // the parser can not start with unknown tokens, so all of them are reported
+        Err(_) => {
+            let errors = Token::lexer(src)
+                .spanned()
+                .filter(|(t, _)| t.is_err())
+                .map(|(_, span)| unknown_token(file, src, &src[span.clone()], span))
+                .collect();
+            return (AstFile::default(), errors);
+        }
+    };
+    let (ast, errors) = parser.parse_recovering();
+    let errors = errors
+        .into_iter()
+        .map(|e| parser.diagnostic(file, e))
+        .collect();
+    (ast, errors)
+}
+
+/// The comment skipped by the parser: the text with the delimiters and the position in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub text: String,
+    pub span: Span,
+}
+
+/// Finds the comments in the source in the order they are written.
+pub fn comments(src: &str) -> Vec<Comment> {
+    Trivia::lexer(src)
+        .spanned()
+        .filter(|(t, _)| *t == Ok(Trivia::Comment))
+        .map(|(_, span)| Comment {
+            text: src[span.clone()].trim_end().to_string(),
+            span: span.into(),
+        })
+        .collect()
+}
+
+/// The word the lexer takes for a keyword, e.g. `true` or `import`, thus it can not be a name.
+pub(crate) fn is_keyword(word: &str) -> bool {
+    let mut tokens = Token::lexer(word);
+    match tokens.next() {
+        Some(Ok(Token::Id(_))) => false,
+        // the numbers and the punctuation are not the words
+        Some(Ok(_)) if word.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+            tokens.span() == (0..word.len()) && tokens.next().is_none()
+        }
+        _ => false,
+    }
+}
+
+/// Parses the standalone expression, e.g. `battery > 20 && mode == "auto"`.
+pub(crate) fn parse_expr(src: &str) -> Result<Expr, TreeError> {
+    let parser = Parser::lex(src, &HashSet::new())
+        .map_err(|_| TreeError::ParseError(format!("the expression {src} can not be parsed")))?;
+    match parser.expr(0) {
+        Step::Success(e, end) if end == parser.spans.len() => Ok(e),
+        _ => Err(TreeError::ParseError(format!(
+            "the expression {src} can not be parsed"
+        ))),
     }
 }
+
+fn unknown_token(file: &str, src: &str, token: &str, range: Range<usize>) -> Diagnostic {
+    Diagnostic::new(
+        file,
+        src,
+        range.into(),
+        format!("the token {token} is unknown"),
+    )
+}
@@ -1,9 +1,11 @@
 pub mod arg;
 pub mod call;
+pub mod expr;
 pub mod invocation;
 pub mod message;
 use crate::runtime::rtree::rnode::Name;
 use crate::runtime::RuntimeError;
+use crate::tree::diagnostic::Span;
 use crate::tree::parser::ast::invocation::Invocation;
 use crate::tree::project::{AliasName, TreeName};
 use crate::tree::TreeError;
@@ -41,9 +43,18 @@ pub enum TreeType {
     Retry,
     Timeout,
     Delay,
+    RetryBackoff,
+    Cooldown,
+    RateLimit,
+    Guard,
+    RunOnce,
+    KeepRunningUntilFailure,
+    KeepRunningUntilSuccess,
     // actions
     Impl,
     Cond,
+    // declaration of the user-defined decorators
+    Decorator,
 }
 
 impl TreeType {
@@ -55,7 +66,14 @@ impl TreeType {
             | TreeType::Repeat
             | TreeType::Retry
             | TreeType::Delay
-            | TreeType::Timeout => true,
+            | TreeType::Timeout
+            | TreeType::RetryBackoff
+            | TreeType::Cooldown
+            | TreeType::RateLimit
+            | TreeType::Guard
+            | TreeType::RunOnce
+            | TreeType::KeepRunningUntilFailure
+            | TreeType::KeepRunningUntilSuccess => true,
             _ => false,
         }
     }
@@ -65,6 +83,12 @@ impl TreeType {
             _ => false,
         }
     }
+    pub fn is_custom_decorator(&self) -> bool {
+        match self {
+            TreeType::Decorator => true,
+            _ => false,
+        }
+    }
 }
 
 pub fn validate_lambda<'a, 'b>(
@@ -76,6 +100,9 @@ pub fn validate_lambda<'a, 'b>(
         TreeType::Impl | TreeType::Cond => {
             Err("the types impl or cond should have declaration and get called by name")
         }
+        TreeType::Decorator => {
+            Err("the type decorator should have declaration and get called by name")
+        }
 
         _ if tpe.is_decorator() => {
             if calls.elems.len() != 1 {
@@ -95,12 +122,22 @@ pub fn validate_lambda<'a, 'b>(
     }
 }
 
+pub fn validate_custom_decorator<'b>(calls: &Calls) -> Result<(), &'b str> {
+    if calls.elems.len() != 1 {
+        Err("any decorator should have only one child")
+    } else {
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Tree {
     pub tpe: TreeType,
     pub name: Key,
     pub params: Params,
     pub calls: Calls,
+    /// the position of the definition in the source file
+    pub span: Span,
 }
 
 impl Tree {
@@ -127,6 +164,15 @@ impl Tree {
             name,
             params,
             calls,
+            span: Span::default(),
+        }
+    }
+    /// the same definition with the positions of it and the calls dropped
+    pub fn without_spans(self) -> Self {
+        Tree {
+            calls: self.calls.without_spans(),
+            span: Span::default(),
+            ..self
         }
     }
     pub fn to_inv(&self) -> Invocation {
@@ -137,8 +183,9 @@ impl Tree {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Import(pub String, pub Vec<ImportName>);
+/// The import of the file or the names from it, the span points to the import statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import(pub String, pub Vec<ImportName>, pub Span);
 
 #[derive(Clone, Debug, PartialEq, Hash, Eq)]
 pub enum ImportName {
@@ -159,20 +206,63 @@ impl ImportName {
 impl Import {
     pub fn f_name(&self) -> &str {
         match self {
-            Import(n, _) => n,
+            Import(n, _, _) => n,
         }
     }
     pub fn file(f: &str) -> Self {
-        Import(f.to_string(), vec![ImportName::WholeFile])
+        Import(f.to_string(), vec![ImportName::WholeFile], Span::default())
     }
     pub fn names(f: &str, names: Vec<&str>) -> Self {
         Import(
             f.to_string(),
             names.into_iter().map(|v| ImportName::id(v)).collect(),
+            Span::default(),
         )
     }
     pub fn names_mixed(f: &str, names: Vec<ImportName>) -> Self {
-        Import(f.to_string(), names)
+        Import(f.to_string(), names, Span::default())
+    }
+    pub fn without_spans(self) -> Self {
+        Import(self.0, self.1, Span::default())
+    }
+}
+
+/// The named value that can be passed as an argument: `const speed = 10;`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Const {
+    pub name: Key,
+    pub value: Message,
+    pub span: Span,
+}
+
+impl Const {
+    pub fn new(name: &str, value: Message) -> Self {
+        Const {
+            name: name.to_string(),
+            value,
+            span: Span::default(),
+        }
+    }
+}
+
+/// The set of the named variants: `enum Zone { Kitchen, Hall }`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enum {
+    pub name: Key,
+    pub variants: Vec<Key>,
+    pub span: Span,
+}
+
+impl Enum {
+    pub fn new(name: &str, variants: Vec<&str>) -> Self {
+        Enum {
+            name: name.to_string(),
+            variants: variants.into_iter().map(ToString::to_string).collect(),
+            span: Span::default(),
+        }
+    }
+    pub fn has(&self, variant: &str) -> bool {
+        self.variants.iter().any(|v| v == variant)
     }
 }
 
@@ -180,13 +270,38 @@ impl Import {
 pub enum FileEntity {
     Tree(Tree),
     Import(Import),
+    Const(Const),
+    Enum(Enum),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl FileEntity {
+    pub fn without_spans(self) -> Self {
+        match self {
+            FileEntity::Tree(t) => FileEntity::Tree(t.without_spans()),
+            FileEntity::Import(i) => FileEntity::Import(i.without_spans()),
+            FileEntity::Const(c) => FileEntity::Const(Const {
+                span: Span::default(),
+                ..c
+            }),
+            FileEntity::Enum(e) => FileEntity::Enum(Enum {
+                span: Span::default(),
+                ..e
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct AstFile(pub Vec<FileEntity>);
 
 impl<'a> AstFile {
     pub fn new(field0: Vec<FileEntity>) -> Self {
         Self(field0)
     }
+
+    /// the same file with all positions dropped,
+    /// thus the files parsed from the differently formatted sources are equal
+    pub fn without_spans(self) -> Self {
+        AstFile(self.0.into_iter().map(FileEntity::without_spans).collect())
+    }
 }
@@ -1,6 +1,8 @@
 use crate::runtime::RuntimeError;
+use crate::tree::diagnostic::Span;
 use crate::tree::parser::ast::arg::ArgumentsType::{Named, Unnamed};
 use crate::tree::parser::ast::call::Call;
+use crate::tree::parser::ast::expr::Expr;
 use crate::tree::parser::ast::message::Message;
 use crate::tree::parser::ast::Key;
 use crate::tree::{cerr, TreeError};
@@ -12,6 +14,14 @@ use std::fmt::{Display, Formatter};
 pub struct Param {
     pub name: Key,
     pub tpe: MesType,
+    /// the output parameter (`out name:type`) accepts only a blackboard reference
+    /// and the action writes the result into it.
+    pub output: bool,
+    /// the optional parameter (`name?:type`) can be omitted in the call
+    /// and then it is absent in the runtime arguments.
+    pub optional: bool,
+    /// the value (`name:type = value`) that is taken when the argument is omitted in the call.
+    pub default: Option<Message>,
 }
 
 impl Param {
@@ -19,8 +29,33 @@ impl Param {
         Param {
             name: id.to_string(),
             tpe,
+            output: false,
+            optional: false,
+            default: None,
         }
     }
+    pub fn output(id: &str, tpe: MesType) -> Self {
+        Param {
+            output: true,
+            ..Param::new(id, tpe)
+        }
+    }
+    pub fn optional(id: &str, tpe: MesType) -> Self {
+        Param {
+            optional: true,
+            ..Param::new(id, tpe)
+        }
+    }
+    pub fn with_default(id: &str, tpe: MesType, default: Message) -> Self {
+        Param {
+            default: Some(default),
+            ..Param::new(id, tpe)
+        }
+    }
+    /// the argument for the parameter should be passed in every call
+    pub fn is_required(&self) -> bool {
+        !self.optional && self.default.is_none()
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -32,6 +67,17 @@ impl Params {
     pub fn new(params: Vec<Param>) -> Self {
         Params { params }
     }
+
+    /// the parameters have the same names, types and directions in the same order.
+    /// The defaults and the optionality are not compared.
+    pub fn same_signature(&self, other: &Params) -> bool {
+        self.params.len() == other.params.len()
+            && self
+                .params
+                .iter()
+                .zip(other.params.iter())
+                .all(|(l, r)| l.name == r.name && l.tpe == r.tpe && l.output == r.output)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -39,6 +85,11 @@ pub enum ArgumentRhs {
     Id(Key),
     Mes(Message),
     Call(Call),
+    /// the explicit reference to the blackboard key, e.g. `{target}`
+    BBRef(Key),
+    /// the expression over the blackboard keys and literals,
+    /// evaluated every time the node is ticked
+    Expr(Expr),
 }
 
 impl ArgumentRhs {
@@ -48,55 +99,100 @@ impl ArgumentRhs {
             _ => None,
         }
     }
+    pub fn without_spans(self) -> Self {
+        match self {
+            ArgumentRhs::Call(call) => ArgumentRhs::Call(call.without_spans()),
+            rhs => rhs,
+        }
+    }
+}
+
+impl From<Expr> for ArgumentRhs {
+    fn from(value: Expr) -> Self {
+        match value {
+            Expr::Id(id) => ArgumentRhs::Id(id),
+            Expr::Mes(m) => ArgumentRhs::Mes(m),
+            e => ArgumentRhs::Expr(e),
+        }
+    }
 }
 
 impl Display for ArgumentRhs {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ArgumentRhs::Id(id) => f.write_str(id),
+            ArgumentRhs::BBRef(key) => write!(f, "{{{}}}", key),
             ArgumentRhs::Mes(m) => write!(f, "{}", m),
+            ArgumentRhs::Expr(e) => write!(f, "{}", e),
             ArgumentRhs::Call(c) => match c {
-                Call::Invocation(name, args) => {
+                Call::Invocation(name, args, _) => {
                     write!(f, "{}({})", name, args)
                 }
-                Call::HoInvocation(name) => {
+                Call::HoInvocation(name, _) => {
                     write!(f, "{}(..)", name)
                 }
-                Call::Lambda(tpe, _) => {
+                Call::Lambda(tpe, _, _) => {
                     write!(f, "{}...", tpe)
                 }
-                Call::Decorator(tpe, args, call) => {
+                Call::Decorator(tpe, args, _, _) => {
                     write!(f, "{}({})...", tpe, args)
                 }
+                Call::CustomDecorator(name, args, _, _) => {
+                    write!(f, "{}({})...", name, args)
+                }
             },
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// The argument of a call with its position in the source file
 pub enum Argument {
-    Assigned(Key, ArgumentRhs),
-    Unassigned(ArgumentRhs),
+    Assigned(Key, ArgumentRhs, Span),
+    Unassigned(ArgumentRhs, Span),
 }
 
 impl Argument {
     pub fn has_name(&self, key: &Key) -> bool {
         match self {
-            Argument::Assigned(k, _) if k == key => true,
+            Argument::Assigned(k, _, _) if k == key => true,
             _ => false,
         }
     }
 
     pub fn name(&self) -> Option<&Key> {
         match self {
-            Argument::Assigned(k, _) => Some(k),
-            Argument::Unassigned(_) => None,
+            Argument::Assigned(k, _, _) => Some(k),
+            Argument::Unassigned(_, _) => None,
         }
     }
 
     pub fn value(&self) -> &ArgumentRhs {
         match self {
-            Argument::Assigned(_, v) | Argument::Unassigned(v) => v,
+            Argument::Assigned(_, v, _) | Argument::Unassigned(v, _) => v,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Argument::Assigned(_, _, span) | Argument::Unassigned(_, span) => *span,
+        }
+    }
+
+    pub fn with_span(mut self, pos: Span) -> Self {
+        match &mut self {
+            Argument::Assigned(_, _, span) | Argument::Unassigned(_, span) => *span = pos,
+        }
+        self
+    }
+
+    /// the same argument with the positions of it and the nested calls dropped
+    pub fn without_spans(self) -> Self {
+        match self {
+            Argument::Assigned(k, v, _) => {
+                Argument::Assigned(k, v.without_spans(), Span::default())
+            }
+            Argument::Unassigned(v, _) => Argument::Unassigned(v.without_spans(), Span::default()),
         }
     }
 }
@@ -104,30 +200,50 @@ impl Argument {
 impl Display for Argument {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Argument::Assigned(k, rhs) => write!(f, "{}={}", k, rhs),
-            Argument::Unassigned(rhs) => write!(f, "{}", rhs),
+            Argument::Assigned(k, rhs, _) => write!(f, "{}={}", k, rhs),
+            Argument::Unassigned(rhs, _) => write!(f, "{}", rhs),
         }
     }
 }
 
 impl Argument {
     pub fn id(v: &str) -> Self {
-        Argument::Unassigned(ArgumentRhs::Id(v.to_string()))
+        Argument::Unassigned(ArgumentRhs::Id(v.to_string()), Span::default())
     }
     pub fn mes(v: Message) -> Self {
-        Argument::Unassigned(ArgumentRhs::Mes(v))
+        Argument::Unassigned(ArgumentRhs::Mes(v), Span::default())
     }
     pub fn call(v: Call) -> Self {
-        Argument::Unassigned(ArgumentRhs::Call(v))
+        Argument::Unassigned(ArgumentRhs::Call(v), Span::default())
+    }
+    pub fn expr(v: Expr) -> Self {
+        Argument::Unassigned(ArgumentRhs::Expr(v), Span::default())
+    }
+    pub fn bb_ref(v: &str) -> Self {
+        Argument::Unassigned(ArgumentRhs::BBRef(v.to_string()), Span::default())
     }
     pub fn id_id(lhs: &str, rhs: &str) -> Self {
-        Argument::Assigned(lhs.to_string(), ArgumentRhs::Id(rhs.to_string()))
+        Argument::Assigned(
+            lhs.to_string(),
+            ArgumentRhs::Id(rhs.to_string()),
+            Span::default(),
+        )
     }
     pub fn id_mes(lhs: &str, rhs: Message) -> Self {
-        Argument::Assigned(lhs.to_string(), ArgumentRhs::Mes(rhs))
+        Argument::Assigned(lhs.to_string(), ArgumentRhs::Mes(rhs), Span::default())
     }
     pub fn id_call(lhs: &str, rhs: Call) -> Self {
-        Argument::Assigned(lhs.to_string(), ArgumentRhs::Call(rhs))
+        Argument::Assigned(lhs.to_string(), ArgumentRhs::Call(rhs), Span::default())
+    }
+    pub fn id_expr(lhs: &str, rhs: Expr) -> Self {
+        Argument::Assigned(lhs.to_string(), ArgumentRhs::Expr(rhs), Span::default())
+    }
+    pub fn id_bb_ref(lhs: &str, rhs: &str) -> Self {
+        Argument::Assigned(
+            lhs.to_string(),
+            ArgumentRhs::BBRef(rhs.to_string()),
+            Span::default(),
+        )
     }
 }
 
@@ -148,10 +264,10 @@ impl Arguments {
 
         for a in &self.args {
             match (a, &curr) {
-                (Argument::Assigned(_, _), None) => curr = Some(Named),
-                (Argument::Unassigned(_), None) => curr = Some(Unnamed),
-                (Argument::Assigned(_, _), Some(Named)) => {}
-                (Argument::Unassigned(_), Some(Unnamed)) => {}
+                (Argument::Assigned(_, _, _), None) => curr = Some(Named),
+                (Argument::Unassigned(_, _), None) => curr = Some(Unnamed),
+                (Argument::Assigned(_, _, _), Some(Named)) => {}
+                (Argument::Unassigned(_, _), Some(Unnamed)) => {}
                 _ => {
                     return Err(cerr(format!(
                         "the arguments should be either named ot unnamed but not a mix"
@@ -174,14 +290,50 @@ impl<'a> Arguments {
     pub fn new(args: Vec<Argument>) -> Self {
         Self { args }
     }
+    pub fn without_spans(self) -> Self {
+        Arguments::new(self.args.into_iter().map(Argument::without_spans).collect())
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum MesType {
     Num,
     Array,
+    /// the array with the given type of the elements, e.g. `array<num>`
+    ArrayOf(Box<MesType>),
     Object,
     String,
     Bool,
     Tree,
+    /// the enum declared in the project with `enum Name { .. }`
+    Enum(Key),
+}
+
+impl MesType {
+    pub fn array_of(tpe: MesType) -> Self {
+        MesType::ArrayOf(Box::new(tpe))
+    }
+    /// the value of the type `self` can be passed to the parameter of the type `expected`
+    pub fn fits(&self, expected: &MesType) -> bool {
+        match (self, expected) {
+            (MesType::ArrayOf(_), MesType::Array) => true,
+            (MesType::ArrayOf(l), MesType::ArrayOf(r)) => l.fits(r),
+            (l, r) => l == r,
+        }
+    }
+}
+
+impl Display for MesType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MesType::Num => f.write_str("num"),
+            MesType::Array => f.write_str("array"),
+            MesType::ArrayOf(t) => write!(f, "array<{}>", t),
+            MesType::Object => f.write_str("object"),
+            MesType::String => f.write_str("string"),
+            MesType::Bool => f.write_str("bool"),
+            MesType::Tree => f.write_str("tree"),
+            MesType::Enum(name) => f.write_str(name),
+        }
+    }
 }
@@ -1,51 +1,96 @@
+use crate::tree::diagnostic::Span;
 use crate::tree::parser::ast::arg::Arguments;
 use crate::tree::parser::ast::{Key, TreeType};
 use serde::{Deserialize, Serialize};
 
+/// The invocation of a tree, every variant keeps the position of the call in the source file.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum Call {
-    Invocation(Key, Arguments),
-    HoInvocation(Key),
-    Lambda(TreeType, Calls),
-    Decorator(TreeType, Arguments, Box<Call>),
+    Invocation(Key, Arguments, Span),
+    HoInvocation(Key, Span),
+    Lambda(TreeType, Calls, Span),
+    Decorator(TreeType, Arguments, Box<Call>, Span),
+    /// The invocation of the user-defined decorator, declared with the keyword `decorator`
+    CustomDecorator(Key, Arguments, Box<Call>, Span),
 }
 
 impl Call {
     pub fn get_ho_invocation(&self) -> Option<Key> {
         match self {
-            Call::HoInvocation(k) => Some(k.clone()),
+            Call::HoInvocation(k, _) => Some(k.clone()),
             _ => None,
         }
     }
 
     pub fn key(&self) -> Option<Key> {
         match self {
-            Call::Invocation(k, _) => Some(k.clone()),
-            Call::HoInvocation(k) => Some(k.clone()),
-            Call::Lambda(_, _) => None,
-            Call::Decorator(_, _, _) => None,
+            Call::Invocation(k, _, _) => Some(k.clone()),
+            Call::HoInvocation(k, _) => Some(k.clone()),
+            Call::Lambda(_, _, _) => None,
+            Call::Decorator(_, _, _, _) => None,
+            Call::CustomDecorator(_, _, _, _) => None,
         }
     }
     pub fn arguments(&self) -> Arguments {
         match self {
-            Call::Invocation(_, args) => args.clone(),
-            Call::HoInvocation(_) => Arguments::default(),
-            Call::Lambda(_, _) => Arguments::default(),
-            Call::Decorator(_, args, _) => args.clone(),
+            Call::Invocation(_, args, _) => args.clone(),
+            Call::HoInvocation(_, _) => Arguments::default(),
+            Call::Lambda(_, _, _) => Arguments::default(),
+            Call::Decorator(_, args, _, _) => args.clone(),
+            Call::CustomDecorator(_, args, _, _) => args.clone(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Call::Invocation(_, _, span)
+            | Call::HoInvocation(_, span)
+            | Call::Lambda(_, _, span)
+            | Call::Decorator(_, _, _, span)
+            | Call::CustomDecorator(_, _, _, span) => *span,
+        }
+    }
+
+    pub fn with_span(mut self, pos: Span) -> Self {
+        match &mut self {
+            Call::Invocation(_, _, span)
+            | Call::HoInvocation(_, span)
+            | Call::Lambda(_, _, span)
+            | Call::Decorator(_, _, _, span)
+            | Call::CustomDecorator(_, _, _, span) => *span = pos,
+        }
+        self
+    }
+
+    /// the same call with the positions of it and the nested nodes dropped
+    pub fn without_spans(self) -> Self {
+        match self {
+            Call::Invocation(name, args, _) => Call::invocation(&name, args.without_spans()),
+            Call::HoInvocation(name, _) => Call::ho_invocation(&name),
+            Call::Lambda(tpe, calls, _) => Call::lambda(tpe, calls.without_spans()),
+            Call::Decorator(tpe, args, call, _) => {
+                Call::decorator(tpe, args.without_spans(), call.without_spans())
+            }
+            Call::CustomDecorator(name, args, call, _) => {
+                Call::custom_decorator(&name, args.without_spans(), call.without_spans())
+            }
         }
     }
 
     pub fn invocation(id: &str, args: Arguments) -> Self {
-        Call::Invocation(id.to_string(), args)
+        Call::Invocation(id.to_string(), args, Span::default())
     }
     pub fn ho_invocation(id: &str) -> Self {
-        Call::HoInvocation(id.to_string())
+        Call::HoInvocation(id.to_string(), Span::default())
     }
     pub fn lambda(tpe: TreeType, calls: Calls) -> Self {
-        Call::Lambda(tpe, calls)
+        Call::Lambda(tpe, calls, Span::default())
     }
     pub fn decorator(tpe: TreeType, args: Arguments, call: Call) -> Self {
-        Call::Decorator(tpe, args, Box::new(call))
+        Call::Decorator(tpe, args, Box::new(call), Span::default())
+    }
+    pub fn custom_decorator(name: &str, args: Arguments, call: Call) -> Self {
+        Call::CustomDecorator(name.to_string(), args, Box::new(call), Span::default())
     }
 }
 
@@ -58,4 +103,7 @@ impl Calls {
     pub fn new(elems: Vec<Call>) -> Self {
         Calls { elems }
     }
+    pub fn without_spans(self) -> Self {
+        Calls::new(self.elems.into_iter().map(Call::without_spans).collect())
+    }
 }
@@ -0,0 +1,114 @@
+use crate::tree::parser::ast::arg::MesType;
+use crate::tree::parser::ast::message::Message;
+use crate::tree::parser::ast::Key;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// The expression over the blackboard keys and literals,
+/// e.g. `battery > 20 && mode == "auto"` or `limit + 1`.
+/// The identifiers denote the keys in the blackboard and get resolved at tick time.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Expr {
+    Id(Key),
+    Mes(Message),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BinOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+impl Expr {
+    pub fn id(v: &str) -> Self {
+        Expr::Id(v.to_string())
+    }
+    pub fn mes(v: Message) -> Self {
+        Expr::Mes(v)
+    }
+    pub fn not(v: Expr) -> Self {
+        Expr::Not(Box::new(v))
+    }
+    pub fn neg(v: Expr) -> Self {
+        Expr::Neg(Box::new(v))
+    }
+    pub fn binary(lhs: Expr, op: BinOp, rhs: Expr) -> Self {
+        Expr::Binary(Box::new(lhs), op, Box::new(rhs))
+    }
+
+    /// the type of the result if it is known before the keys are resolved.
+    /// The `+` gives a string if any side is a string, the enums turn into the names of the variants.
+    pub fn tpe(&self) -> Option<MesType> {
+        match self {
+            Expr::Id(_) => None,
+            Expr::Mes(Message::Enum(..)) => Some(MesType::String),
+            Expr::Mes(m) => Some(m.tpe()),
+            Expr::Not(_) => Some(MesType::Bool),
+            Expr::Neg(_) => Some(MesType::Num),
+            Expr::Binary(lhs, BinOp::Add, rhs) => match (lhs.tpe(), rhs.tpe()) {
+                (Some(MesType::String), _) | (_, Some(MesType::String)) => Some(MesType::String),
+                (Some(MesType::Num), Some(MesType::Num)) => Some(MesType::Num),
+                _ => None,
+            },
+            Expr::Binary(_, op, _) if op.is_arithmetic() => Some(MesType::Num),
+            Expr::Binary(..) => Some(MesType::Bool),
+        }
+    }
+}
+
+impl BinOp {
+    pub fn is_arithmetic(&self) -> bool {
+        match self {
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod => true,
+            _ => false,
+        }
+    }
+}
+
+impl Display for BinOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            BinOp::Or => "||",
+            BinOp::And => "&&",
+            BinOp::Eq => "==",
+            BinOp::Ne => "!=",
+            BinOp::Gt => ">",
+            BinOp::Ge => ">=",
+            BinOp::Lt => "<",
+            BinOp::Le => "<=",
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Mod => "%",
+        };
+        f.write_str(op)
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Id(id) => f.write_str(id),
+            Expr::Mes(Message::String(s)) => write!(f, "\"{}\"", s.0),
+            Expr::Mes(m) => write!(f, "{}", m),
+            Expr::Not(e) => write!(f, "!{}", e),
+            Expr::Neg(e) => write!(f, "-{}", e),
+            Expr::Binary(lhs, op, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
+        }
+    }
+}
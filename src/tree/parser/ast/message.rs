@@ -1,4 +1,5 @@
 use crate::tree::parser::ast::arg::MesType;
+use crate::tree::parser::ast::Key;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
@@ -36,6 +37,8 @@ pub enum Message {
     Bool(Bool),
     Array(Vec<Message>),
     Object(HashMap<String, Message>),
+    /// the variant of the enum declared with `enum`, e.g. `Zone::Kitchen`
+    Enum(Key, Key),
 }
 
 impl Display for Message {
@@ -64,6 +67,7 @@ impl Display for Message {
                 map.entries(entries);
                 map.finish()
             }
+            Message::Enum(tpe, variant) => write!(f, "{}::{}", tpe, variant),
         }
     }
 }
@@ -75,11 +79,24 @@ impl Message {
             (Message::String(_), MesType::String) => true,
             (Message::Bool(_), MesType::Bool) => true,
             (Message::Array(_), MesType::Array) => true,
+            (Message::Array(elems), MesType::ArrayOf(t)) => elems.iter().all(|e| e.same(t)),
             (Message::Object(_), MesType::Object) => true,
+            (Message::Enum(tpe, _), MesType::Enum(expected)) => tpe == expected,
             _ => false,
         }
     }
 
+    pub fn tpe(&self) -> MesType {
+        match self {
+            Message::Num(_) => MesType::Num,
+            Message::String(_) => MesType::String,
+            Message::Bool(_) => MesType::Bool,
+            Message::Array(_) => MesType::Array,
+            Message::Object(_) => MesType::Object,
+            Message::Enum(tpe, _) => MesType::Enum(tpe.clone()),
+        }
+    }
+
     pub fn str(v: &str) -> Self {
         Message::String(StringLit(v.to_string()))
     }
@@ -102,4 +119,7 @@ impl Message {
     pub fn array(elems: Vec<Message>) -> Self {
         Message::Array(elems)
     }
+    pub fn enum_value(tpe: &str, variant: &str) -> Self {
+        Message::Enum(tpe.to_string(), variant.to_string())
+    }
 }
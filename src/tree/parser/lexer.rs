@@ -1,6 +1,6 @@
 use crate::tree::parser::ast::message::Number;
-use logos::Lexer;
 use logos::Logos;
+use logos::{FilterResult, Lexer};
 
 #[derive(Logos, Debug, Clone, PartialEq)]
 #[logos(subpattern digit = r"[0-9]([0-9_]*[0-9])?")]
@@ -13,11 +13,13 @@ pub enum Token {
     #[regex(r#""(?:[^"\\]|\\.)*""#, parse_qt_lit)]
     StringLit(String),
 
-    #[regex(r"-?(?&digit)", number)]
-    #[regex(r"-?(?&digit)(?&exp)", number)]
-    #[regex(r"-?(?&digit)?\.(?&digit)(?&exp)?[fFdD]?", float)]
+    // the minus is a separate token, so `limit-1` is a subtraction (see `Parser::num`)
+    #[regex(r"(?&digit)", number)]
+    #[regex(r"(?&digit)(ms|s|m|h)", duration)]
+    #[regex(r"(?&digit)(?&exp)", number)]
+    #[regex(r"(?&digit)?\.(?&digit)(?&exp)?[fFdD]?", float)]
     #[regex(r"0[bB][01][01]*", binary)]
-    #[regex(r"-?0x[0-9a-f](([0-9a-f]|[_])*[0-9a-f])?", hex)]
+    #[regex(r"0x[0-9a-f](([0-9a-f]|[_])*[0-9a-f])?", hex)]
     Digit(Number),
 
     #[token("(")]
@@ -47,6 +49,9 @@ pub enum Token {
     #[token(":")]
     Colon,
 
+    #[token("::")]
+    ColonColon,
+
     #[token(";")]
     Semi,
 
@@ -56,6 +61,51 @@ pub enum Token {
     #[token("..")]
     DotDot,
 
+    #[token("?")]
+    Question,
+
+    #[token("==")]
+    Eq,
+
+    #[token("!=")]
+    NotEq,
+
+    #[token(">")]
+    Gt,
+
+    #[token(">=")]
+    Ge,
+
+    #[token("<")]
+    Lt,
+
+    #[token("<=")]
+    Le,
+
+    #[token("&&")]
+    And,
+
+    #[token("||")]
+    Or,
+
+    #[token("!")]
+    Not,
+
+    #[token("+")]
+    Plus,
+
+    #[token("-")]
+    Minus,
+
+    #[token("*")]
+    Star,
+
+    #[token("/")]
+    Slash,
+
+    #[token("%")]
+    Percent,
+
     #[token("false")]
     False,
 
@@ -79,7 +129,8 @@ pub enum Token {
     #[token("import")]
     Import,
 
-    #[regex(r"(?s)/\*.*\*/", logos::skip)]
+    // the block comment ends at the first */
+    #[token("/*", skip_block_comment)]
     #[regex(r"//[^\r\n]*", logos::skip)]
     Comment,
 
@@ -87,10 +138,60 @@ pub enum Token {
     Whitespace,
 }
 
+/// The comments the lexer skips. The strings are recognized as well,
+/// thus the comment-like content of them is not taken as a comment.
+#[derive(Logos, Debug, Clone, PartialEq)]
+pub enum Trivia {
+    #[regex(r#""(?:[^"\\]|\\.)*""#)]
+    StringLit,
+
+    #[token("/*", block_comment)]
+    #[regex(r"//[^\r\n]*")]
+    Comment,
+}
+
+// moves the lexer to the end of the block comment, the unclosed comment is an error
+fn block_comment<'s, T: Logos<'s, Source = str>>(lex: &mut Lexer<'s, T>) -> bool {
+    match lex.remainder().find("*/") {
+        Some(idx) => {
+            lex.bump(idx + 2);
+            true
+        }
+        None => false,
+    }
+}
+
+fn skip_block_comment(lex: &mut Lexer<Token>) -> FilterResult<(), ()> {
+    if block_comment(lex) {
+        FilterResult::Skip
+    } else {
+        FilterResult::Error(())
+    }
+}
+
 fn number(lex: &mut Lexer<Token>) -> Option<Number> {
     lex.slice().parse::<i64>().map(|r| Number::Int(r)).ok()
 }
 
+// the number with the unit suffix (ms, s, m, h) gets converted to milliseconds
+fn duration(lex: &mut Lexer<Token>) -> Option<Number> {
+    let slice = lex.slice();
+    let (value, factor) = if let Some(v) = slice.strip_suffix("ms") {
+        (v, 1)
+    } else if let Some(v) = slice.strip_suffix('s') {
+        (v, 1000)
+    } else if let Some(v) = slice.strip_suffix('m') {
+        (v, 60_000)
+    } else {
+        (slice.trim_end_matches('h'), 3_600_000)
+    };
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|v| v.checked_mul(factor))
+        .map(Number::Int)
+}
+
 fn float(lex: &mut Lexer<Token>) -> Option<Number> {
     lex.slice().parse::<f64>().map(|r| Number::Float(r)).ok()
 }
@@ -107,9 +208,22 @@ fn hex(lex: &mut Lexer<Token>) -> Option<Number> {
         .ok()
 }
 
+/// the string without the quotes, the escaped quotes and backslashes (`\"`, `\\`) are unescaped.
+/// The other backslashes are kept as they are.
 fn parse_qt_lit(lexer: &mut Lexer<Token>) -> String {
     let qt_lit: &str = lexer.slice();
-    (&qt_lit[1..qt_lit.len() - 1]).to_string()
+    let mut lit = String::new();
+    let mut chars = qt_lit[1..qt_lit.len() - 1].chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(next @ ('"' | '\\'))) => {
+                lit.push(*next);
+                chars.next();
+            }
+            (c, _) => lit.push(c),
+        }
+    }
+    lit
 }
 fn parse_id(lexer: &mut Lexer<Token>) -> String {
     let qt_lit: &str = lexer.slice();
@@ -132,10 +246,78 @@ mod tests {
         );
     }
     #[test]
+    fn duration() {
+        lt::expect::<Token>(r#"500ms"#, vec![Token::Digit(Number::Int(500))]);
+        lt::expect::<Token>(r#"2s"#, vec![Token::Digit(Number::Int(2000))]);
+        lt::expect::<Token>(r#"1m"#, vec![Token::Digit(Number::Int(60_000))]);
+        lt::expect::<Token>(r#"1h"#, vec![Token::Digit(Number::Int(3_600_000))]);
+    }
+    #[test]
+    fn operators() {
+        lt::expect::<Token>(
+            r#"a >= 1 && !b || c != "x""#,
+            vec![
+                Token::Id("a".to_string()),
+                Token::Ge,
+                Token::Digit(Number::Int(1)),
+                Token::And,
+                Token::Not,
+                Token::Id("b".to_string()),
+                Token::Or,
+                Token::Id("c".to_string()),
+                Token::NotEq,
+                Token::StringLit("x".to_string()),
+            ],
+        );
+    }
+    #[test]
+    fn arithmetic() {
+        lt::expect::<Token>(
+            r#"limit-1*2"#,
+            vec![
+                Token::Id("limit".to_string()),
+                Token::Minus,
+                Token::Digit(Number::Int(1)),
+                Token::Star,
+                Token::Digit(Number::Int(2)),
+            ],
+        );
+    }
+    #[test]
+    fn consts() {
+        lt::expect::<Token>(
+            r#"const zone = Zone::Kitchen;"#,
+            vec![
+                Token::Id("const".to_string()),
+                Token::Id("zone".to_string()),
+                Token::Assign,
+                Token::Id("Zone".to_string()),
+                Token::ColonColon,
+                Token::Id("Kitchen".to_string()),
+                Token::Semi,
+            ],
+        );
+    }
+    #[test]
     fn string() {
         lt::expect::<Token>(
             "\"C:\\projects\"",
             vec![Token::StringLit("C:\\projects".to_string())],
         );
+        lt::expect::<Token>(
+            r#""say \"hi\" \\ C:\n""#,
+            vec![Token::StringLit(r#"say "hi" \ C:\n"#.to_string())],
+        );
+    }
+    #[test]
+    fn comments() {
+        lt::expect::<Token>(
+            "/* a */ x /** b **/ y // c\n z",
+            vec![
+                Token::Id("x".to_string()),
+                Token::Id("y".to_string()),
+                Token::Id("z".to_string()),
+            ],
+        );
     }
 }
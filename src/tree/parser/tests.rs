@@ -17,13 +17,39 @@ mod params;
 mod tests {
     use crate::tree::parser::ast::arg::{Argument, Arguments};
     use crate::tree::parser::ast::call::Call;
+    use crate::tree::parser::ast::call::Calls;
     use crate::tree::parser::ast::message::{Bool, Message, Number, StringLit};
     use crate::tree::parser::ast::Key;
-    use crate::tree::parser::Parser;
-    use parsit::test::parser_test::*;
+    use crate::tree::parser::ast::{AstFile, Import, Tree};
+    use crate::tree::parser::{is_keyword, Parser};
+    use parsit::step::Step;
+    use parsit::test::parser_test;
+    use std::fmt::Debug;
     use std::fs;
     use std::path::PathBuf;
 
+    /// The nodes carrying the positions in the source.
+    pub trait WithoutSpans {
+        fn without_spans(self) -> Self;
+    }
+
+    macro_rules! without_spans {
+        ($($tpe:ty),*) => {
+            $(impl WithoutSpans for $tpe {
+                fn without_spans(self) -> Self {
+                    <$tpe>::without_spans(self)
+                }
+            })*
+        };
+    }
+
+    without_spans!(Argument, Arguments, Call, Calls, Tree, Import, AstFile);
+
+    /// Compares the parsed node with the expected one by the structure, the positions are dropped.
+    pub fn expect<T: WithoutSpans + PartialEq + Debug>(res: Step<T>, expected: T) {
+        parser_test::expect(res.map(T::without_spans), expected)
+    }
+
     pub fn load_file(path: &str) -> String {
         let mut ex = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         for next in path.split("/") {
@@ -41,4 +67,17 @@ mod tests {
         let parser = Parser::new(script.as_str()).unwrap();
         let result = parser.parse().unwrap();
     }
+
+    #[test]
+    fn keywords() {
+        for word in ["true", "false", "array", "num", "import"] {
+            assert!(is_keyword(word), "{word}");
+        }
+        // the contextual keywords are the names elsewhere
+        for word in [
+            "out", "const", "enum", "truth", "num1", "move", "_", "1", "a b", "",
+        ] {
+            assert!(!is_keyword(word), "{word}");
+        }
+    }
 }
@@ -1,9 +1,10 @@
 use crate::tree::parser::ast::arg::{Argument, Arguments};
 use crate::tree::parser::ast::call::{Call, Calls};
+use crate::tree::parser::ast::expr::{BinOp, Expr};
 use crate::tree::parser::ast::message::Message;
 use crate::tree::parser::ast::*;
+use crate::tree::parser::tests::tests::expect;
 use crate::tree::parser::Parser;
-use parsit::test::parser_test::expect;
 
 #[test]
 fn plain_arg() {
@@ -70,3 +71,70 @@ fn call_arg_part() {
         Argument::id_call("a", Call::ho_invocation("x")),
     );
 }
+
+#[test]
+fn expr_arg() {
+    let parser = Parser::new(r#"expected = limit + 1 * 2"#).unwrap();
+    expect(
+        parser.arg(0),
+        Argument::id_expr(
+            "expected",
+            Expr::binary(
+                Expr::id("limit"),
+                BinOp::Add,
+                Expr::binary(
+                    Expr::mes(Message::int(1)),
+                    BinOp::Mul,
+                    Expr::mes(Message::int(2)),
+                ),
+            ),
+        ),
+    );
+
+    let parser = Parser::new(r#"(limit-1) % 3"#).unwrap();
+    expect(
+        parser.arg(0),
+        Argument::expr(Expr::binary(
+            Expr::binary(Expr::id("limit"), BinOp::Sub, Expr::mes(Message::int(1))),
+            BinOp::Mod,
+            Expr::mes(Message::int(3)),
+        )),
+    );
+
+    let parser = Parser::new(r#"-1"#).unwrap();
+    expect(parser.arg(0), Argument::mes(Message::int(-1)));
+
+    let parser = Parser::new(r#"-limit"#).unwrap();
+    expect(parser.arg(0), Argument::expr(Expr::neg(Expr::id("limit"))));
+
+    let parser = Parser::new(r#"name = "robot-" + id"#).unwrap();
+    expect(
+        parser.arg(0),
+        Argument::id_expr(
+            "name",
+            Expr::binary(
+                Expr::mes(Message::str("robot-")),
+                BinOp::Add,
+                Expr::id("id"),
+            ),
+        ),
+    );
+}
+
+#[test]
+fn bb_ref_arg() {
+    let parser = Parser::new(r#"{target}"#).unwrap();
+    expect(parser.arg(0), Argument::bb_ref("target"));
+
+    let parser = Parser::new(r#"a = {target}"#).unwrap();
+    expect(parser.arg(0), Argument::id_bb_ref("a", "target"));
+
+    let parser = Parser::new(r#"a = {"target":1}"#).unwrap();
+    expect(
+        parser.arg(0),
+        Argument::id_mes(
+            "a",
+            Message::object(vec![("target".to_string(), Message::int(1))]),
+        ),
+    );
+}
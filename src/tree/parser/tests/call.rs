@@ -1,9 +1,13 @@
+use crate::tree::diagnostic::Span;
 use crate::tree::parser::ast::arg::{Argument, Arguments};
 use crate::tree::parser::ast::call::{Call, Calls};
+use crate::tree::parser::ast::expr::{BinOp, Expr};
 use crate::tree::parser::ast::message::{Message, Number};
 use crate::tree::parser::ast::*;
+use crate::tree::parser::tests::tests::expect;
 use crate::tree::parser::Parser;
 use parsit::error::ParseError;
+use parsit::step::Step;
 use parsit::test::parser_test::*;
 use std::collections::HashMap;
 
@@ -137,6 +141,82 @@ fn decorator() {
     );
 }
 
+#[test]
+fn custom_decorator() {
+    let parser = Parser::new(
+        r#"
+decorator only_when(key:string);
+only_when("ready") call()"#,
+    )
+    .unwrap();
+    expect(
+        parser.call(8),
+        Call::custom_decorator(
+            "only_when",
+            Arguments::new(vec![Argument::mes(Message::str("ready"))]),
+            Call::invocation("call", Arguments::default()),
+        ),
+    );
+
+    let parser = Parser::new(
+        r#"
+decorator only_when(key:string);
+only_when { call() call2() }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        parser.call(8).error(),
+        Some(ParseError::FailedOnValidation(
+            "any decorator should have only one child",
+            17
+        ))
+    );
+
+    let parser = Parser::new(r#"only_when("ready") call()"#).unwrap();
+    expect(
+        parser.call(0),
+        Call::invocation(
+            "only_when",
+            Arguments::new(vec![Argument::mes(Message::str("ready"))]),
+        ),
+    );
+}
+
+#[test]
+fn guard() {
+    let parser = Parser::new(r#"guard(battery > 20 && mode == "auto" || !ready) call()"#).unwrap();
+    expect(
+        parser.call(0),
+        Call::decorator(
+            TreeType::Guard,
+            Arguments::new(vec![Argument::expr(Expr::binary(
+                Expr::binary(
+                    Expr::binary(Expr::id("battery"), BinOp::Gt, Expr::mes(Message::int(20))),
+                    BinOp::And,
+                    Expr::binary(Expr::id("mode"), BinOp::Eq, Expr::mes(Message::str("auto"))),
+                ),
+                BinOp::Or,
+                Expr::not(Expr::id("ready")),
+            ))]),
+            Call::invocation("call", Arguments::default()),
+        ),
+    );
+
+    let parser = Parser::new(r#"guard(!(a || b)) call()"#).unwrap();
+    expect(
+        parser.call(0),
+        Call::decorator(
+            TreeType::Guard,
+            Arguments::new(vec![Argument::expr(Expr::not(Expr::binary(
+                Expr::id("a"),
+                BinOp::Or,
+                Expr::id("b"),
+            )))]),
+            Call::invocation("call", Arguments::default()),
+        ),
+    );
+}
+
 #[test]
 fn calls() {
     let txt = r#"
@@ -156,14 +236,14 @@ fn calls() {
     expect(
         parser.calls(0),
         Calls::new(vec![
-            Call::Lambda(
+            Call::lambda(
                 TreeType::Fallback,
                 Calls::new(vec![
                     Call::invocation("ball_found", Arguments::new(vec![Argument::id("obj")])),
                     Call::invocation("find_ball", Arguments::new(vec![Argument::id("obj")])),
                 ]),
             ),
-            Call::Lambda(
+            Call::lambda(
                 TreeType::Fallback,
                 Calls::new(vec![
                     Call::invocation("close", Arguments::new(vec![Argument::id("obj")])),
@@ -173,3 +253,30 @@ fn calls() {
         ]),
     );
 }
+
+#[test]
+fn spans() {
+    let parser = Parser::new("sequence {\n    move(1, speed = 2)\n}").unwrap();
+    let lambda = match parser.call(0) {
+        Step::Success(call, _) => call,
+        e => panic!("{:?}", e.error()),
+    };
+    let pos = |span: Span| (span.start, span.end);
+    assert_eq!(pos(lambda.span()), (0, 35));
+    // the positions take part in the comparison
+    assert_ne!(lambda.clone().without_spans(), lambda);
+
+    let Call::Lambda(_, calls, _) = lambda else {
+        panic!("the lambda is expected")
+    };
+    let call = &calls.elems[0];
+    assert_eq!(pos(call.span()), (15, 33));
+
+    let args: Vec<_> = call
+        .arguments()
+        .args
+        .iter()
+        .map(|a| pos(a.span()))
+        .collect();
+    assert_eq!(args, vec![(20, 21), (23, 32)]);
+}
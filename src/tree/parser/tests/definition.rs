@@ -2,6 +2,7 @@ use crate::tree::parser::ast::arg::{Argument, Arguments, MesType, Param, Params}
 use crate::tree::parser::ast::call::{Call, Calls};
 use crate::tree::parser::ast::message::{Message, Number};
 use crate::tree::parser::ast::*;
+use crate::tree::parser::tests::tests::expect;
 use crate::tree::parser::Parser;
 use parsit::error::ParseError;
 use parsit::test::parser_test::*;
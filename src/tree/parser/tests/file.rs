@@ -1,6 +1,8 @@
 use crate::tree::parser::ast::arg::{Argument, Arguments, MesType, Param, Params};
 use crate::tree::parser::ast::call::{Call, Calls};
+use crate::tree::parser::ast::message::Message;
 use crate::tree::parser::ast::*;
+use crate::tree::parser::tests::tests::expect;
 use crate::tree::parser::Parser;
 use crate::tree::project::file::File;
 use parsit::error::ParseError;
@@ -96,3 +98,124 @@ cond ball_found {}
         ]),
     );
 }
+
+#[test]
+fn recovery() {
+    let src = r#"
+impl move(speed:num);
+impl stop(;
+
+root main sequence {
+    move(1
+}
+
+sequence next {
+    move(2)
+}
+"#;
+    let parser = Parser::new(src).unwrap();
+    assert_eq!(parser.parse().map_err(|e| e.len()), Err(2));
+
+    let (ast, errors) = crate::tree::parser::parse_file("main.tree", src);
+    let messages: Vec<_> = errors
+        .iter()
+        .map(|e| (e.line, e.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            (3, "the token ; is unexpected"),
+            (7, "the token } is unexpected")
+        ]
+    );
+
+    let names: Vec<_> = ast
+        .0
+        .iter()
+        .flat_map(|e| match e {
+            FileEntity::Tree(t) => Some(t.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["move", "stop", "main", "next"]);
+}
+
+#[test]
+fn consts_and_enums() {
+    let parser = Parser::new(
+        r#"
+const speed = 10;
+const home = Zone::Kitchen;
+enum Zone { Kitchen, Hall, }
+
+impl move(speed:num, zone:Zone);
+root main move(speed, Zone::Hall)
+"#,
+    )
+    .unwrap();
+    expect(
+        parser.file(0),
+        AstFile(vec![
+            FileEntity::Const(Const::new("speed", Message::int(10))),
+            FileEntity::Const(Const::new("home", Message::enum_value("Zone", "Kitchen"))),
+            FileEntity::Enum(Enum::new("Zone", vec!["Kitchen", "Hall"])),
+            FileEntity::Tree(Tree::new(
+                TreeType::Impl,
+                "move".to_string(),
+                Params::new(vec![
+                    Param::new("speed", MesType::Num),
+                    Param::new("zone", MesType::Enum("Zone".to_string())),
+                ]),
+                Calls::default(),
+            )),
+            FileEntity::Tree(Tree::new(
+                TreeType::Root,
+                "main".to_string(),
+                Params::default(),
+                Calls::new(vec![Call::invocation(
+                    "move",
+                    Arguments::new(vec![
+                        Argument::id("speed"),
+                        Argument::mes(Message::enum_value("Zone", "Hall")),
+                    ]),
+                )]),
+            )),
+        ]),
+    );
+}
+
+#[test]
+fn contextual_keywords() {
+    let parser = Parser::new(
+        r#"
+const enum = 1;
+impl const(enum:num, out out:num);
+root main const(enum, {out})
+"#,
+    )
+    .unwrap();
+    expect(
+        parser.file(0),
+        AstFile(vec![
+            FileEntity::Const(Const::new("enum", Message::int(1))),
+            FileEntity::Tree(Tree::new(
+                TreeType::Impl,
+                "const".to_string(),
+                Params::new(vec![
+                    Param::new("enum", MesType::Num),
+                    Param::output("out", MesType::Num),
+                ]),
+                Calls::default(),
+            )),
+            FileEntity::Tree(Tree::new(
+                TreeType::Root,
+                "main".to_string(),
+                Params::default(),
+                Calls::new(vec![Call::invocation(
+                    "const",
+                    Arguments::new(vec![Argument::id("enum"), Argument::bb_ref("out")]),
+                )]),
+            )),
+        ]),
+    );
+}
@@ -1,7 +1,8 @@
 use crate::tree::parser::ast::arg::{MesType, Param, Params};
+use crate::tree::parser::ast::message::Message;
 use crate::tree::parser::ast::*;
 use crate::tree::parser::Parser;
-use parsit::test::parser_test::expect;
+use parsit::test::parser_test::{expect, fail};
 
 #[test]
 fn params() {
@@ -22,4 +23,52 @@ fn params() {
             Param::new("b", MesType::String),
         ]),
     );
+
+    let parser = Parser::new(r#"(a:num, out res:num)"#).unwrap();
+    expect(
+        parser.params(0),
+        Params::new(vec![
+            Param::new("a", MesType::Num),
+            Param::output("res", MesType::Num),
+        ]),
+    );
+
+    let parser = Parser::new(r#"(out:num, out out:num)"#).unwrap();
+    expect(
+        parser.params(0),
+        Params::new(vec![
+            Param::new("out", MesType::Num),
+            Param::output("out", MesType::Num),
+        ]),
+    );
+}
+
+#[test]
+fn default_params() {
+    let parser =
+        Parser::new(r#"(speed:num = 1.0, target?:object, name:string = "robot")"#).unwrap();
+    expect(
+        parser.params(0),
+        Params::new(vec![
+            Param::with_default("speed", MesType::Num, Message::float(1.0)),
+            Param::optional("target", MesType::Object),
+            Param::with_default("name", MesType::String, Message::str("robot")),
+        ]),
+    );
+
+    let parser = Parser::new(r#"(points:array<num> = [1, 2], matrix:array<array<num>>)"#).unwrap();
+    expect(
+        parser.params(0),
+        Params::new(vec![
+            Param::with_default(
+                "points",
+                MesType::array_of(MesType::Num),
+                Message::array(vec![Message::int(1), Message::int(2)]),
+            ),
+            Param::new("matrix", MesType::array_of(MesType::array_of(MesType::Num))),
+        ]),
+    );
+
+    let parser = Parser::new(r#"(speed:num = "fast")"#).unwrap();
+    fail(parser.params(0));
 }
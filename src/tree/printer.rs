@@ -0,0 +1,755 @@
+use crate::tree::diagnostic::{Diagnostic, Span};
+use crate::tree::parser::ast::arg::{Argument, ArgumentRhs, Arguments, Params};
+use crate::tree::parser::ast::call::Call;
+use crate::tree::parser::ast::expr::{BinOp, Expr};
+use crate::tree::parser::ast::message::{Bool, Message, Number};
+use crate::tree::parser::ast::{
+    AstFile, Const, Enum, FileEntity, Import, ImportName, Tree, TreeType,
+};
+use crate::tree::parser::{comments, parse_file, Comment};
+use crate::tree::TreeError;
+use itertools::Itertools;
+use std::collections::VecDeque;
+
+const INDENT: &str = "    ";
+
+/// Prints the file back to the source:
+/// ```f-tree
+/// import "std::actions"
+///
+/// impl move_to(target:string);
+///
+/// sequence patrol {
+///     move_to("a")
+///     retry(3) move_to("b")
+/// }
+///
+/// root main patrol()
+/// ```
+/// The imports, the constants, the enums and the declarations of the actions
+/// are grouped together, the definitions with the bodies are separated with the blank lines.
+pub fn print(file: &AstFile) -> String {
+    Printer::default().file(file)
+}
+
+/// Formats the source of the file the same way [`print`] does, keeping the comments.
+/// Besides, the blank lines between the calls and the literals as they are written (e.g. `2s`) are kept.
+/// The source with the errors is not formatted,
+/// as well as the source with the comments inside the imports, the parameters or the arguments.
+pub fn format(file: &str, src: &str) -> Result<String, TreeError> {
+    let (ast, errors) = parse_file(file, src);
+    if !errors.is_empty() {
+        return Err(TreeError::Diagnostics(errors));
+    }
+    let comments = comments(src);
+    let code = without_comments(src, &comments);
+    let misplaced: Vec<Diagnostic> = comments
+        .iter()
+        .filter(|c| !kept_in_file(&ast, &code, c.span.start))
+        .map(|c| {
+            Diagnostic::new(
+                file,
+                src,
+                c.span,
+                "the comment inside the node can not be kept in place, it should be moved before or after the node"
+                    .to_string(),
+            )
+        })
+        .collect();
+    if !misplaced.is_empty() {
+        return Err(TreeError::Diagnostics(misplaced));
+    }
+    let printer = Printer {
+        src: Some(src),
+        comments: comments.into(),
+        ..Printer::default()
+    };
+    Ok(printer.file(&ast))
+}
+
+/// the source with the comments replaced by the spaces, so the braces in them are not found
+fn without_comments(src: &str, comments: &[Comment]) -> String {
+    let mut code = src.to_string();
+    for c in comments {
+        code.replace_range(
+            c.span.start..c.span.end,
+            &" ".repeat(c.span.end - c.span.start),
+        );
+    }
+    code
+}
+
+fn inside(span: Span, pos: usize) -> bool {
+    span.start <= pos && pos < span.end
+}
+
+/// The printer keeps the comments between the entities of the file and between the calls of the blocks.
+/// The comments inside the imports, the parameters and the arguments would be moved.
+fn kept_in_file(file: &AstFile, code: &str, pos: usize) -> bool {
+    match file.0.iter().find(|e| inside(span(e), pos)) {
+        None => true,
+        Some(FileEntity::Tree(tree)) if !is_declaration(tree) => {
+            let first = tree
+                .calls
+                .elems
+                .first()
+                .map(|c| c.span().start)
+                .unwrap_or(tree.span.end);
+            // the body starts after the last brace before the first call,
+            // the root with the single call may go without the braces
+            let body = code[tree.span.start..first]
+                .rfind('{')
+                .map(|i| tree.span.start + i + 1)
+                .unwrap_or(first);
+            pos >= body && kept_in_calls(&tree.calls.elems, code, pos)
+        }
+        Some(_) => false,
+    }
+}
+
+fn kept_in_calls(calls: &[Call], code: &str, pos: usize) -> bool {
+    match calls.iter().find(|c| inside(c.span(), pos)) {
+        None => true,
+        Some(call) => kept_in_call(call, code, pos),
+    }
+}
+
+fn kept_in_call(call: &Call, code: &str, pos: usize) -> bool {
+    match call {
+        Call::Lambda(_, calls, span) => {
+            let first = calls
+                .elems
+                .first()
+                .map(|c| c.span().start)
+                .unwrap_or(span.end);
+            let body = code[span.start..first]
+                .find('{')
+                .map(|i| span.start + i + 1)
+                .unwrap_or(first);
+            pos >= body && kept_in_calls(&calls.elems, code, pos)
+        }
+        Call::Decorator(_, _, child, _) | Call::CustomDecorator(_, _, child, _)
+            if inside(child.span(), pos) =>
+        {
+            kept_in_call(child, code, pos)
+        }
+        Call::Decorator(_, args, _, _)
+        | Call::CustomDecorator(_, args, _, _)
+        | Call::Invocation(_, args, _) => args.args.iter().any(|a| match a.value() {
+            // the lambdas passed as the arguments keep the comments in their blocks
+            ArgumentRhs::Call(call) if inside(call.span(), pos) => kept_in_call(call, code, pos),
+            _ => false,
+        }),
+        Call::HoInvocation(_, _) => false,
+    }
+}
+
+/// the one-line entities of the same kind are written without the blank lines between them
+fn same_group(lhs: &FileEntity, rhs: &FileEntity) -> bool {
+    match (lhs, rhs) {
+        (FileEntity::Import(_), FileEntity::Import(_))
+        | (FileEntity::Const(_), FileEntity::Const(_))
+        | (FileEntity::Enum(_), FileEntity::Enum(_)) => true,
+        (FileEntity::Tree(l), FileEntity::Tree(r)) => is_declaration(l) && is_declaration(r),
+        _ => false,
+    }
+}
+
+/// the actions and the decorators are declared without the bodies
+fn is_declaration(tree: &Tree) -> bool {
+    tree.tpe.is_action() || tree.tpe.is_custom_decorator()
+}
+
+fn span(entity: &FileEntity) -> Span {
+    match entity {
+        FileEntity::Tree(t) => t.span,
+        FileEntity::Import(Import(_, _, span)) => *span,
+        FileEntity::Const(c) => c.span,
+        FileEntity::Enum(e) => e.span,
+    }
+}
+
+#[derive(Default)]
+struct Printer<'a> {
+    out: String,
+    /// the source the file is parsed from.
+    /// The ast without the source is printed without the comments and the blank lines.
+    src: Option<&'a str>,
+    /// the comments that are not printed yet
+    comments: VecDeque<Comment>,
+}
+
+impl<'a> Printer<'a> {
+    fn file(mut self, file: &AstFile) -> String {
+        let mut prev: Option<&FileEntity> = None;
+        for entity in file.0.iter() {
+            let span = span(entity);
+            if let Some(prev) = prev {
+                self.out.push('\n');
+                if !same_group(prev, entity) || self.blank_before(self.start(span)) {
+                    self.out.push('\n');
+                }
+            }
+            self.leading(span.start, 0);
+            self.entity(entity);
+            self.trailing(span.end);
+            prev = Some(entity);
+        }
+        // the comments at the end of the file
+        if let Some(first) = self.comments.front() {
+            if !self.out.is_empty() {
+                self.out.push('\n');
+                if self.blank_before(first.span.start) {
+                    self.out.push('\n');
+                }
+            }
+            self.leading(usize::MAX, 0);
+            self.out.truncate(self.out.trim_end().len());
+        }
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+
+    /// the position the node starts at along with the comments before it
+    fn start(&self, span: Span) -> usize {
+        match self.comments.front() {
+            Some(c) if c.span.start < span.start => c.span.start,
+            _ => span.start,
+        }
+    }
+
+    /// there is at least one empty line in the source right before the position
+    fn blank_before(&self, pos: usize) -> bool {
+        match self.src.and_then(|src| src.get(..pos)) {
+            Some(before) => {
+                before
+                    .chars()
+                    .rev()
+                    .take_while(|c| c.is_whitespace())
+                    .filter(|c| *c == '\n')
+                    .count()
+                    > 1
+            }
+            None => false,
+        }
+    }
+
+    /// the comments before the position, every one on its own line
+    fn leading(&mut self, pos: usize, depth: usize) {
+        while let Some(comment) = self.pop_before(pos) {
+            self.out.push_str(&comment.text);
+            self.out.push('\n');
+            let next = self.start(Span::new(pos, pos));
+            if next != usize::MAX && self.blank_before(next) {
+                self.out.push('\n');
+            }
+            self.out.push_str(&INDENT.repeat(depth));
+        }
+    }
+
+    /// the comment on the same line right after the node
+    fn trailing(&mut self, end: usize) {
+        let same_line = match (self.src, self.comments.front()) {
+            (Some(src), Some(c)) if c.span.start >= end => src
+                .get(end..c.span.start)
+                .map(|gap| !gap.contains('\n'))
+                .unwrap_or(false),
+            _ => false,
+        };
+        if same_line {
+            if let Some(comment) = self.comments.pop_front() {
+                self.out.push(' ');
+                self.out.push_str(&comment.text);
+            }
+        }
+    }
+
+    fn pop_before(&mut self, pos: usize) -> Option<Comment> {
+        match self.comments.front() {
+            Some(c) if c.span.start < pos => self.comments.pop_front(),
+            _ => None,
+        }
+    }
+
+    /// The literal as it is written in the source if it is on one line and has no comments inside.
+    /// The assigned value is taken after the sign `=`.
+    fn written(&self, span: Span, assigned: bool) -> Option<String> {
+        let text = self.src?.get(span.start..span.end)?;
+        let text = if assigned {
+            text.split_once('=')?.1
+        } else {
+            text
+        };
+        let text = text.trim().trim_end_matches(';').trim_end();
+        let commented = self
+            .comments
+            .iter()
+            .any(|c| span.start <= c.span.start && c.span.start < span.end);
+        if text.is_empty() || text.contains('\n') || commented {
+            None
+        } else {
+            Some(text.to_string())
+        }
+    }
+
+    fn entity(&mut self, entity: &FileEntity) {
+        match entity {
+            FileEntity::Tree(tree) => self.tree(tree),
+            FileEntity::Import(import) => self.import(import),
+            FileEntity::Const(Const { name, value, span }) => {
+                let value = self.written(*span, true).unwrap_or_else(|| message(value));
+                self.out.push_str(&format!("const {name} = {value};"))
+            }
+            FileEntity::Enum(Enum { name, variants, .. }) => self
+                .out
+                .push_str(&format!("enum {name} {{ {} }}", variants.join(", "))),
+        }
+    }
+
+    fn import(&mut self, Import(file, names, _): &Import) {
+        self.out.push_str(&format!("import \"{file}\""));
+        if names.iter().any(|n| *n == ImportName::WholeFile) {
+            return;
+        }
+        self.out.push_str(" {\n");
+        for name in names {
+            let name = match name {
+                ImportName::Id(id) => id.clone(),
+                ImportName::Alias(id, alias) => format!("{id} => {alias}"),
+                ImportName::WholeFile => continue,
+            };
+            self.out.push_str(&format!("{INDENT}{name},\n"));
+        }
+        self.out.push('}');
+    }
+
+    fn tree(&mut self, tree: &Tree) {
+        self.out.push_str(&format!("{} {}", tree.tpe, tree.name));
+        // the declarations keep the parentheses even without the parameters
+        if !tree.params.params.is_empty() || is_declaration(tree) {
+            self.out.push_str(&params(&tree.params));
+        }
+        match tree.calls.elems.as_slice() {
+            [] if is_declaration(tree) => self.out.push(';'),
+            // the root is usually the single call written on the same line
+            [call] if tree.tpe == TreeType::Root => {
+                self.out.push(' ');
+                self.call(call, 0);
+            }
+            calls => {
+                self.out.push(' ');
+                self.block(calls, 0, tree.span.end);
+            }
+        }
+    }
+
+    /// the calls in the braces, every call on its own line.
+    /// The block ends at the position `end` in the source, the comments before it stay inside.
+    fn block(&mut self, calls: &[Call], depth: usize, end: usize) {
+        let inner = INDENT.repeat(depth + 1);
+        if calls.is_empty() && self.start(Span::new(end, end)) == end {
+            self.out.push_str("{}");
+            return;
+        }
+        self.out.push_str("{\n");
+        for (idx, call) in calls.iter().enumerate() {
+            let span = call.span();
+            if idx > 0 && self.blank_before(self.start(span)) {
+                self.out.push('\n');
+            }
+            self.out.push_str(&inner);
+            self.leading(span.start, depth + 1);
+            self.call(call, depth + 1);
+            self.trailing(span.end);
+            self.out.push('\n');
+        }
+        if self.start(Span::new(end, end)) < end {
+            if !calls.is_empty() && self.blank_before(self.start(Span::new(end, end))) {
+                self.out.push('\n');
+            }
+            self.out.push_str(&inner);
+            self.leading(end, depth + 1);
+            self.out.truncate(self.out.trim_end().len());
+            self.out.push('\n');
+        }
+        self.out.push_str(&INDENT.repeat(depth));
+        self.out.push('}');
+    }
+
+    fn call(&mut self, call: &Call, depth: usize) {
+        match call {
+            Call::Invocation(name, args, _) => {
+                self.out.push_str(name);
+                self.args(args, depth);
+            }
+            Call::HoInvocation(name, _) => self.out.push_str(&format!("{name}(..)")),
+            Call::Lambda(tpe, calls, span) => {
+                self.out.push_str(&format!("{tpe} "));
+                self.block(&calls.elems, depth, span.end);
+            }
+            Call::Decorator(tpe, args, child, _) => {
+                self.decorator(&tpe.to_string(), args, child, depth)
+            }
+            Call::CustomDecorator(name, args, child, _) => self.decorator(name, args, child, depth),
+        }
+    }
+
+    /// the decorator and the child on the same line: `retry(3) timeout(2s) action()`
+    fn decorator(&mut self, name: &str, args: &Arguments, child: &Call, depth: usize) {
+        self.out.push_str(name);
+        if !args.args.is_empty() {
+            self.args(args, depth);
+        }
+        self.out.push(' ');
+        self.call(child, depth);
+    }
+
+    fn args(&mut self, args: &Arguments, depth: usize) {
+        self.out.push('(');
+        for (idx, arg) in args.args.iter().enumerate() {
+            if idx > 0 {
+                self.out.push_str(", ");
+            }
+            let rhs = match arg {
+                Argument::Assigned(name, rhs, _) => {
+                    self.out.push_str(&format!("{name} = "));
+                    rhs
+                }
+                Argument::Unassigned(rhs, _) => rhs,
+            };
+            match rhs {
+                ArgumentRhs::Id(id) => self.out.push_str(id),
+                ArgumentRhs::Mes(m) => {
+                    let assigned = matches!(arg, Argument::Assigned(..));
+                    let value = self
+                        .written(arg.span(), assigned)
+                        .unwrap_or_else(|| message(m));
+                    self.out.push_str(&value)
+                }
+                ArgumentRhs::BBRef(key) => self.out.push_str(&format!("{{{key}}}")),
+                ArgumentRhs::Expr(e) => self.out.push_str(&expr(e)),
+                ArgumentRhs::Call(call) => self.call(call, depth),
+            }
+        }
+        self.out.push(')');
+    }
+}
+
+fn params(params: &Params) -> String {
+    let params = params
+        .params
+        .iter()
+        .map(|p| {
+            let out = if p.output { "out " } else { "" };
+            let optional = if p.optional { "?" } else { "" };
+            let default = match &p.default {
+                Some(v) => format!(" = {}", message(v)),
+                None => String::new(),
+            };
+            format!("{out}{}{optional}:{}{default}", p.name, p.tpe)
+        })
+        .join(", ");
+    format!("({params})")
+}
+
+/// The message as it is written in the source.
+/// The strings keep the content as it is, the floats always have the point.
+pub fn message(m: &Message) -> String {
+    match m {
+        Message::Num(Number::Int(v)) => v.to_string(),
+        Message::Num(Number::Float(v)) => {
+            let v = v.to_string();
+            if v.contains('.') || !v.ends_with(|c: char| c.is_ascii_digit()) {
+                v
+            } else {
+                format!("{v}.0")
+            }
+        }
+        Message::Num(Number::Hex(v)) if *v < 0 => format!("-0x{:x}", v.unsigned_abs()),
+        Message::Num(Number::Hex(v)) => format!("0x{v:x}"),
+        Message::Num(Number::Binary(v)) if *v < 0 => format!("-0b{:b}", v.unsigned_abs()),
+        Message::Num(Number::Binary(v)) => format!("0b{v:b}"),
+        Message::String(s) => format!("\"{}\"", s.0.replace('\\', "\\\\").replace('"', "\\\"")),
+        Message::Bool(Bool::True) => "true".to_string(),
+        Message::Bool(Bool::False) => "false".to_string(),
+        Message::Array(elems) => format!("[{}]", elems.iter().map(message).join(", ")),
+        // the pairs are sorted since the object does not keep the order
+        Message::Object(pairs) => format!(
+            "{{{}}}",
+            pairs
+                .iter()
+                .sorted_by(|l, r| l.0.cmp(r.0))
+                .map(|(k, v)| format!("\"{k}\": {}", message(v)))
+                .join(", ")
+        ),
+        Message::Enum(tpe, variant) => format!("{tpe}::{variant}"),
+    }
+}
+
+/// The expression with the parentheses only where the precedence requires them.
+pub fn expr(e: &Expr) -> String {
+    match e {
+        Expr::Id(id) => id.clone(),
+        Expr::Mes(m) => message(m),
+        Expr::Not(e) => format!("!{}", operand(e)),
+        Expr::Neg(e) => format!("-{}", operand(e)),
+        Expr::Binary(lhs, op, rhs) => {
+            let prec = precedence(op);
+            // the comparisons do not chain, thus the nested one gets the parentheses on both sides
+            let lhs_prec = if prec == precedence(&BinOp::Eq) {
+                prec + 1
+            } else {
+                prec
+            };
+            format!("{} {op} {}", grouped(lhs, lhs_prec), grouped(rhs, prec + 1))
+        }
+    }
+}
+
+/// the operand of the unary operator is grouped unless it is a single term
+fn operand(e: &Expr) -> String {
+    match e {
+        Expr::Binary(..) => format!("({})", expr(e)),
+        Expr::Mes(Message::Num(_)) => format!("({})", expr(e)),
+        e => expr(e),
+    }
+}
+
+fn grouped(e: &Expr, min: u8) -> String {
+    match e {
+        Expr::Binary(_, op, _) if precedence(op) < min => format!("({})", expr(e)),
+        e => expr(e),
+    }
+}
+
+fn precedence(op: &BinOp) -> u8 {
+    match op {
+        BinOp::Or => 1,
+        BinOp::And => 2,
+        BinOp::Eq | BinOp::Ne | BinOp::Gt | BinOp::Ge | BinOp::Lt | BinOp::Le => 3,
+        BinOp::Add | BinOp::Sub => 4,
+        BinOp::Mul | BinOp::Div | BinOp::Mod => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::parser::ast::arg::{Argument, Arguments};
+    use crate::tree::parser::ast::call::Call;
+    use crate::tree::parser::ast::expr::{BinOp, Expr};
+    use crate::tree::parser::ast::message::Message;
+    use crate::tree::parser::ast::{AstFile, FileEntity, Tree};
+    use crate::tree::parser::{parse_file, Parser};
+    use crate::tree::printer::{expr, format, print};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn expressions() {
+        let e = Expr::binary(
+            Expr::binary(Expr::id("a"), BinOp::Add, Expr::id("b")),
+            BinOp::Mul,
+            Expr::binary(Expr::id("c"), BinOp::Sub, Expr::mes(Message::int(1))),
+        );
+        assert_eq!(expr(&e), "(a + b) * (c - 1)");
+
+        let e = Expr::binary(
+            Expr::binary(Expr::id("a"), BinOp::Gt, Expr::mes(Message::int(1))),
+            BinOp::And,
+            Expr::not(Expr::binary(Expr::id("b"), BinOp::Or, Expr::id("c"))),
+        );
+        assert_eq!(expr(&e), "a > 1 && !(b || c)");
+
+        let e = Expr::binary(
+            Expr::id("a"),
+            BinOp::Sub,
+            Expr::binary(Expr::id("b"), BinOp::Sub, Expr::id("c")),
+        );
+        assert_eq!(expr(&e), "a - (b - c)");
+    }
+
+    #[test]
+    fn file() {
+        let file = AstFile::new(vec![FileEntity::Tree(Tree::root(
+            "main",
+            vec![Call::invocation(
+                "go",
+                Arguments::new(vec![
+                    Argument::id_mes("speed", Message::float(2.0)),
+                    Argument::id_bb_ref("target", "point"),
+                ]),
+            )],
+        ))]);
+        assert_eq!(
+            print(&file),
+            "root main go(speed = 2.0, target = {point})\n"
+        );
+    }
+
+    #[test]
+    fn parsed_back() {
+        let src = r#"import "std::actions"
+import "nav.tree" {
+    move_to => go,
+    dock,
+}
+
+const speed = 0x1f;
+const zones = [Zone::Hall, Zone::Kitchen];
+
+enum Zone { Hall, Kitchen }
+
+impl pick(items:array<string>, out picked:string, limit?:num, mode:string = "fast");
+cond is_ready();
+decorator only_when(key:string);
+
+sequence patrol(point:string, job:tree) {
+    go(point)
+    fallback {
+        is_ready()
+        job(..)
+        fail("not ready")
+    }
+    m_sequence {}
+}
+
+root main r_sequence {
+    patrol("dock", sequence {
+        success()
+    })
+    retry(3) timeout(2000) pick(["a", "b"], {picked})
+    guard(speed > 1 && !(a || b)) only_when("ready") go({picked})
+    inverter fail_empty()
+}
+"#;
+        let ast = Parser::new(src).unwrap().parse().unwrap();
+        assert_eq!(print(&ast), src);
+    }
+
+    #[test]
+    fn formatted() {
+        let src = r#"// the header
+import "nav.tree" { go,   dock }
+/* the speed */
+const speed=2s; // in ms
+
+impl   move_to(target:string);   // moves
+cond is_ready(  );
+
+// the patrol
+sequence patrol(point:string)
+{
+  // first
+  move_to(point) // go there
+  retry( 3 ) is_ready()
+
+
+  fallback {
+    // nothing
+  }
+  sequence {}
+  // last words
+}
+root main sequence {
+  patrol("a")
+  inverter fail_empty() }
+// the end
+"#;
+        let expected = r#"// the header
+import "nav.tree" {
+    go,
+    dock,
+}
+
+/* the speed */
+const speed = 2s; // in ms
+
+impl move_to(target:string); // moves
+cond is_ready();
+
+// the patrol
+sequence patrol(point:string) {
+    // first
+    move_to(point) // go there
+    retry(3) is_ready()
+
+    fallback {
+        // nothing
+    }
+    sequence {}
+    // last words
+}
+
+root main sequence {
+    patrol("a")
+    inverter fail_empty()
+}
+// the end
+"#;
+        assert_eq!(format("main.tree", src).unwrap(), expected);
+        assert_eq!(format("main.tree", expected).unwrap(), expected);
+        assert!(format("main.tree", "root main sequence {").is_err());
+    }
+
+    #[test]
+    fn misplaced_comments() {
+        let misplaced = [
+            "import \"nav.tree\" {\n    go, // the move\n    dock,\n}\n",
+            "impl move_to(target:string /* the point */);\n",
+            "sequence patrol(/* the point */ point:string) {\n    move_to(point)\n}\n",
+            "root main sequence {\n    move_to(\"a\", // the point\n        1)\n}\n",
+            "root main retry(/* twice */ 2) move_to(\"a\")\n",
+            "root main /* the flow */ sequence {\n    move_to(\"a\")\n}\n",
+        ];
+        for src in misplaced {
+            let err = format("main.tree", src).unwrap_err().to_string();
+            assert!(err.contains("the comment inside the node"), "{src}: {err}");
+        }
+
+        let kept = r#"root main sequence {
+    patrol(sequence {
+        // inside the lambda
+        success()
+    }) // after the call
+    /* before the call */ retry(2) move_to("a")
+}
+"#;
+        let formatted = format("main.tree", kept).unwrap();
+        assert!(formatted.contains("// inside the lambda"));
+        assert!(formatted.contains("// after the call"));
+        assert!(formatted.contains("/* before the call */"));
+    }
+
+    #[test]
+    fn formatted_examples() {
+        let mut root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        root.push("tree/tests");
+        let mut dirs = vec![root];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.extension().map_or(false, |e| e == "tree") {
+                    let src = fs::read_to_string(&path).unwrap();
+                    let file = path.to_string_lossy().to_string();
+                    let (ast, errors) = parse_file(file.as_str(), src.as_str());
+                    if !errors.is_empty() {
+                        continue;
+                    }
+                    let formatted = format(file.as_str(), src.as_str()).unwrap();
+                    let (formatted_ast, errors) = parse_file(file.as_str(), formatted.as_str());
+                    assert!(errors.is_empty(), "{file}");
+                    assert_eq!(formatted_ast.without_spans(), ast.without_spans(), "{file}");
+                    assert_eq!(
+                        format(file.as_str(), formatted.as_str()).unwrap(),
+                        formatted,
+                        "{file}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,18 @@
+pub mod consts;
 pub mod file;
 pub mod imports;
+pub mod libs;
+pub mod typecheck;
 
 use crate::read_file;
 use crate::runtime::action::ActionName;
 use crate::runtime::builder::BuilderBuiltInActions;
 use crate::runtime::RtResult;
+use crate::tree::diagnostic::{Diagnostic, Location, Span};
+use crate::tree::parser;
 use crate::tree::parser::ast::{AstFile, FileEntity, Import, ImportName, Key, Tree};
-use crate::tree::parser::Parser;
 use crate::tree::project::file::File;
+use crate::tree::project::libs::Libs;
 use crate::tree::{cerr, TreeError};
 use itertools::Itertools;
 use parsit::error::ParseError;
@@ -24,13 +29,17 @@ pub type AliasName = String;
 /// the base structure represents the folder on the disk with some auxiliary info
 /// ## Structure
 ///   - `root` is a root of the project. Every import relates to it.
+///   - `libs` is a list of the library paths to search the packages in, see [`Libs`]
 ///   - `main` is a pointer to the file and definition when the tree is started.
 ///   - `files` is a map of the files
+///   - `sources` is a map of the texts of the files to point the errors to the places in them
 #[derive(Debug, Default, Clone)]
 pub struct Project {
     pub root: PathBuf,
+    pub libs: Libs,
     pub main: (FileName, TreeName),
     pub files: HashMap<FileName, File>,
+    pub sources: HashMap<FileName, String>,
     pub std: HashSet<ActionName>,
 }
 
@@ -51,69 +60,237 @@ impl<'a> Project {
         self.files.get(file).and_then(|f| f.definitions.get(tree))
     }
 
+    /// attaches the position in the file to the error if the text of the file is known
+    pub fn locate(&self, file: &str, span: Span, e: TreeError) -> TreeError {
+        match self.sources.get(file) {
+            Some(src) => e.at(file, src, span),
+            None => e,
+        }
+    }
+
+    /// the position of the span in the file, the file name only if the text of the file is unknown
+    pub fn location(&self, file: &str, span: Span) -> Location {
+        match self.sources.get(file) {
+            Some(src) => Location::new(file, src, span),
+            None => Location {
+                file: file.to_string(),
+                ..Location::default()
+            },
+        }
+    }
+
     pub fn build_with_root(
         main_file: FileName,
         main_call: TreeName,
         root: PathBuf,
     ) -> Result<Project, TreeError> {
-        let mut project = Project {
-            root: root.clone(),
-            main: ("".to_string(), "".to_string()),
-            files: Default::default(),
-            std: Default::default(),
-        };
-        project.main = (main_file.clone(), main_call);
-        let _ = project.parse_file(root.clone(), main_file.clone())?;
-        Ok(project)
+        Project::build_with_libs(main_file, Some(main_call), root, vec![])
     }
     pub fn build(main_file: FileName, root: PathBuf) -> Result<Project, TreeError> {
+        Project::build_with_libs(main_file, None, root, vec![])
+    }
+
+    /// builds the project searching the packages in the library paths of the manifest
+    /// and in the given ones.
+    /// If the main call is not set, the first root definition of the main file is taken.
+    pub fn build_with_libs(
+        main_file: FileName,
+        main_call: Option<TreeName>,
+        root: PathBuf,
+        libs: Vec<PathBuf>,
+    ) -> Result<Project, TreeError> {
         let mut project = Project {
             root: root.clone(),
+            libs: Libs::from_manifest(&root)?,
             main: ("".to_string(), "".to_string()),
             files: Default::default(),
+            sources: Default::default(),
             std: Default::default(),
         };
+        for lib in libs {
+            project.libs.add(lib);
+        }
 
-        let _ = project.parse_file(root.clone(), main_file.clone())?;
-
-        let main_call = project
-            .files
-            .get(main_file.as_str())
-            .and_then(|file| file.definitions.iter().find(|(name, t)| t.is_root()))
-            .map(|(name, _)| name.to_string())
-            .ok_or(TreeError::IOError(format!(
-                "no root operation in the file {}",
-                main_file.clone()
-            )))?;
+        project.parse_files(root, main_file.clone())?;
+
+        let main_call = match main_call {
+            Some(call) => call,
+            None => project
+                .files
+                .get(main_file.as_str())
+                .and_then(|file| file.definitions.iter().find(|(name, t)| t.is_root()))
+                .map(|(name, _)| name.to_string())
+                .ok_or(TreeError::IOError(format!(
+                    "no root operation in the file {}",
+                    main_file.clone()
+                )))?,
+        };
         project.main = (main_file, main_call);
         Ok(project)
     }
 
-    fn parse_file(&mut self, mut root: PathBuf, file: FileName) -> Result<(), TreeError> {
-        let text = file_to_str(root.clone(), file.clone())?;
-        let ast_file = Parser::new(text.as_str())?.parse()?;
+    /// parses the main file and the imported ones, checks the types of the parameters
+    /// and inlines the constants.
+    /// The errors in one file do not stop the parsing of the others,
+    /// thus all the errors in the project are reported at once.
+    fn parse_files(&mut self, root: PathBuf, main_file: FileName) -> Result<(), TreeError> {
+        let mut errors = vec![];
+        self.parse_file(root, main_file, &mut vec![], &mut errors)?;
+        if errors.is_empty() {
+            errors.extend(imports::check_conflicts(self));
+        }
+        if errors.is_empty() {
+            errors.extend(typecheck::check_param_types(self));
+        }
+        if errors.is_empty() {
+            consts::inline_consts(self)
+        } else {
+            Err(TreeError::Diagnostics(errors))
+        }
+    }
 
+    /// parses the file and the imported ones.
+    /// The `path` is the chain of the imports leading to the file, to detect the cycles.
+    fn parse_file(
+        &mut self,
+        mut root: PathBuf,
+        file: FileName,
+        path: &mut Vec<FileName>,
+        errors: &mut Vec<Diagnostic>,
+    ) -> Result<(), TreeError> {
         if !self.files.contains_key(file.as_str()) {
+            let text = file_to_str(root.clone(), &self.libs, file.clone())?;
+            path.push(file.clone());
+            let (mut ast_file, mut parse_errors) = parser::parse_file(file.as_str(), text.as_str());
+
+            // the imported decorators are parsed like the declared ones,
+            // thus the file is parsed again when the imported files bring some of them.
+            // The errors of the imported files go after the errors of the file.
+            let mut imported_errors = vec![];
+            let decorators =
+                self.parse_imports(&root, &file, &text, &ast_file, path, &mut imported_errors);
+            if !decorators.is_empty() {
+                (ast_file, parse_errors) = parser::parse_file_with(&file, &text, &decorators);
+            }
+            errors.extend(parse_errors);
+            errors.extend(imported_errors);
+
             let mut file = File::new(file.clone());
 
             for ent in ast_file.0.into_iter() {
                 let _ = match ent {
-                    FileEntity::Tree(t) => file.add_def(t)?,
+                    FileEntity::Tree(t) => {
+                        let span = t.span;
+                        if let Err(e) = file.add_def(t) {
+                            errors.push(Diagnostic::new(&file.name, &text, span, e.to_string()));
+                        }
+                    }
+                    FileEntity::Const(c) => {
+                        let span = c.span;
+                        if let Err(e) = file.add_const(c) {
+                            errors.push(Diagnostic::new(&file.name, &text, span, e.to_string()));
+                        }
+                    }
+                    FileEntity::Enum(en) => {
+                        let span = en.span;
+                        if let Err(e) = file.add_enum(en) {
+                            errors.push(Diagnostic::new(&file.name, &text, span, e.to_string()));
+                        }
+                    }
                     FileEntity::Import(i) => {
-                        let _ = self.parse_file(root.clone(), i.f_name().to_string())?;
-                        file.add_import(i)?
+                        let i = libs::qualify(&file.name, i);
+                        let imported = i.f_name().to_string();
+                        match path.iter().position(|f| f == &imported) {
+                            Some(start) => {
+                                let cycle = path[start..].iter().chain([&imported]).join(" -> ");
+                                let msg = format!("the import cycle is detected: {cycle}");
+                                errors.push(Diagnostic::new(&file.name, &text, i.2, msg));
+                            }
+                            // the imported files are parsed already
+                            None => {}
+                        }
+                        let span = i.2;
+                        if let Err(e) = file.add_import(i) {
+                            errors.push(Diagnostic::new(&file.name, &text, span, e.to_string()));
+                        }
                     }
                 };
             }
+            path.pop();
 
+            self.sources.insert(file.name.clone(), text);
             self.files.insert(file.name.clone(), file);
         }
         Ok(())
     }
+
+    /// parses the files imported in the ast and collects the names
+    /// the decorators declared in them are known by in the file.
+    /// The file that can not be read is reported at the import, the other files are parsed further.
+    /// The cycles are skipped here and reported when the imports are added to the file.
+    fn parse_imports(
+        &mut self,
+        root: &PathBuf,
+        file: &FileName,
+        src: &str,
+        ast_file: &AstFile,
+        path: &mut Vec<FileName>,
+        errors: &mut Vec<Diagnostic>,
+    ) -> HashSet<Key> {
+        let mut decorators = HashSet::new();
+        for ent in ast_file.0.iter() {
+            if let FileEntity::Import(i) = ent {
+                let i = libs::qualify(file, i.clone());
+                let imported = i.f_name().to_string();
+                if path.contains(&imported) {
+                    continue;
+                }
+                if let Err(e) = self.parse_file(root.clone(), imported.clone(), path, errors) {
+                    let msg = match e {
+                        TreeError::IOError(_) => format!("the file {imported} can not be read"),
+                        e => e.to_string(),
+                    };
+                    errors.push(Diagnostic::new(file, src, i.2, msg));
+                    continue;
+                }
+                let Some(imported) = self.files.get(&imported) else {
+                    continue;
+                };
+                let is_decorator = |name: &String| {
+                    imported
+                        .definitions
+                        .get(name)
+                        .map(|t| t.tpe.is_custom_decorator())
+                        .unwrap_or(false)
+                };
+                for name in i.1.iter() {
+                    match name {
+                        ImportName::Id(id) if is_decorator(id) => {
+                            decorators.insert(id.clone());
+                        }
+                        ImportName::Alias(id, alias) if is_decorator(id) => {
+                            decorators.insert(alias.clone());
+                        }
+                        ImportName::WholeFile => decorators.extend(
+                            imported
+                                .definitions
+                                .values()
+                                .filter(|t| t.tpe.is_custom_decorator())
+                                .map(|t| t.name.clone()),
+                        ),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        decorators
+    }
 }
-fn file_to_str<'a>(root: PathBuf, file: FileName) -> Result<String, TreeError> {
+fn file_to_str<'a>(root: PathBuf, libs: &Libs, file: FileName) -> Result<String, TreeError> {
     if file == "std::actions" {
         Ok(BuilderBuiltInActions::builtin_actions_file())
+    } else if let Some((pkg, path)) = libs::package(file.as_str()) {
+        Ok(read_file(&libs.find(pkg, path)?)?)
     } else {
         let mut path = root;
         path.push(file.clone());
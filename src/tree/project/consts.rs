@@ -0,0 +1,120 @@
+use crate::tree::parser::ast::arg::{Argument, ArgumentRhs, Arguments};
+use crate::tree::parser::ast::call::{Call, Calls};
+use crate::tree::parser::ast::expr::Expr;
+use crate::tree::parser::ast::message::Message;
+use crate::tree::parser::ast::Key;
+use crate::tree::project::file::File;
+use crate::tree::project::imports::ImportMap;
+use crate::tree::project::{FileName, Project};
+use crate::tree::TreeError;
+use std::collections::{HashMap, HashSet};
+
+/// Replaces the constants in the arguments of the calls with their values.
+/// The constant is visible in the file it is declared in and in the files importing it.
+/// The parameter of the enclosing tree shadows the constant with the same name.
+pub fn inline_consts(project: &mut Project) -> Result<(), TreeError> {
+    let mut visible: HashMap<FileName, HashMap<Key, Message>> = HashMap::new();
+    for file in project.files.values() {
+        visible.insert(file.name.clone(), visible_consts(file, project)?);
+    }
+
+    for file in project.files.values_mut() {
+        let consts = &visible[&file.name];
+        if consts.is_empty() {
+            continue;
+        }
+        for tree in file.definitions.values_mut() {
+            let inliner = Inliner {
+                consts,
+                params: tree.params.params.iter().map(|p| p.name.clone()).collect(),
+            };
+            inliner.calls(&mut tree.calls);
+        }
+    }
+    Ok(())
+}
+
+/// the constants declared in the file and imported into it
+fn visible_consts(file: &File, project: &Project) -> Result<HashMap<Key, Message>, TreeError> {
+    let import_map = ImportMap::build(file)?;
+    let mut consts = HashMap::new();
+    for f in import_map.files.iter().flat_map(|f| project.files.get(f)) {
+        consts.extend(f.consts.values().map(|c| (c.name.clone(), c.value.clone())));
+    }
+    for name in import_map.trees.keys().chain(import_map.aliases.keys()) {
+        if let Some(c) = import_map.find_const(name, project) {
+            consts.insert(name.clone(), c.value.clone());
+        }
+    }
+    consts.extend(
+        file.consts
+            .values()
+            .map(|c| (c.name.clone(), c.value.clone())),
+    );
+    Ok(consts)
+}
+
+struct Inliner<'a> {
+    consts: &'a HashMap<Key, Message>,
+    params: HashSet<Key>,
+}
+
+impl<'a> Inliner<'a> {
+    fn value(&self, id: &Key) -> Option<Message> {
+        if self.params.contains(id) {
+            None
+        } else {
+            self.consts.get(id).cloned()
+        }
+    }
+
+    fn calls(&self, calls: &mut Calls) {
+        for call in calls.elems.iter_mut() {
+            self.call(call);
+        }
+    }
+
+    fn call(&self, call: &mut Call) {
+        match call {
+            Call::Invocation(_, args, _) => self.args(args),
+            Call::HoInvocation(_, _) => {}
+            Call::Lambda(_, calls, _) => self.calls(calls),
+            Call::Decorator(_, args, call, _) | Call::CustomDecorator(_, args, call, _) => {
+                self.args(args);
+                self.call(call);
+            }
+        }
+    }
+
+    fn args(&self, args: &mut Arguments) {
+        for a in args.args.iter_mut() {
+            let (Argument::Assigned(_, rhs, _) | Argument::Unassigned(rhs, _)) = a;
+            match rhs {
+                ArgumentRhs::Id(id) => {
+                    if let Some(m) = self.value(id) {
+                        *rhs = ArgumentRhs::Mes(m);
+                    }
+                }
+                ArgumentRhs::Expr(e) => self.expr(e),
+                ArgumentRhs::Call(call) => self.call(call),
+                ArgumentRhs::Mes(_) | ArgumentRhs::BBRef(_) => {}
+            }
+        }
+    }
+
+    fn expr(&self, expr: &mut Expr) {
+        match expr {
+            Expr::Id(id) => {
+                if let Some(m) = self.value(id) {
+                    *expr = Expr::Mes(m);
+                }
+            }
+            Expr::Mes(_) => {}
+            Expr::Not(e) | Expr::Neg(e) => self.expr(e),
+            Expr::Binary(l, _, r) => {
+                self.expr(l);
+                self.expr(r);
+            }
+        }
+    }
+}
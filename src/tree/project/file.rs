@@ -1,4 +1,5 @@
-use crate::tree::parser::ast::{Import, ImportName, Tree};
+use crate::tree::diagnostic::Span;
+use crate::tree::parser::ast::{Const, Enum, Import, ImportName, Key, Tree};
 use crate::tree::project::{FileName, TreeName};
 use crate::tree::TreeError;
 use std::collections::{HashMap, HashSet};
@@ -8,7 +9,11 @@ use std::collections::{HashMap, HashSet};
 pub struct File {
     pub name: String,
     pub imports: HashMap<FileName, HashSet<ImportName>>,
+    /// the position of the first import statement of every imported file
+    pub import_spans: HashMap<FileName, Span>,
     pub definitions: HashMap<TreeName, Tree>,
+    pub consts: HashMap<Key, Const>,
+    pub enums: HashMap<Key, Enum>,
 }
 
 impl File {
@@ -16,11 +21,17 @@ impl File {
         File {
             name,
             imports: Default::default(),
+            import_spans: Default::default(),
             definitions: Default::default(),
+            consts: Default::default(),
+            enums: Default::default(),
         }
     }
 
     pub fn add_import(&mut self, import: Import) -> Result<(), TreeError> {
+        self.import_spans
+            .entry(import.0.clone())
+            .or_insert(import.2);
         self.imports
             .entry(import.0.clone())
             .and_modify(|names| names.extend(import.1.clone()))
@@ -28,6 +39,12 @@ impl File {
         Ok(())
     }
     pub fn add_def(&mut self, tree: Tree) -> Result<(), TreeError> {
+        if self.consts.contains_key(&tree.name) || self.enums.contains_key(&tree.name) {
+            return Err(TreeError::ParseError(format!(
+                "the name '{}' is already presented",
+                tree.name
+            )));
+        }
         match self.definitions.get(&tree.name) {
             None => {
                 self.definitions.insert(tree.clone().name, tree);
@@ -40,4 +57,37 @@ impl File {
             ))),
         }
     }
+
+    pub fn add_const(&mut self, c: Const) -> Result<(), TreeError> {
+        self.check_name(&c.name)?;
+        self.consts.insert(c.name.clone(), c);
+        Ok(())
+    }
+
+    pub fn add_enum(&mut self, e: Enum) -> Result<(), TreeError> {
+        self.check_name(&e.name)?;
+        let mut variants = HashSet::new();
+        if let Some(v) = e.variants.iter().find(|v| !variants.insert(*v)) {
+            return Err(TreeError::ParseError(format!(
+                "the variant '{v}' is presented twice in the enum '{}'",
+                e.name
+            )));
+        }
+        self.enums.insert(e.name.clone(), e);
+        Ok(())
+    }
+
+    /// the constants and the enums share the names with the trees
+    fn check_name(&self, name: &Key) -> Result<(), TreeError> {
+        if self.definitions.contains_key(name)
+            || self.consts.contains_key(name)
+            || self.enums.contains_key(name)
+        {
+            Err(TreeError::ParseError(format!(
+                "the name '{name}' is already presented"
+            )))
+        } else {
+            Ok(())
+        }
+    }
 }
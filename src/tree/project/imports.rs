@@ -1,8 +1,13 @@
-use crate::tree::parser::ast::{ImportName, Tree};
+use crate::tree::diagnostic::{Diagnostic, Span};
+use crate::tree::parser::ast::{Const, Enum, ImportName, Key, Tree};
 use crate::tree::project::{AliasName, File, FileName, Project, TreeName};
 use crate::tree::{cerr, TreeError};
+use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
+/// the file of the built-in actions
+pub(crate) const STD: &str = "std::actions";
+
 /// reordering by tree definition
 #[derive(Default)]
 pub struct ImportMap {
@@ -14,8 +19,7 @@ pub struct ImportMap {
 impl ImportMap {
     /// processes the imports checking there is no crossing between aliases and definitions
     /// ## Note
-    /// For now, when the import of the whole file there is no validations on crossings and other things.
-    /// Thus, better off to perform imports only for the used definitions.
+    /// The crossings of the names imported with the whole files are checked in [`check_conflicts`].
     pub fn build(file: &File) -> Result<Self, TreeError> {
         let mut map = ImportMap::default();
         for (file, items) in &file.imports {
@@ -83,4 +87,102 @@ impl ImportMap {
                 )))
         }
     }
+
+    /// the imported constant, either by name, by alias or with the whole file
+    pub fn find_const<'a>(&'a self, key: &Key, project: &'a Project) -> Option<&'a Const> {
+        self.lookup(key, project, |f, k| f.consts.get(k))
+    }
+
+    /// the imported enum, either by name, by alias or with the whole file
+    pub fn find_enum<'a>(&'a self, key: &Key, project: &'a Project) -> Option<&'a Enum> {
+        self.lookup(key, project, |f, k| f.enums.get(k))
+    }
+
+    fn lookup<'a, T, G>(&'a self, key: &Key, project: &'a Project, get: G) -> Option<&'a T>
+    where
+        G: Fn(&'a File, &Key) -> Option<&'a T>,
+    {
+        let id = self.aliases.get(key).unwrap_or(key);
+        match self.trees.get(id) {
+            Some(file) => project.files.get(file).and_then(|f| get(f, id)),
+            None => self
+                .files
+                .iter()
+                .flat_map(|f| project.files.get(f))
+                .find_map(|f| get(f, key)),
+        }
+    }
+}
+
+/// checks the imported names do not clash in every file of the project:
+///  - the whole files imported into the same file do not define the same name
+///  - the imported names and aliases do not collide with the definitions of the file
+///    except the built-in actions from `std::actions`
+pub fn check_conflicts(project: &Project) -> Vec<Diagnostic> {
+    let mut errors = vec![];
+    for file in project.files.values().sorted_by_key(|f| &f.name) {
+        let src = project
+            .sources
+            .get(&file.name)
+            .map(String::as_str)
+            .unwrap_or_default();
+        let error = |span: Span, msg: String| Diagnostic::new(&file.name, src, span, msg);
+        // the imported name and the file it comes from
+        let mut imported: HashMap<&Key, &FileName> = HashMap::new();
+
+        for f_name in file.imports.keys().sorted() {
+            let names = &file.imports[f_name];
+            let span = file.import_spans.get(f_name).copied().unwrap_or_default();
+            if names.contains(&ImportName::WholeFile) {
+                if let Some(f) = project.files.get(f_name) {
+                    for name in local_names(f).sorted() {
+                        match imported.get(name) {
+                            Some(other) if *other != f_name => errors.push(error(
+                                span,
+                                format!("the name {name} is defined in both imported files {other} and {f_name}"),
+                            )),
+                            _ => {
+                                imported.insert(name, f_name);
+                            }
+                        }
+                    }
+                }
+            }
+            for name in names.iter() {
+                match name {
+                    ImportName::Id(name) | ImportName::Alias(_, name) => {
+                        imported.insert(name, f_name);
+                    }
+                    ImportName::WholeFile => {}
+                }
+            }
+        }
+
+        // the local definitions are allowed to override the built-in actions
+        for (name, f_name) in imported.into_iter().filter(|(_, f)| *f != STD).sorted() {
+            if let Some(span) = local_span(file, name) {
+                errors.push(error(
+                    span,
+                    format!("the definition {name} conflicts with the name imported from the file {f_name}"),
+                ));
+            }
+        }
+    }
+    errors
+}
+
+/// the names of the trees, the constants and the enums defined in the file
+pub(crate) fn local_names(file: &File) -> impl Iterator<Item = &Key> {
+    file.definitions
+        .keys()
+        .chain(file.consts.keys())
+        .chain(file.enums.keys())
+}
+
+fn local_span(file: &File, name: &Key) -> Option<Span> {
+    file.definitions
+        .get(name)
+        .map(|t| t.span)
+        .or(file.consts.get(name).map(|c| c.span))
+        .or(file.enums.get(name).map(|e| e.span))
 }
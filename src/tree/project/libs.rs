@@ -0,0 +1,95 @@
+use crate::tree::parser::ast::Import;
+use crate::tree::project::FileName;
+use crate::tree::{cerr, TreeError};
+use crate::{get_pb, read_file};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// The separator between the package and the path in the import: `import "nav::follow.tree"`
+const PKG_SEP: &str = "::";
+
+/// The name of the manifest file of the project, see [`Manifest`](crate::manifest::Manifest).
+pub const MANIFEST: &str = "forester.toml";
+
+/// The part of the manifest the project reads itself: `libs = ["../libs"]`.
+/// The rest of the manifest belongs to the tools built on top of the project.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct ManifestLibs {
+    /// the library paths relating to the folder of the manifest, see [`Libs`]
+    #[serde(default)]
+    pub libs: Vec<PathBuf>,
+}
+
+impl ManifestLibs {
+    /// the library paths resolved against the folder of the manifest
+    pub fn resolve(&self, dir: &PathBuf) -> Libs {
+        Libs {
+            paths: self
+                .libs
+                .iter()
+                .map(|p| get_pb(&p.to_string_lossy().to_string(), dir.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// The search paths of the libraries.
+/// Every library path is a folder with the packages,
+/// and every package is a folder with the tree files.
+/// ```text
+/// libs
+/// └── nav
+///     ├── follow.tree
+///     └── utils.tree
+/// ```
+/// The file `follow.tree` is imported as `import "nav::follow.tree"`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Libs {
+    pub paths: Vec<PathBuf>,
+}
+
+impl Libs {
+    /// reads the library paths from the manifest file in the root if the file exists.
+    pub fn from_manifest(root: &PathBuf) -> Result<Self, TreeError> {
+        let file = root.join(MANIFEST);
+        if !file.exists() {
+            return Ok(Libs::default());
+        }
+        let manifest: ManifestLibs = toml::from_str(read_file(&file)?.as_str())
+            .map_err(|e| TreeError::IOError(format!("the manifest {MANIFEST} is invalid: {e}")))?;
+        Ok(manifest.resolve(root))
+    }
+
+    pub fn add(&mut self, path: PathBuf) {
+        if !self.paths.contains(&path) {
+            self.paths.push(path);
+        }
+    }
+
+    /// finds the file of the package in the first library path containing the package.
+    pub fn find(&self, pkg: &str, path: &str) -> Result<PathBuf, TreeError> {
+        self.paths
+            .iter()
+            .map(|lib| lib.join(pkg))
+            .find(|p| p.is_dir())
+            .map(|p| p.join(path))
+            .ok_or(cerr(format!(
+                "the package {pkg} is not found in the library paths {:?}",
+                self.paths
+            )))
+    }
+}
+
+/// splits the import into the package and the path in it, if the import refers to a package.
+pub fn package(file: &str) -> Option<(&str, &str)> {
+    file.split_once(PKG_SEP)
+}
+
+/// The imports in the file of a package are relative to the package.
+/// Thus `import "utils.tree"` in the file `nav::follow.tree` refers to `nav::utils.tree`.
+pub fn qualify(importer: &FileName, import: Import) -> Import {
+    match (package(importer), package(import.f_name())) {
+        (Some((pkg, _)), None) => Import(format!("{pkg}{PKG_SEP}{}", import.0), import.1, import.2),
+        _ => import,
+    }
+}
@@ -0,0 +1,311 @@
+use crate::tree::diagnostic::{Diagnostic, Span};
+use crate::tree::parser::ast::arg::{
+    Argument, ArgumentRhs, Arguments, ArgumentsType, MesType, Param, Params,
+};
+use crate::tree::parser::ast::call::Call;
+use crate::tree::parser::ast::message::Message;
+use crate::tree::parser::ast::{Enum, Key, Tree};
+use crate::tree::project::file::File;
+use crate::tree::project::imports::ImportMap;
+use crate::tree::project::Project;
+use crate::tree::TreeError;
+use itertools::Itertools;
+
+/// Checks the arguments of every invocation in the project against the parameters of the definitions:
+///  - the number of the arguments and the names of the named arguments
+///  - the literals against the types of the parameters including the elements of the arrays (`array<num>`)
+///  - the pointers to the parameters of the enclosing tree against the types of the parameters
+///  - the result of the expressions against the types of the parameters if it is known
+///  - the calls are passed only to the parameters of the type `tree`
+///    and only the parameters of the type `tree` are invoked as `param(..)`
+///  - the output parameters get only the references to the blackboard
+///  - the enums used in the arguments are declared and have the given variants
+///
+/// The types of the parameters are checked when the project is built, see [`check_param_types`].
+/// All errors are collected and reported at once, every error points to the call or the argument in the source.
+pub fn check_types(project: &Project) -> Result<(), TreeError> {
+    let mut errors = vec![];
+    for file in project.files.values().sorted_by_key(|f| &f.name) {
+        let import_map = ImportMap::build(file)?;
+        for tree in file.definitions.values().sorted_by_key(|t| &t.name) {
+            let scope = Scope {
+                project,
+                file,
+                src: project
+                    .sources
+                    .get(&file.name)
+                    .map(String::as_str)
+                    .unwrap_or_default(),
+                import_map: &import_map,
+                tree,
+            };
+            for call in tree.calls.elems.iter() {
+                scope.check_call(call, &mut errors);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(TreeError::Diagnostics(errors))
+    }
+}
+
+/// Checks the types of the parameters in every definition of the project.
+/// The parser takes every name that is not a built-in type for an enum,
+/// thus the name should be an enum declared in the file or imported into it.
+pub fn check_param_types(project: &Project) -> Vec<Diagnostic> {
+    let mut errors = vec![];
+    for file in project.files.values().sorted_by_key(|f| &f.name) {
+        let Ok(import_map) = ImportMap::build(file) else {
+            continue;
+        };
+        let src = project
+            .sources
+            .get(&file.name)
+            .map(String::as_str)
+            .unwrap_or_default();
+        for tree in file.definitions.values().sorted_by_key(|t| &t.name) {
+            for p in tree.params.params.iter() {
+                let mut tpe = &p.tpe;
+                while let MesType::ArrayOf(elem) = tpe {
+                    tpe = elem;
+                }
+                if let MesType::Enum(name) = tpe {
+                    let declared = file.enums.contains_key(name)
+                        || import_map.find_enum(name, project).is_some();
+                    if !declared {
+                        let msg = format!("the type {name} of the parameter {} is unknown", p.name);
+                        errors.push(Diagnostic::new(&file.name, src, tree.span, msg));
+                    }
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// the tree where the calls are checked
+struct Scope<'a> {
+    project: &'a Project,
+    file: &'a File,
+    src: &'a str,
+    import_map: &'a ImportMap,
+    tree: &'a Tree,
+}
+
+impl<'a> Scope<'a> {
+    fn error(&self, errors: &mut Vec<Diagnostic>, span: Span, msg: String) {
+        errors.push(Diagnostic::new(&self.file.name, self.src, span, msg))
+    }
+
+    fn find_definition(&self, name: &String) -> Result<&'a Tree, TreeError> {
+        match self.file.definitions.get(name) {
+            Some(tree) => Ok(tree),
+            None => self.import_map.find(name, self.project).map(|(t, _)| t),
+        }
+    }
+
+    fn param(&self, name: &String) -> Option<&'a Param> {
+        self.tree.params.params.iter().find(|p| &p.name == name)
+    }
+
+    fn find_enum(&self, name: &Key) -> Option<&'a Enum> {
+        self.file
+            .enums
+            .get(name)
+            .or_else(|| self.import_map.find_enum(name, self.project))
+    }
+
+    /// the variants of the enums in the message are declared
+    fn check_message(&self, m: &Message, span: Span, errors: &mut Vec<Diagnostic>) {
+        match m {
+            Message::Enum(tpe, variant) => match self.find_enum(tpe) {
+                Some(e) if e.has(variant) => {}
+                Some(_) => self.error(
+                    errors,
+                    span,
+                    format!("the enum {tpe} does not have the variant {variant}"),
+                ),
+                None => self.error(errors, span, format!("the enum {tpe} is not declared")),
+            },
+            Message::Array(elems) => {
+                for e in elems {
+                    self.check_message(e, span, errors);
+                }
+            }
+            Message::Object(elems) => {
+                for e in elems.values() {
+                    self.check_message(e, span, errors);
+                }
+            }
+            Message::Num(_) | Message::String(_) | Message::Bool(_) => {}
+        }
+    }
+
+    fn check_call(&self, call: &Call, errors: &mut Vec<Diagnostic>) {
+        let span = call.span();
+        match call {
+            Call::Invocation(name, args, _) => match self.find_definition(name) {
+                Ok(def) => self.check_args(name, span, args, &def.params, errors),
+                Err(e) => self.error(errors, span, tree_error_msg(e)),
+            },
+            Call::HoInvocation(key, _) => match self.param(key) {
+                Some(p) if p.tpe == MesType::Tree => {}
+                Some(p) => self.error(
+                    errors,
+                    span,
+                    format!(
+                        "the parameter {key} has the type {} and can not be invoked",
+                        p.tpe
+                    ),
+                ),
+                None => self.error(
+                    errors,
+                    span,
+                    format!(
+                        "the invocation {key}(..) does not refer to a parameter of the type tree"
+                    ),
+                ),
+            },
+            Call::Lambda(_, calls, _) => {
+                for call in calls.elems.iter() {
+                    self.check_call(call, errors);
+                }
+            }
+            Call::Decorator(_, _, call, _) => self.check_call(call, errors),
+            Call::CustomDecorator(name, args, call, _) => {
+                match self.find_definition(name) {
+                    Ok(def) => self.check_args(name, span, args, &def.params, errors),
+                    Err(e) => self.error(errors, span, tree_error_msg(e)),
+                }
+                self.check_call(call, errors);
+            }
+        }
+    }
+
+    fn check_args(
+        &self,
+        name: &String,
+        span: Span,
+        args: &Arguments,
+        params: &Params,
+        errors: &mut Vec<Diagnostic>,
+    ) {
+        let required = params.params.iter().filter(|p| p.is_required()).count();
+        if args.args.len() > params.params.len() || args.args.len() < required {
+            self.error(
+                errors,
+                span,
+                format!("the call {name} doesn't have the same number of arguments and parameters"),
+            );
+            return;
+        }
+        match args.get_type() {
+            Err(e) => self.error(
+                errors,
+                span,
+                format!("the call {name}: {}", tree_error_msg(e)),
+            ),
+            Ok(ArgumentsType::Unnamed) => {
+                for (a, p) in args.args.iter().zip(params.params.iter()) {
+                    self.check_arg(name, a, p, errors);
+                }
+            }
+            Ok(ArgumentsType::Named) => {
+                for a in args.args.iter() {
+                    match a.name().and_then(|n| params.params.iter().find(|p| &p.name == n)) {
+                        Some(p) => self.check_arg(name, a, p, errors),
+                        None => self.error(
+                            errors,
+                            a.span(),
+                            format!("the argument {a} of the call {name} does not correspond to the definition"),
+                        ),
+                    }
+                }
+                for p in params.params.iter().filter(|p| p.is_required()) {
+                    if !args.args.iter().any(|a| a.has_name(&p.name)) {
+                        self.error(
+                            errors,
+                            span,
+                            format!(
+                                "the call {name} does not have the argument for the parameter {}",
+                                p.name
+                            ),
+                        );
+                    }
+                }
+            }
+            Ok(ArgumentsType::Empty) => {}
+        }
+    }
+
+    fn check_arg(&self, name: &String, a: &Argument, param: &Param, errors: &mut Vec<Diagnostic>) {
+        let span = a.span();
+        let arg = a.value();
+        let mismatch = |tpe: String| {
+            format!(
+                "the argument {arg} of the call {name} has the type {tpe} but the parameter {} has the type {}",
+                param.name, param.tpe
+            )
+        };
+        if param.output {
+            match arg {
+                ArgumentRhs::BBRef(_) | ArgumentRhs::Id(_) => {}
+                _ => self.error(
+                    errors,
+                    span,
+                    format!(
+                        "the output parameter {} of the call {name} expects a reference to the blackboard but got {arg}",
+                        param.name
+                    ),
+                ),
+            }
+            return;
+        }
+        if let ArgumentRhs::Mes(m) = arg {
+            self.check_message(m, span, errors);
+        }
+        match arg {
+            ArgumentRhs::Mes(m) if !m.same(&param.tpe) => {
+                self.error(errors, span, mismatch(m.tpe().to_string()))
+            }
+            ArgumentRhs::Mes(_) => {}
+            ArgumentRhs::Call(call) if param.tpe == MesType::Tree => self.check_call(call, errors),
+            ArgumentRhs::Call(_) => self.error(errors, span, mismatch("tree".to_string())),
+            ArgumentRhs::Id(id) => match self.param(id) {
+                Some(p) if !p.tpe.fits(&param.tpe) => {
+                    self.error(errors, span, mismatch(p.tpe.to_string()))
+                }
+                _ if param.tpe == MesType::Tree => {
+                    self.error(errors, span, mismatch("pointer".to_string()))
+                }
+                _ => {}
+            },
+            ArgumentRhs::BBRef(_) if param.tpe == MesType::Tree => {
+                self.error(errors, span, mismatch("reference".to_string()))
+            }
+            ArgumentRhs::Expr(_) if param.tpe == MesType::Tree => {
+                self.error(errors, span, mismatch("expression".to_string()))
+            }
+            ArgumentRhs::Expr(e) => match e.tpe() {
+                Some(tpe) if !tpe.fits(&param.tpe) => {
+                    self.error(errors, span, mismatch(tpe.to_string()))
+                }
+                _ => {}
+            },
+            ArgumentRhs::BBRef(_) => {}
+        }
+    }
+}
+
+fn tree_error_msg(e: TreeError) -> String {
+    match e {
+        TreeError::ParseError(s)
+        | TreeError::CompileError(s)
+        | TreeError::VisualizationError(s)
+        | TreeError::IOError(s) => s,
+        TreeError::Diagnostics(ds) => ds.into_iter().map(|d| d.message).join("; "),
+    }
+}
@@ -62,7 +62,17 @@ impl<'a> Visualizer {
 
         while let Some(id) = stack.pop_front() {
             if let Some(node) = runtime_tree.nodes.get(&id) {
-                graph.add_stmt(node.to_stmt(id.to_string()));
+                let mut tooltip = runtime_tree
+                    .meta
+                    .get(&id)
+                    .map(|m| format!("{} at {}", m.path, m.location))
+                    .unwrap_or_default();
+                let origins = runtime_tree.origins(&id);
+                if origins.len() > 1 {
+                    let folded = origins.iter().filter(|o| **o != id).join(", ");
+                    tooltip = format!("{tooltip}, folds the nodes {folded}");
+                }
+                graph.add_stmt(node.to_stmt(id.to_string(), tooltip));
                 match node {
                     RNode::Leaf(_, _) => {}
                     RNode::Flow(_, _, _, children) => {
@@ -9,7 +9,8 @@ use itertools::Itertools;
 use std::fmt::format;
 
 pub trait ToStmt {
-    fn to_stmt(&self, id: String) -> Stmt;
+    /// the tooltip shows the path of the node and the place in the source
+    fn to_stmt(&self, id: String, tooltip: String) -> Stmt;
 }
 
 fn name_to_label(name: &RNodeName) -> String {
@@ -21,7 +22,8 @@ fn name_to_label(name: &RNodeName) -> String {
 }
 
 impl ToStmt for RNode {
-    fn to_stmt(&self, id: String) -> Stmt {
+    fn to_stmt(&self, id: String, tooltip: String) -> Stmt {
+        let tooltip = NodeAttributes::tooltip(format!("\"{tooltip}\""));
         match self {
             RNode::Leaf(name, args) => {
                 let label = NodeAttributes::label(format!(
@@ -33,7 +35,7 @@ impl ToStmt for RNode {
                 let color = NodeAttributes::color(color_name::green);
                 let shape = NodeAttributes::shape(shape::component);
 
-                stmt!(node!(id.as_str(); label, shape, color))
+                stmt!(node!(id.as_str(); label, shape, color, tooltip))
             }
             RNode::Flow(t, name, args, _) => {
                 let color = flow_color(t);
@@ -48,7 +50,7 @@ impl ToStmt for RNode {
                     NodeAttributes::label(format!("\"({}) {}\n{} {}\"", id, t, name_s, args_s))
                 };
 
-                stmt!(node!(id.as_str(); label,shape,color))
+                stmt!(node!(id.as_str(); label, shape, color, tooltip))
             }
             RNode::Decorator(t, args, _) => {
                 let label = NodeAttributes::label(format!(
@@ -59,7 +61,7 @@ impl ToStmt for RNode {
                 ));
                 let color = NodeAttributes::color(color_name::purple);
                 let shape = NodeAttributes::shape(shape::tab);
-                stmt!(node!(id.as_str(); label,shape,color))
+                stmt!(node!(id.as_str(); label, shape, color, tooltip))
             }
         }
     }
@@ -2,10 +2,12 @@ use std::ffi::OsString;
 use std::path::PathBuf;
 
 use clap::{arg, value_parser, ArgMatches, Command};
-use forester::runtime::action::Tick;
-use forester::runtime::RtResult;
-use forester::simulator::builder::SimulatorBuilder;
-use forester::visualizer::Visualizer;
+use forester::btcpp;
+use forester::manifest::Manifest;
+use forester::runtime::{RtResult, RuntimeError};
+use forester::tree::parser::parse_file;
+use forester::tree::printer;
+use forester::tree::TreeError;
 
 #[macro_use]
 extern crate log;
@@ -19,11 +21,11 @@ fn cli() -> Command {
         .subcommand(
             Command::new("sim")
                 .about(r#"Runs simulation. Expects a simulation profile"#)
-                .arg(arg!(-p --profile <PATH> "a path to a sim profile"))
+                .arg(arg!(-p --profile <PATH> "a path to a sim profile or the name of the profile in the manifest"))
                 .arg(arg!(-r --root <ROOT> "a path to a root folder. The <PWD> folder by default"))
                 .arg(arg!(-m --main <MAIN> "a path to a main file. The 'main.tree' by default"))
                 .arg(arg!(-t --tree <TREE> "a root in a main file. If there is only one root it takes by default"))
-                .arg_required_else_help(true)
+                .arg(arg!(-c --manifest <MANIFEST> "a path to a manifest. The 'forester.toml' in the root folder by default"))
         )
         .subcommand(
             Command::new("vis")
@@ -32,7 +34,35 @@ fn cli() -> Command {
                 .arg(arg!(-r --root <ROOT> "a path to a root folder. The <PWD> folder by default"))
                 .arg(arg!(-m --main <MAIN> "a path to a main file. The 'main.tree' by default"))
                 .arg(arg!(-t --tree <TREE> "a root in a main file. If there is only one root it takes by default"))
-                .arg_required_else_help(true)
+                .arg(arg!(-c --manifest <MANIFEST> "a path to a manifest. The 'forester.toml' in the root folder by default"))
+        )
+        .subcommand(
+            Command::new("export")
+                .about(r#"Exports the tree to the BehaviorTree.CPP xml format."#)
+                .arg(arg!(-p --output <OUTPUT> "a file for xml. If  no, the name from the main file will be taken."))
+                .arg(arg!(-r --root <ROOT> "a path to a root folder. The <PWD> folder by default"))
+                .arg(arg!(-m --main <MAIN> "a path to a main file. The 'main.tree' by default"))
+                .arg(arg!(-t --tree <TREE> "a root in a main file. If there is only one root it takes by default"))
+                .arg(arg!(-c --manifest <MANIFEST> "a path to a manifest. The 'forester.toml' in the root folder by default"))
+        )
+        .subcommand(
+            Command::new("import")
+                .about(r#"Imports the BehaviorTree.CPP xml into the tree file."#)
+                .arg(arg!(<INPUT> "a path to the xml file"))
+                .arg(arg!(-p --output <OUTPUT> "a file for the tree. If  no, the name of the xml file will be taken."))
+        )
+        .subcommand(
+            Command::new("fmt")
+                .about(r#"Formats the tree files keeping the comments."#)
+                .arg(arg!([PATH]... "the files or the folders to format. The <PWD> folder by default"))
+                .arg(arg!(--check "prints the files that are not formatted instead of writing them"))
+        )
+        .subcommand(
+            Command::new("lint")
+                .about(r#"Runs the static analysis of every root in the project and prints the warnings."#)
+                .arg(arg!(-r --root <ROOT> "a path to a root folder. The <PWD> folder by default"))
+                .arg(arg!(-m --main <MAIN> "a path to a main file. The 'main.tree' by default"))
+                .arg(arg!(-c --manifest <MANIFEST> "a path to a manifest. The 'forester.toml' in the root folder by default"))
         )
 }
 
@@ -47,86 +77,226 @@ fn buf(val: &str, relative: PathBuf) -> PathBuf {
     }
 }
 
+/// The manifest from the option or from the root folder.
+/// Without the manifest file, the root folder is taken as the folder of the empty manifest.
+/// The options of the command line override the manifest.
+fn manifest(matches: &ArgMatches) -> RtResult<Manifest> {
+    let pwd = std::env::current_dir().expect("the current directory is presented");
+    let root = matches
+        .get_one::<String>("root")
+        .map(|r| buf(r.as_str(), pwd.clone()));
+
+    let mut manifest = match matches.get_one::<String>("manifest") {
+        Some(m) => Manifest::load(&buf(m.as_str(), pwd))?,
+        None => {
+            let dir = root.clone().unwrap_or(pwd);
+            Manifest::find(&dir)?.unwrap_or(Manifest {
+                dir,
+                ..Manifest::default()
+            })
+        }
+    };
+    if let Some(root) = root {
+        manifest.root = Some(root);
+    }
+    if let Some(main) = matches.get_one::<String>("main") {
+        manifest.main = Some(main.to_string());
+    }
+    // the lint analyzes every root, so it has no option for the tree
+    if let Some(tree) = matches.try_get_one::<String>("tree").ok().flatten() {
+        manifest.tree = Some(tree.to_string());
+    }
+    Ok(manifest)
+}
+
 fn sim(matches: &ArgMatches) {
-    if let Some(p) = matches.get_one::<String>("profile") {
-        let pwd = std::env::current_dir().expect("the current directory is presented");
+    let simulator = manifest(matches).and_then(|mut m| {
+        match matches.get_one::<String>("profile") {
+            // the profile is either the name in the manifest or the path to the file
+            Some(p) if !m.simulation.contains_key(p) => {
+                m.simulation.clear();
+                m.simulation
+                    .insert("cli".to_string(), buf(p.as_str(), m.root()));
+                m.simulator(Some("cli"))
+            }
+            p => m.simulator(p.map(String::as_str)),
+        }
+    });
+
+    match simulator {
+        Ok(mut s) => match s.run() {
+            Ok(r) => {
+                info!("the process is finished with the result: {:?}", r)
+            }
+            Err(err) => {
+                error!("the runtime error occured : {:?}", err)
+            }
+        },
+        Err(RuntimeError::CompileError(err)) => {
+            error!("the building error occured:\n{}", err)
+        }
+        Err(err) => {
+            error!("the building error occured: {:?}", err)
+        }
+    }
+}
+
+fn vis(matches: &ArgMatches) {
+    let result = manifest(matches).and_then(|mut m| {
+        if let Some(output) = matches.get_one::<String>("output") {
+            m.output.svg = Some(PathBuf::from(output));
+        }
+        Ok(m.visualize()?)
+    });
+    match result {
+        Ok(_) => info!("the visualization is finished"),
+        Err(RuntimeError::CompileError(err)) => {
+            error!("the building error occured:\n{}", err)
+        }
+        Err(err) => {
+            error!("the visualization error occured: {:?}", err)
+        }
+    }
+}
 
-        let root = match matches.get_one::<String>("root") {
-            Some(root) => buf(root.as_str(), pwd),
-            None => pwd,
+fn export(matches: &ArgMatches) {
+    let result = manifest(matches).and_then(|m| {
+        let file = match matches.get_one::<String>("output") {
+            Some(output) => m.output(&PathBuf::from(output)),
+            None => m.output(&PathBuf::from(m.main_file()).with_extension("xml")),
         };
+        std::fs::write(&file, m.export()?)?;
+        Ok(file)
+    });
+    match result {
+        Ok(file) => info!("the tree is exported to {:?}", file),
+        Err(RuntimeError::CompileError(err)) => {
+            error!("the building error occured:\n{}", err)
+        }
+        Err(err) => {
+            error!("the export error occured: {:?}", err)
+        }
+    }
+}
 
-        let sim = buf(p, root.clone());
-        let main_file = matches
-            .get_one::<String>("main")
-            .map(|v| v.to_string())
-            .unwrap_or("main.tree".to_string());
-        let main_tree = matches.get_one::<String>("tree");
-
-        let mut sb = SimulatorBuilder::new();
-        sb.profile(sim);
-        sb.root(root);
-        sb.main_file(main_file);
-        if main_tree.is_some() {
-            sb.main_tree(main_tree.unwrap().to_string())
-        }
-
-        match sb.build() {
-            Ok(mut s) => match s.run() {
-                Ok(r) => {
-                    info!("the process is finished with the result: {:?}", r)
-                }
-                Err(err) => {
-                    error!("the runtime error occured : {:?}", err)
-                }
-            },
-            Err(err) => {
-                error!("the building error occured: {:?}", err)
+fn import(matches: &ArgMatches) {
+    let input = PathBuf::from(
+        matches
+            .get_one::<String>("INPUT")
+            .expect("the input is required"),
+    );
+    let output = match matches.get_one::<String>("output") {
+        Some(output) => PathBuf::from(output),
+        None => input.with_extension("tree"),
+    };
+    let result: RtResult<()> = std::fs::read_to_string(&input)
+        .map_err(RuntimeError::from)
+        .and_then(|xml| Ok(btcpp::import_source(&xml)?))
+        .and_then(|src| Ok(std::fs::write(&output, src)?));
+    match result {
+        Ok(_) => info!("the tree is imported to {:?}", output),
+        Err(RuntimeError::CompileError(err)) => {
+            error!("the import error occured: {}", err)
+        }
+        Err(err) => {
+            error!("the import error occured: {:?}", err)
+        }
+    }
+}
+
+/// collects the tree files in the folder and the subfolders
+fn tree_files(path: PathBuf, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.is_dir() || path.extension().map_or(false, |e| e == "tree") {
+                tree_files(path, files)?;
             }
         }
     } else {
-        error!("the simulation profile is required")
+        files.push(path);
     }
+    Ok(())
 }
-fn viz(matches: &ArgMatches) {
-    if let Some(p) = matches.get_one::<String>("profile") {
-        let pwd = std::env::current_dir().expect("the current directory is presented");
 
-        let root = match matches.get_one::<String>("root") {
-            Some(root) => buf(root.as_str(), pwd),
-            None => pwd,
-        };
+fn fmt(matches: &ArgMatches) {
+    let check = matches.get_flag("check");
+    let mut files = vec![];
+    let paths: Vec<&String> = matches
+        .get_many::<String>("PATH")
+        .map(|ps| ps.collect())
+        .unwrap_or_default();
+    let paths = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths.into_iter().map(PathBuf::from).collect()
+    };
+    for path in paths {
+        if let Err(err) = tree_files(path, &mut files) {
+            error!("the formatting error occured: {:?}", err);
+            std::process::exit(1);
+        }
+    }
 
-        let sim = buf(p, root.clone());
-        let main_file = matches
-            .get_one::<String>("main")
-            .map(|v| v.to_string())
-            .unwrap_or("main.tree".to_string());
-        let main_tree = matches.get_one::<String>("tree");
-
-        let mut sb = SimulatorBuilder::new();
-        sb.profile(sim);
-        sb.root(root);
-        sb.main_file(main_file);
-        if main_tree.is_some() {
-            sb.main_tree(main_tree.unwrap().to_string())
-        }
-
-        match sb.build() {
-            Ok(mut s) => match s.run() {
-                Ok(r) => {
-                    info!("the process is finished with the result: {:?}", r)
+    let mut failed = 0;
+    for file in files.iter() {
+        let result: RtResult<bool> = std::fs::read_to_string(file)
+            .map_err(RuntimeError::from)
+            .and_then(|src| {
+                let name = file.to_string_lossy();
+                let formatted = printer::format(name.as_ref(), &src)?;
+                // the file is written only if the formatted source is parsed into the same tree
+                let (before, _) = parse_file(name.as_ref(), &src);
+                let (after, errors) = parse_file(name.as_ref(), &formatted);
+                if !errors.is_empty() || after.without_spans() != before.without_spans() {
+                    return Err(TreeError::ParseError(format!(
+                        "the formatted source of {} is parsed into another tree, the file is left as it is",
+                        file.display()
+                    ))
+                    .into());
                 }
-                Err(err) => {
-                    error!("the runtime error occured : {:?}", err)
+                let changed = formatted != src;
+                if changed && !check {
+                    std::fs::write(file, formatted)?;
                 }
-            },
+                Ok(changed)
+            });
+        match result {
+            Ok(true) if check => {
+                println!("{} is not formatted", file.display());
+                failed += 1;
+            }
+            Ok(_) => {}
+            Err(RuntimeError::CompileError(err)) => {
+                error!("the file is not formatted:\n{}", err);
+                failed += 1;
+            }
             Err(err) => {
-                error!("the building error occured: {:?}", err)
+                error!("the formatting error occured: {:?}", err);
+                failed += 1;
             }
         }
-    } else {
-        error!("the simulation profile is required")
+    }
+    info!("{} files are checked, {} failed", files.len(), failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn lint(matches: &ArgMatches) {
+    match manifest(matches).and_then(|m| Ok(m.lint()?)) {
+        Ok(lints) => {
+            for lint in lints.iter() {
+                println!("{lint}\n");
+            }
+            info!("the analysis is finished with {} warnings", lints.len())
+        }
+        Err(RuntimeError::CompileError(err)) => {
+            error!("the building error occured:\n{}", err)
+        }
+        Err(err) => {
+            error!("the analysis error occured: {:?}", err)
+        }
     }
 }
 
@@ -138,8 +308,20 @@ fn main() {
         Some(("sim", args)) => {
             sim(args);
         }
-        Some(("viz", args)) => {
-            viz(args);
+        Some(("vis", args)) => {
+            vis(args);
+        }
+        Some(("export", args)) => {
+            export(args);
+        }
+        Some(("import", args)) => {
+            import(args);
+        }
+        Some(("fmt", args)) => {
+            fmt(args);
+        }
+        Some(("lint", args)) => {
+            lint(args);
         }
         Some((e, _)) => {
             error!("the command '{e}' does not match the expected commands. ");